@@ -9,7 +9,9 @@ import { SimplifiedConfigCard } from "./components/SimplifiedConfigCard";
 import { ChangeInputCard } from "./components/ChangeInputCard";
 import { GeneratedChangeCard } from "./components/GeneratedChangeCard";
 import { ChangeCommandExamplesModal } from "./components/ChangeCommandExamplesModal";
+import type { EditorDiagnostic } from "./components/CodeMirrorTextarea";
 import { demoBaseConfig, demoChangeInput } from "./demoData";
+import { splitChangeDiagnostics } from "./changeDiagnostics";
 
 const wasm = wasmModule;
 
@@ -28,15 +30,50 @@ function App() {
   const hasLintIssues = lintOutput.length > 0;
   const changeResult = useMemo(() => {
     if (changeInput.trim().length === 0) {
-      return { changeOutput: "", errorMessage: "" };
+      return {
+        changeOutput: "",
+        rollbackOutput: "",
+        appliedSimplifiedConfig: "",
+        errorMessage: "",
+        warningMessage: "",
+        diagnostics: [] as EditorDiagnostic[],
+      };
     }
 
     try {
-      const result = wasm.generate_change_config(src, changeInput);
-      return { changeOutput: result.changeOutput, errorMessage: "" };
+      const result = wasm.generate_change_report(src, changeInput);
+      const { errorMessage, warningMessage } = splitChangeDiagnostics(result.diagnostics);
+      const diagnostics: EditorDiagnostic[] = result.diagnostics.flatMap((diag) =>
+        diag.line !== undefined
+          ? [
+              {
+                line: diag.line,
+                colStart: diag.colStart ?? 0,
+                colEnd: diag.colEnd ?? 0,
+                severity: diag.severity,
+                message: diag.message,
+              },
+            ]
+          : [],
+      );
+      return {
+        changeOutput: result.changeOutput,
+        rollbackOutput: result.rollbackOutput,
+        appliedSimplifiedConfig: result.appliedSimplifiedConfig,
+        errorMessage,
+        warningMessage,
+        diagnostics,
+      };
     } catch (error) {
       const errorMessage = error instanceof Error ? error.message : String(error);
-      return { changeOutput: "", errorMessage };
+      return {
+        changeOutput: "",
+        rollbackOutput: "",
+        appliedSimplifiedConfig: "",
+        errorMessage,
+        warningMessage: "",
+        diagnostics: [] as EditorDiagnostic[],
+      };
     }
   }, [src, changeInput]);
   const openConfigModal = () => {
@@ -50,6 +87,8 @@ function App() {
       ? "Lint指摘をすべて解消すると、簡略化されたconfigが表示されます。"
       : "変換結果がここに表示されます。";
   const simplifiedConfig = !isConfigEmpty && !hasLintIssues ? currentConfig.simplifiedConfig : "";
+  const appliedSimplifiedConfig =
+    !isConfigEmpty && !hasLintIssues ? changeResult.appliedSimplifiedConfig : "";
 
   return (
     <>
@@ -65,20 +104,24 @@ function App() {
               value={changeInput}
               onChange={setChangeInput}
               onOpenExamples={() => setExampleModalOpen(true)}
+              diagnostics={changeResult.diagnostics}
             />
           </Box>
 
           <Flex direction="column" flex={1} gap="xl" miw={0}>
             <Box flex={1} mih={0}>
               <GeneratedChangeCard
-                value={changeResult.changeOutput}
+                changeValue={changeResult.changeOutput}
+                rollbackValue={changeResult.rollbackOutput}
                 errorMessage={changeResult.errorMessage}
+                warningMessage={changeResult.warningMessage}
               />
             </Box>
 
             <Box flex={1} mih={0}>
               <SimplifiedConfigCard
                 value={simplifiedConfig}
+                appliedValue={appliedSimplifiedConfig}
                 placeholderMessage={simplifiedPlaceholderMessage}
                 onOpenConfigModal={openConfigModal}
               />
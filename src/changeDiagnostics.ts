@@ -0,0 +1,26 @@
+type ChangeDiagnostic = {
+  severity: string;
+  message: string;
+  line?: number;
+};
+
+export type ChangeMessages = {
+  errorMessage: string;
+  warningMessage: string;
+};
+
+function formatDiagnostic(diag: ChangeDiagnostic) {
+  return diag.line !== undefined ? `${diag.message}（${diag.line}行目）` : diag.message;
+}
+
+export function splitChangeDiagnostics(diagnostics: ChangeDiagnostic[]): ChangeMessages {
+  const messagesOf = (isError: boolean) =>
+    diagnostics
+      .filter((diag) => (diag.severity === "error") === isError)
+      .map(formatDiagnostic)
+      .join("\n");
+  return {
+    errorMessage: messagesOf(true),
+    warningMessage: messagesOf(false),
+  };
+}
@@ -1,11 +1,13 @@
 import { Button, Flex, Group, Paper, Text } from "@mantine/core";
 import type { Completion, CompletionSource } from "@codemirror/autocomplete";
 import { CodeMirrorTextarea } from "./CodeMirrorTextarea";
+import type { EditorDiagnostic } from "./CodeMirrorTextarea";
 
 type ChangeInputCardProps = {
   value: string;
   onChange: (value: string) => void;
   onOpenExamples: () => void;
+  diagnostics?: EditorDiagnostic[];
 };
 
 const changeCommandCompletions: Completion[] = [
@@ -105,7 +107,12 @@ const changeCommandCompletionSource: CompletionSource = (context) => {
   };
 };
 
-export function ChangeInputCard({ value, onChange, onOpenExamples }: ChangeInputCardProps) {
+export function ChangeInputCard({
+  value,
+  onChange,
+  onOpenExamples,
+  diagnostics,
+}: ChangeInputCardProps) {
   return (
     <Paper withBorder radius="md" p="lg" h="100%">
       <Flex direction="column" h="100%" gap="sm">
@@ -121,6 +128,7 @@ export function ChangeInputCard({ value, onChange, onOpenExamples }: ChangeInput
           onChange={onChange}
           showLineNumbers
           completionSource={changeCommandCompletionSource}
+          diagnostics={diagnostics}
         />
       </Flex>
     </Paper>
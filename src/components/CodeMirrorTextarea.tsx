@@ -3,7 +3,15 @@ import { useComputedColorScheme } from "@mantine/core";
 import CodeMirror from "@uiw/react-codemirror";
 import { autocompletion } from "@codemirror/autocomplete";
 import type { CompletionSource } from "@codemirror/autocomplete";
-import { EditorView, lineNumbers } from "@codemirror/view";
+import { Decoration, EditorView, lineNumbers } from "@codemirror/view";
+
+export type EditorDiagnostic = {
+  line: number;
+  colStart: number;
+  colEnd: number;
+  severity: string;
+  message: string;
+};
 
 export type CodeMirrorTextareaProps = {
   value: string;
@@ -12,8 +20,30 @@ export type CodeMirrorTextareaProps = {
   placeholder?: string;
   showLineNumbers?: boolean;
   completionSource?: CompletionSource;
+  diagnostics?: EditorDiagnostic[];
 };
 
+function diagnosticDecorations(diagnostics: EditorDiagnostic[]) {
+  return EditorView.decorations.of((view) => {
+    const doc = view.state.doc;
+    const ranges = diagnostics.flatMap((diag) => {
+      if (diag.line < 1 || diag.line > doc.lines) {
+        return [];
+      }
+      const line = doc.line(diag.line);
+      const from = Math.min(line.from + diag.colStart, line.to);
+      const to = Math.min(line.from + diag.colEnd, line.to);
+      const spec = { class: `cm-diagnostic-${diag.severity}`, attributes: { title: diag.message } };
+      // 列情報がない場合は行全体を強調する
+      if (from >= to) {
+        return [Decoration.line(spec).range(line.from)];
+      }
+      return [Decoration.mark(spec).range(from, to)];
+    });
+    return Decoration.set(ranges, true);
+  });
+}
+
 export function CodeMirrorTextarea({
   value,
   onChange,
@@ -21,6 +51,7 @@ export function CodeMirrorTextarea({
   placeholder,
   showLineNumbers = false,
   completionSource,
+  diagnostics,
 }: CodeMirrorTextareaProps) {
   const colorScheme = useComputedColorScheme("light");
 
@@ -63,6 +94,15 @@ export function CodeMirrorTextarea({
           ".cm-tooltip-autocomplete ul li[aria-selected]": {
             backgroundColor: "var(--mantine-primary-color-light)",
           },
+          ".cm-diagnostic-error": {
+            textDecoration: "underline wavy var(--mantine-color-red-6)",
+          },
+          ".cm-diagnostic-warning": {
+            textDecoration: "underline wavy var(--mantine-color-yellow-6)",
+          },
+          ".cm-diagnostic-info": {
+            textDecoration: "underline dotted var(--mantine-color-blue-6)",
+          },
         },
         { dark: colorScheme === "dark" },
       ),
@@ -82,8 +122,11 @@ export function CodeMirrorTextarea({
         }),
       );
     }
+    if (diagnostics && diagnostics.length > 0) {
+      base.push(diagnosticDecorations(diagnostics));
+    }
     return base;
-  }, [showLineNumbers, themeExtension, completionSource]);
+  }, [showLineNumbers, themeExtension, completionSource, diagnostics]);
 
   return (
     <CodeMirror
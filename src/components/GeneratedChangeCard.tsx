@@ -1,17 +1,51 @@
-import { Alert, Box, Flex, Paper, Text, ActionIcon, Tooltip } from "@mantine/core";
-import { IconAlertCircle, IconCopy } from "@tabler/icons-react";
+import {
+  Alert,
+  Box,
+  Flex,
+  Group,
+  Paper,
+  SegmentedControl,
+  Text,
+  ActionIcon,
+  Tooltip,
+} from "@mantine/core";
+import { IconAlertCircle, IconAlertTriangle, IconCopy } from "@tabler/icons-react";
+import { useState } from "react";
 import { CodeMirrorTextarea } from "./CodeMirrorTextarea";
 
 type GeneratedChangeCardProps = {
-  value: string;
+  changeValue: string;
+  rollbackValue: string;
   errorMessage: string;
+  warningMessage: string;
 };
 
-export function GeneratedChangeCard({ value, errorMessage }: GeneratedChangeCardProps) {
+type OutputKind = "change" | "rollback";
+
+export function GeneratedChangeCard({
+  changeValue,
+  rollbackValue,
+  errorMessage,
+  warningMessage,
+}: GeneratedChangeCardProps) {
+  const [kind, setKind] = useState<OutputKind>("change");
+  const value = kind === "change" ? changeValue : rollbackValue;
+
   return (
     <Paper withBorder radius="md" p="lg" h="100%">
       <Flex direction="column" h="100%" gap="sm">
-        <Text fw={600}>コピペ可能な設定</Text>
+        <Group justify="space-between">
+          <Text fw={600}>コピペ可能な設定</Text>
+          <SegmentedControl
+            size="xs"
+            value={kind}
+            onChange={(next) => setKind(next as OutputKind)}
+            data={[
+              { label: "変更", value: "change" },
+              { label: "切り戻し", value: "rollback" },
+            ]}
+          />
+        </Group>
         {errorMessage && (
           <Alert
             variant="light"
@@ -20,7 +54,22 @@ export function GeneratedChangeCard({ value, errorMessage }: GeneratedChangeCard
             icon={<IconAlertCircle size={16} />}
             title="生成に失敗しました"
           >
-            <Text size="sm">{errorMessage}</Text>
+            <Text size="sm" style={{ whiteSpace: "pre-line" }}>
+              {errorMessage}
+            </Text>
+          </Alert>
+        )}
+        {warningMessage && (
+          <Alert
+            variant="light"
+            color="yellow"
+            radius="md"
+            icon={<IconAlertTriangle size={16} />}
+            title="警告があります"
+          >
+            <Text size="sm" style={{ whiteSpace: "pre-line" }}>
+              {warningMessage}
+            </Text>
           </Alert>
         )}
         <Box pos="relative" flex={1} mih={0}>
@@ -1,28 +1,47 @@
-import { Box, Button, Flex, Group, Paper, Text } from "@mantine/core";
+import { Box, Button, Flex, Group, Paper, SegmentedControl, Text } from "@mantine/core";
+import { useState } from "react";
 import { CodeMirrorTextarea } from "./CodeMirrorTextarea";
 
 type SimplifiedConfigCardProps = {
   placeholderMessage: string;
   value: string;
+  appliedValue: string;
   onOpenConfigModal: () => void;
 };
 
+type ConfigView = "current" | "applied";
+
 export function SimplifiedConfigCard({
   placeholderMessage,
   value,
+  appliedValue,
   onOpenConfigModal,
 }: SimplifiedConfigCardProps) {
+  const [view, setView] = useState<ConfigView>("current");
+  const shownValue = view === "current" ? value : appliedValue;
+
   return (
     <Paper withBorder radius="md" p="lg" h="100%">
       <Flex direction="column" h="100%" gap="sm">
         <Group justify="space-between" align="center">
-          <Text fw={600}>現在の設定</Text>
-          <Button size="xs" variant="light" onClick={onOpenConfigModal}>
-            Import Config
-          </Button>
+          <Text fw={600}>{view === "current" ? "現在の設定" : "変更後の設定"}</Text>
+          <Group gap="xs">
+            <SegmentedControl
+              size="xs"
+              value={view}
+              onChange={(next) => setView(next as ConfigView)}
+              data={[
+                { label: "変更前", value: "current" },
+                { label: "変更後", value: "applied" },
+              ]}
+            />
+            <Button size="xs" variant="light" onClick={onOpenConfigModal}>
+              Import Config
+            </Button>
+          </Group>
         </Group>
         <Box flex={1} mih={0}>
-          <CodeMirrorTextarea value={value} readOnly placeholder={placeholderMessage} />
+          <CodeMirrorTextarea value={shownValue} readOnly placeholder={placeholderMessage} />
         </Box>
       </Flex>
     </Paper>
@@ -0,0 +1,105 @@
+import { it, expect } from "vitest";
+import { wasm } from "./helpers";
+
+it("converts a trunk port to an access port", () => {
+  const baseConfig = `
+interface FortyGigE0/0/0/46
+  description To:server1
+`.trim();
+
+  const changeInput = `
+vlan database
+  vlan 300 name test
+
+interface FortyGigE0/0/0/46
+  description To:server1
+  switchport mode access
+  switchport access vlan 300
+`.trim();
+
+  const result = wasm.generate_change_config(baseConfig, changeInput);
+
+  expect(result.changeOutput).toEqual(
+    [
+      "no interface FortyGigE0/0/0/46",
+      "interface FortyGigE0/0/0/46 l2transport",
+      "  description To:server1",
+      "exit",
+      "",
+      "l2vpn",
+      "  bridge group VLAN",
+      "    bridge-domain VLAN300",
+      "      description test",
+      "      interface FortyGigE0/0/0/46",
+      "      exit",
+      "    exit",
+      "  exit",
+      "exit",
+    ].join("\n") + "\n",
+  );
+  expect(result.appliedSimplifiedConfig).toContain(
+    "  switchport mode access\n  switchport access vlan 300",
+  );
+});
+
+it("shows access ports in the simplified config", () => {
+  const baseConfig = `
+interface FortyGigE0/0/0/46 l2transport
+ description To:server1
+!
+l2vpn
+ bridge group VLAN
+  bridge-domain VLAN300
+   description test
+   interface FortyGigE0/0/0/46
+  !
+ !
+!
+`.trim();
+
+  const result = wasm.analyze_config(baseConfig);
+
+  expect(result.simplifiedConfig).toEqual(
+    [
+      "interface FortyGigE0/0/0/46",
+      "  description To:server1",
+      "  switchport mode access",
+      "  switchport access vlan 300",
+      "",
+      "vlan database",
+      "  vlan 300 name test",
+    ].join("\n"),
+  );
+});
+
+it("requires an access VLAN when converting to access mode", () => {
+  const baseConfig = `
+interface FortyGigE0/0/0/46
+  description To:server1
+`.trim();
+
+  const changeInput = `
+interface FortyGigE0/0/0/46
+  switchport mode access
+`.trim();
+
+  expect(() => wasm.generate_change_config(baseConfig, changeInput)).toThrow(
+    /accessモードにするにはaccess VLANが必要です/,
+  );
+});
+
+it("rejects unsupported switchport modes", () => {
+  const baseConfig = `
+interface FortyGigE0/0/0/46
+  description To:server1
+`.trim();
+
+  const changeInput = `
+interface FortyGigE0/0/0/46
+  switchport mode dynamic
+`.trim();
+
+  expect(() => wasm.generate_change_config(baseConfig, changeInput)).toThrow(
+    /switchport mode dynamic はサポートされていません/,
+  );
+});
@@ -0,0 +1,53 @@
+import { it, expect } from "vitest";
+import { wasm } from "./helpers";
+
+it("renders the full and simplified config after the change", () => {
+  const baseConfig = `
+interface FortyGigE0/0/0/46
+ description To:server1
+!
+interface FortyGigE0/0/0/46.300 l2transport
+ description test,To:server1
+ encapsulation dot1q 300
+ rewrite ingress tag pop 1 symmetric
+!
+l2vpn
+ bridge group VLAN
+  bridge-domain VLAN300
+   description test
+   interface FortyGigE0/0/0/46.300
+  !
+ !
+!
+`.trim();
+
+  const changeInput = `
+vlan database
+  vlan 400 name web
+
+interface FortyGigE0/0/0/46
+  switchport trunk allowed vlan add 400
+`.trim();
+
+  const result = wasm.generate_change_config(baseConfig, changeInput);
+
+  expect(result.appliedConfig).toContain(
+    `
+interface FortyGigE0/0/0/46.400 l2transport
+ description web,To:server1
+ encapsulation dot1q 400
+ rewrite ingress tag pop 1 symmetric
+!
+`.trim(),
+  );
+  expect(result.appliedConfig).toContain(
+    `
+  bridge-domain VLAN400
+   description web
+   interface FortyGigE0/0/0/46.400
+  !
+`.trim(),
+  );
+  expect(result.appliedSimplifiedConfig).toContain("switchport trunk allowed vlan 300 400");
+  expect(result.appliedSimplifiedConfig).toContain("vlan 400 name web");
+});
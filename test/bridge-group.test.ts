@@ -0,0 +1,87 @@
+import { it, expect } from "vitest";
+import { wasm } from "./helpers";
+
+const baseConfig = `
+interface FortyGigE0/0/0/46
+ description To:server1
+!
+interface FortyGigE0/0/0/47
+ description To:server2
+!
+interface FortyGigE0/0/0/47.500 l2transport
+ description web,To:server2
+ encapsulation dot1q 500
+ rewrite ingress tag pop 1 symmetric
+!
+l2vpn
+ bridge group CUSTOMER
+  bridge-domain web-servers
+   description web
+   interface FortyGigE0/0/0/47.500
+  !
+ !
+!
+`.trim();
+
+it("maps named bridge-domains to VLANs through member encapsulations", () => {
+  const result = wasm.analyze_config(baseConfig);
+
+  expect(
+    result.domains.map((d) => [d.bridgeGroup, d.name, d.vlanTag]),
+  ).toEqual([["CUSTOMER", "web-servers", 500]]);
+  expect(result.simplifiedConfig).toContain("  vlan 500 name web");
+});
+
+it("edits existing bridge groups and creates new domains in the default group", () => {
+  const changeInput = `
+vlan database
+  vlan 600 name db
+
+interface FortyGigE0/0/0/46
+  switchport trunk allowed vlan add 500
+  switchport trunk allowed vlan add 600
+`.trim();
+
+  const result = wasm.generate_change_config(
+    baseConfig,
+    changeInput,
+    undefined,
+    "TENANT",
+  );
+
+  expect(result.changeOutput).toContain(
+    [
+      "l2vpn",
+      "  bridge group CUSTOMER",
+      "    bridge-domain web-servers",
+      "      interface FortyGigE0/0/0/46.500",
+      "      exit",
+      "    exit",
+      "  exit",
+      "  bridge group TENANT",
+      "    bridge-domain VLAN600",
+      "      description db",
+      "      interface FortyGigE0/0/0/46.600",
+      "      exit",
+      "    exit",
+      "  exit",
+      "exit",
+    ].join("\n"),
+  );
+});
+
+it("rejects bridge group names that cannot form an l2vpn command", () => {
+  const changeInput = `
+vlan database
+  vlan 600 name db
+`.trim();
+
+  for (const name of ["", "MY GROUP"]) {
+    expect(() =>
+      wasm.generate_change_config(baseConfig, changeInput, undefined, name),
+    ).toThrow(`invalid bridge group \`${name}\``);
+    expect(() =>
+      wasm.generate_change_report(baseConfig, changeInput, undefined, name),
+    ).toThrow(`invalid bridge group \`${name}\``);
+  }
+});
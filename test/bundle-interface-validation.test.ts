@@ -114,3 +114,47 @@ interface FortyGigE0/0/0/2
     expect(errorStr).toContain("Bundle-Ether200");
   }
 });
+
+it("adds members to a bundle with channel-group", () => {
+  const baseConfig = `
+interface Bundle-Ether100
+  description uplink
+interface HundredGigE0/0/0/10
+  description uplink-1
+  bundle id 100 mode active
+interface HundredGigE0/0/0/11
+  description uplink-2
+`.trim();
+
+  const changeInput = `
+interface HundredGigE0/0/0/11
+  channel-group 100 mode active
+`.trim();
+
+  const result = wasm.generate_change_config(baseConfig, changeInput);
+  expect(result.changeOutput).toEqual(
+    ["interface HundredGigE0/0/0/11", "  bundle id 100 mode active", "exit"].join("\n") +
+      "\n",
+  );
+});
+
+it("rejects bundle members of a different speed", () => {
+  const baseConfig = `
+interface Bundle-Ether100
+  description uplink
+interface HundredGigE0/0/0/10
+  description uplink-1
+  bundle id 100 mode active
+interface FortyGigE0/0/0/1
+  description uplink-2
+`.trim();
+
+  const changeInput = `
+interface FortyGigE0/0/0/1
+  channel-group 100 mode active
+`.trim();
+
+  expect(() => wasm.generate_change_config(baseConfig, changeInput)).toThrow(
+    /FortyGigE0\/0\/0\/1（FortyGigE）はBundle-Ether100の他のメンバー（HundredGigE）と速度が異なります/,
+  );
+});
@@ -0,0 +1,41 @@
+import { it, expect } from "vitest";
+import { wasm } from "./helpers";
+
+it("derives change input from an edited simplified config", () => {
+  const baseConfig = `
+interface FortyGigE0/0/0/46
+  description To:server1
+interface FortyGigE0/0/0/46.300 l2transport
+  description test,To:server1
+  encapsulation dot1q 300
+  rewrite ingress tag pop 1 symmetric
+
+l2vpn
+  bridge group VLAN
+    bridge-domain VLAN300
+      description test
+      interface FortyGigE0/0/0/46.300
+`.trim();
+
+  const original = wasm.analyze_config(baseConfig).simplifiedConfig;
+  const edited = original
+    .replace("switchport trunk allowed vlan 300", "switchport trunk allowed vlan 400")
+    .concat("\n  vlan 400 name web\n");
+
+  const changeInput = wasm.generate_change_input(original, edited);
+
+  expect(changeInput).toBe(
+    `
+vlan database
+  vlan 400 name web
+
+interface FortyGigE0/0/0/46
+  switchport trunk allowed vlan add 400
+  switchport trunk allowed vlan remove 300
+`.trimStart(),
+  );
+
+  const result = wasm.generate_change_config(baseConfig, changeInput);
+  expect(result.changeOutput).toContain("interface FortyGigE0/0/0/46.400 l2transport");
+  expect(result.changeOutput).toContain("no interface FortyGigE0/0/0/46.300 l2transport");
+});
@@ -0,0 +1,36 @@
+import { it, expect } from "vitest";
+import { wasm } from "./helpers";
+import { splitChangeDiagnostics } from "../src/changeDiagnostics";
+
+it("shows warnings of a successful change apart from errors", () => {
+  const baseConfig = `
+interface FortyGigE0/0/0/46
+  description To:server1
+  shutdown
+`.trim();
+
+  const changeInput = `
+vlan database
+  vlan 300 name web
+
+interface FortyGigE0/0/0/46
+  switchport trunk allowed vlan add 300
+`.trim();
+
+  const result = wasm.generate_change_report(baseConfig, changeInput);
+  const { errorMessage, warningMessage } = splitChangeDiagnostics(result.diagnostics);
+
+  expect(result.changeOutput).toContain("interface FortyGigE0/0/0/46.300 l2transport");
+  expect(errorMessage).toEqual("");
+  expect(warningMessage).toContain("（5行目）");
+});
+
+it("keeps errors in the error message", () => {
+  const { errorMessage, warningMessage } = splitChangeDiagnostics([
+    { severity: "error", message: "VLAN 100 is not present", line: 2 },
+    { severity: "warning", message: "port is shut down" },
+  ]);
+
+  expect(errorMessage).toEqual("VLAN 100 is not present（2行目）");
+  expect(warningMessage).toEqual("port is shut down");
+});
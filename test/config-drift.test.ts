@@ -0,0 +1,38 @@
+import { it, expect } from "vitest";
+import { wasm } from "./helpers";
+
+const oldConfig = `
+interface FortyGigE0/0/0/46
+ description To:server1
+!
+interface FortyGigE0/0/0/46.300 l2transport
+ description servers,To:server1
+ encapsulation dot1q 300
+ rewrite ingress tag pop 1 symmetric
+!
+l2vpn
+ bridge group VLAN
+  bridge-domain VLAN300
+   description servers
+   interface FortyGigE0/0/0/46.300
+  !
+ !
+!
+`.trim();
+
+it("reports no drift for reordered but equivalent configs", () => {
+  const reordered = oldConfig.replace(
+    "   description servers\n   interface FortyGigE0/0/0/46.300",
+    "   interface FortyGigE0/0/0/46.300\n   description servers",
+  );
+  expect(wasm.diff_running_configs(oldConfig, reordered)).toBe("");
+});
+
+it("matches blocks by header and summarizes VLAN changes", () => {
+  const newConfig = oldConfig.replace("description To:server1\n!", "description To:server2\n!");
+  const diff = wasm.diff_running_configs(oldConfig, newConfig);
+
+  expect(diff).toContain("~ interface FortyGigE0/0/0/46 (1 -> 1)");
+  expect(diff).toContain("~ description To:server1 => description To:server2 (2 -> 2)");
+  expect(diff).not.toContain("VLAN summary");
+});
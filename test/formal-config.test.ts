@@ -0,0 +1,59 @@
+import { it, expect } from "vitest";
+import { wasm } from "./helpers";
+
+const formalConfig = `
+interface FortyGigE0/0/0/46 description To:server1
+interface FortyGigE0/0/0/46.300 l2transport description servers,To:server1
+interface FortyGigE0/0/0/46.300 l2transport encapsulation dot1q 300
+interface FortyGigE0/0/0/46.300 l2transport rewrite ingress tag pop 1 symmetric
+l2vpn bridge group VLAN bridge-domain VLAN300 description servers
+l2vpn bridge group VLAN bridge-domain VLAN300 interface FortyGigE0/0/0/46.300
+`.trim();
+
+it("analyzes show running-config formal output", () => {
+  const result = wasm.analyze_config(formalConfig);
+
+  expect(result.lintOutput).toBe("");
+  expect(result.simplifiedConfig).toContain("switchport trunk allowed vlan 300");
+});
+
+it("generates changes against a formal base config", () => {
+  const changeInput = `
+interface FortyGigE0/0/0/46
+  switchport trunk allowed vlan remove 300
+`.trim();
+
+  const result = wasm.generate_change_config(formalConfig, changeInput);
+
+  expect(result.changeOutput).toContain("no interface FortyGigE0/0/0/46.300 l2transport");
+  expect(result.appliedConfig).toBe(
+    [
+      "interface FortyGigE0/0/0/46 description To:server1",
+      "l2vpn bridge group VLAN bridge-domain VLAN300 description servers",
+      "",
+    ].join("\n"),
+  );
+});
+
+it("converts an indented config to the formal format", () => {
+  const config = `
+interface FortyGigE0/0/0/46
+ description To:server1
+!
+l2vpn
+ bridge group VLAN
+  bridge-domain VLAN300
+   interface FortyGigE0/0/0/46.300
+  !
+ !
+!
+`.trim();
+
+  expect(wasm.format_config_formal(config)).toBe(
+    [
+      "interface FortyGigE0/0/0/46 description To:server1",
+      "l2vpn bridge group VLAN bridge-domain VLAN300 interface FortyGigE0/0/0/46.300",
+      "",
+    ].join("\n"),
+  );
+});
@@ -0,0 +1,34 @@
+import { it, expect } from "vitest";
+import { wasm } from "./helpers";
+
+it("reads tab-indented configuration", () => {
+  const config = [
+    "interface FortyGigE0/0/0/46",
+    "\tdescription To:server1",
+  ].join("\n");
+
+  const result = wasm.analyze_config(config);
+
+  expect(result.lintOutput).toBe("");
+  expect(result.simplifiedConfig).toEqual(
+    ["interface FortyGigE0/0/0/46", "  description To:server1"].join("\n"),
+  );
+});
+
+it("warns about inconsistent indentation and keeps the block", () => {
+  const config = [
+    "interface FortyGigE0/0/0/46",
+    "    description To:server1",
+    "  mtu 9216",
+  ].join("\n");
+
+  const diagnostics = wasm.lint_config_diagnostics(config, "en");
+
+  expect(diagnostics).toHaveLength(1);
+  const [diag] = diagnostics;
+  expect(diag.code).toEqual("inconsistent-indentation");
+  expect(diag.severity).toEqual("warning");
+  expect(diag.line).toEqual(3);
+  expect(diag.colStart).toEqual(2);
+  expect(diag.colEnd).toEqual(10);
+});
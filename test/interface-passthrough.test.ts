@@ -77,3 +77,39 @@ interface FortyGigE0/0/0/1
   expect(result.changeOutput).toContain("  mru 9216");
   expect(result.changeOutput).toContain("interface FortyGigE0/0/0/1.200 l2transport");
 });
+
+it("updates existing subinterface descriptions with the interface description", () => {
+  const baseConfig = `
+interface FortyGigE0/0/0/1
+  description To:server1
+interface FortyGigE0/0/0/1.100 l2transport
+  description test,To:server1
+  encapsulation dot1q 100
+  rewrite ingress tag pop 1 symmetric
+
+l2vpn
+  bridge group VLAN
+    bridge-domain VLAN100
+      description test
+      interface FortyGigE0/0/0/1.100
+`.trim();
+
+  const changeInput = `
+interface FortyGigE0/0/0/1
+  description To:server2
+`.trim();
+
+  const result = wasm.generate_change_config(baseConfig, changeInput);
+
+  expect(result.changeOutput).toEqual(
+    [
+      "interface FortyGigE0/0/0/1",
+      "  description To:server2",
+      "exit",
+      "",
+      "interface FortyGigE0/0/0/1.100 l2transport",
+      "  description test,To:server2",
+      "exit",
+    ].join("\n") + "\n",
+  );
+});
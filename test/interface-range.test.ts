@@ -0,0 +1,63 @@
+import { it, expect } from "vitest";
+import { wasm } from "./helpers";
+
+it("applies an interface range to every port", () => {
+  const baseConfig = `
+interface FortyGigE0/0/0/10
+  description To:server10
+interface FortyGigE0/0/0/11
+  description To:server11
+interface FortyGigE0/0/0/20
+  description To:server20
+`.trim();
+
+  const changeInput = `
+vlan database
+  vlan 300 name web
+
+interface range FortyGigE0/0/0/10 - 11, FortyGigE0/0/0/20
+  switchport trunk allowed vlan add 300
+`.trim();
+
+  const result = wasm.generate_change_config(baseConfig, changeInput);
+
+  for (const port of ["10", "11", "20"]) {
+    expect(result.changeOutput).toContain(
+      `interface FortyGigE0/0/0/${port}.300 l2transport\n  description web,To:server${port}\n`,
+    );
+  }
+});
+
+it("reports problems in an interface range once on its line", () => {
+  const baseConfig = `
+interface FortyGigE0/0/0/10
+  description To:server10
+interface FortyGigE0/0/0/11
+  description To:server11
+`.trim();
+
+  const changeInput = `
+interface range FortyGigE0/0/0/10 - 11
+  switchport trunk allowed vlan add 999
+`.trim();
+
+  const result = wasm.generate_change_report(baseConfig, changeInput);
+
+  expect(result.diagnostics).toHaveLength(1);
+  expect(result.diagnostics[0].code).toEqual("vlan-not-defined");
+  expect(result.diagnostics[0].line).toEqual(2);
+});
+
+it("rejects malformed interface ranges", () => {
+  const changeInput = `
+interface range FortyGigE0/0/0/20 - 10
+  switchport trunk allowed vlan add 300
+`.trim();
+
+  const result = wasm.generate_change_report("", changeInput);
+
+  expect(result.diagnostics.map((d: { code: string }) => d.code)).toEqual([
+    "invalid-interface-range",
+  ]);
+  expect(result.diagnostics[0].line).toEqual(1);
+});
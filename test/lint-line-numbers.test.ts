@@ -0,0 +1,38 @@
+import { it, expect } from "vitest";
+import { wasm } from "./helpers";
+
+it("reports base config lint findings with line numbers", () => {
+  const config = `
+interface FortyGigE0/0/0/46
+  description To:server1
+interface FortyGigE0/0/0/46.300 l2transport
+  description servers,To:server1
+  encapsulation dot1q 301
+  rewrite ingress tag pop 1 symmetric
+
+l2vpn
+  bridge group VLAN
+    bridge-domain VLAN300
+      description servers
+      interface FortyGigE0/0/0/46.300
+      interface FortyGigE0/0/0/47.310
+`.trim();
+
+  const lintOutput = wasm.analyze_config(config).lintOutput;
+
+  expect(lintOutput).toEqual(
+    [
+      "--- interface FortyGigE0/0/0/46.300 l2transport ---",
+      "sub-interface number と encapsulation tag が一致していない: FortyGigE0/0/0/46.300（5行目）",
+      "--- bridge-domain VLAN300 ---",
+      "sub-interface number がブリッジ名と異なる: FortyGigE0/0/0/47.310（13行目）",
+      "",
+    ].join("\n"),
+  );
+
+  const diagnostics = wasm.lint_config_diagnostics(config);
+  expect(diagnostics.map((d) => [d.code, d.line, d.colStart, d.colEnd])).toEqual([
+    ["encapsulation-mismatch", 5, 2, 25],
+    ["subinterface-number-mismatch", 13, 6, 37],
+  ]);
+});
@@ -0,0 +1,36 @@
+import { it, expect } from "vitest";
+import { wasm } from "./helpers";
+
+const baseConfig = `
+interface FortyGigE0/0/0/46
+  description To:server1
+interface FortyGigE0/0/0/46.300 l2transport
+  description test,To:server1
+  encapsulation dot1q 301
+  rewrite ingress tag pop 1 symmetric
+`.trim();
+
+const changeInput = `
+interface FortyGigE0/0/0/46
+  switchport trunk allowed vlan remove 100
+`.trim();
+
+it("renders change errors in English when requested", () => {
+  expect(() => wasm.generate_change_config(baseConfig, changeInput, "en")).toThrow(
+    /Cannot remove VLAN 100 from interface FortyGigE0\/0\/0\/46.*\(line 2\)/,
+  );
+});
+
+it("keeps Japanese as the default locale", () => {
+  expect(() => wasm.generate_change_config(baseConfig, changeInput)).toThrow(
+    /VLAN 100を削除できません（2行目）/,
+  );
+});
+
+it("renders lint findings in English when requested", () => {
+  const lintOutput = wasm.analyze_config(baseConfig, "en-US").lintOutput;
+  expect(lintOutput).toContain("sub-interface number does not match the encapsulation tag");
+
+  const [diag] = wasm.lint_config_diagnostics(baseConfig, "en");
+  expect(diag.hint).toEqual("Make the encapsulation dot1q tag match the sub-interface number");
+});
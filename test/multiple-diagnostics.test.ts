@@ -0,0 +1,39 @@
+import { it, expect } from "vitest";
+import { wasm } from "./helpers";
+
+it("reports every problem in the change input at once", () => {
+  const baseConfig = `
+interface FortyGigE0/0/0/46
+  description To:server1
+interface FortyGigE0/0/0/46.300 l2transport
+  description test,To:server1
+  encapsulation dot1q 300
+  rewrite ingress tag pop 1 symmetric
+
+l2vpn
+  bridge group VLAN
+    bridge-domain VLAN300
+      description test
+      interface FortyGigE0/0/0/46.300
+`.trim();
+
+  const changeInput = `
+vlan database
+  vlan 400
+
+interface FortyGigE0/0/0/46
+  switchport trunk allowed vlan add 400 500
+  switchport trunk allowed vlan remove 100
+`.trim();
+
+  try {
+    wasm.generate_change_config(baseConfig, changeInput);
+    expect.fail("Expected an error to be thrown");
+  } catch (e: unknown) {
+    const lines = String(e).split("\n");
+    expect(lines).toHaveLength(3);
+    expect(lines[0]).toMatch(/VLAN名は必須です.*2行目/);
+    expect(lines[1]).toMatch(/VLAN 500 がvlan databaseまたはベース設定で定義されていません.*5行目/);
+    expect(lines[2]).toMatch(/VLAN 100を削除できません.*6行目/);
+  }
+});
@@ -0,0 +1,72 @@
+import { it, expect } from "vitest";
+import { wasm } from "./helpers";
+
+const baseConfig = `
+interface FortyGigE0/0/0/46
+  description To:server1
+interface FortyGigE0/0/0/46.300 l2transport
+  description web,To:server1
+  encapsulation untagged
+
+l2vpn
+  bridge group VLAN
+    bridge-domain VLAN300
+      description web
+      interface FortyGigE0/0/0/46.300
+`.trim();
+
+it("shows untagged subinterfaces as the native VLAN", () => {
+  const result = wasm.analyze_config(baseConfig);
+
+  expect(result.lintOutput).toEqual("");
+  expect(result.simplifiedConfig).toContain(
+    "  description To:server1\n  switchport trunk native vlan 300",
+  );
+});
+
+it("creates an untagged subinterface for a new native VLAN", () => {
+  const changeInput = `
+vlan database
+  vlan 400 name mgmt
+
+interface FortyGigE0/0/0/46
+  switchport trunk native vlan 400
+`.trim();
+
+  const result = wasm.generate_change_config(baseConfig, changeInput);
+
+  expect(result.changeOutput).toEqual(
+    [
+      "no interface FortyGigE0/0/0/46.300 l2transport",
+      "",
+      "interface FortyGigE0/0/0/46.400 l2transport",
+      "  description mgmt,To:server1",
+      "  encapsulation untagged",
+      "exit",
+      "",
+      "l2vpn",
+      "  bridge group VLAN",
+      "    bridge-domain VLAN300",
+      "      no interface FortyGigE0/0/0/46.300",
+      "    exit",
+      "    bridge-domain VLAN400",
+      "      description mgmt",
+      "      interface FortyGigE0/0/0/46.400",
+      "      exit",
+      "    exit",
+      "  exit",
+      "exit",
+    ].join("\n") + "\n",
+  );
+});
+
+it("rejects a native VLAN that is also allowed tagged", () => {
+  const changeInput = `
+interface FortyGigE0/0/0/46
+  switchport trunk allowed vlan add 300
+`.trim();
+
+  expect(() => wasm.generate_change_config(baseConfig, changeInput)).toThrow(
+    /native VLANのため、tagged VLANとして許可できません/,
+  );
+});
@@ -0,0 +1,70 @@
+import { it, expect } from "vitest";
+import { wasm } from "./helpers";
+
+const baseConfig = `
+interface FortyGigE0/0/0/46
+  description To:provider
+interface FortyGigE0/0/0/46.200 l2transport
+  description cust-a,To:provider
+  encapsulation dot1q 100 second-dot1q 200
+  rewrite ingress tag pop 1 symmetric
+
+l2vpn
+  bridge group VLAN
+    bridge-domain VLAN200
+      description cust-a
+      interface FortyGigE0/0/0/46.200
+`.trim();
+
+it("lints the rewrite of Q-in-Q subinterfaces", () => {
+  const result = wasm.analyze_config(baseConfig);
+
+  expect(result.lintOutput).toContain(
+    "rewrite ingress tag pop 2 symmetric が存在しない: FortyGigE0/0/0/46.200",
+  );
+  expect(result.lintOutput).not.toContain("encapsulation tag が一致していない");
+  expect(result.simplifiedConfig).toContain(
+    "  switchport dot1q-tunnel vlan 100 inner 200",
+  );
+});
+
+it("adds a Q-in-Q service", () => {
+  const changeInput = `
+vlan database
+  vlan 201 name cust-b
+
+interface FortyGigE0/0/0/46
+  switchport dot1q-tunnel vlan 100 inner 201
+`.trim();
+
+  const result = wasm.generate_change_config(baseConfig, changeInput);
+
+  expect(result.changeOutput).toContain(
+    [
+      "interface FortyGigE0/0/0/46.201 l2transport",
+      "  description cust-b,To:provider",
+      "  encapsulation dot1q 100 second-dot1q 201",
+      "  rewrite ingress tag pop 2 symmetric",
+      "exit",
+    ].join("\n"),
+  );
+  expect(result.changeOutput).toContain(
+    "    bridge-domain VLAN201\n      description cust-b\n      interface FortyGigE0/0/0/46.201\n",
+  );
+});
+
+it("gives a Q-in-Q service sharing an inner tag its own subinterface and bridge-domain", () => {
+  const changeInput = `
+interface FortyGigE0/0/0/46
+  switchport dot1q-tunnel vlan 101 inner 200
+`.trim();
+
+  const result = wasm.generate_change_config(baseConfig, changeInput);
+
+  expect(result.changeOutput).toContain(
+    "interface FortyGigE0/0/0/46.101 l2transport\n  description cust-a,To:provider\n  encapsulation dot1q 101 second-dot1q 200\n",
+  );
+  expect(result.changeOutput).toContain(
+    "    bridge-domain VLAN101-200\n      interface FortyGigE0/0/0/46.101\n",
+  );
+});
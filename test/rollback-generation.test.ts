@@ -0,0 +1,52 @@
+import { it, expect } from "vitest";
+import { wasm } from "./helpers";
+
+it("generates rollback commands that restore the base config", () => {
+  const baseConfig = `
+interface FortyGigE0/0/0/46
+  description To:server1
+interface FortyGigE0/0/0/46.300 l2transport
+  description test,To:server1
+  encapsulation dot1q 300
+  rewrite ingress tag pop 1 symmetric
+
+l2vpn
+  bridge group VLAN
+    bridge-domain VLAN300
+      description test
+      interface FortyGigE0/0/0/46.300
+`.trim();
+
+  const changeInput = `
+vlan database
+  vlan 400 name web
+
+interface FortyGigE0/0/0/46
+  switchport trunk allowed vlan add 400
+  switchport trunk allowed vlan remove 300
+`.trim();
+
+  const result = wasm.generate_change_config(baseConfig, changeInput);
+
+  expect(result.rollbackOutput).toBe(
+    `
+no interface FortyGigE0/0/0/46.400 l2transport
+
+interface FortyGigE0/0/0/46.300 l2transport
+  description test,To:server1
+  encapsulation dot1q 300
+  rewrite ingress tag pop 1 symmetric
+exit
+
+l2vpn
+  bridge group VLAN
+    bridge-domain VLAN300
+      interface FortyGigE0/0/0/46.300
+      exit
+    exit
+    no bridge-domain VLAN400
+  exit
+exit
+`.trimStart(),
+  );
+});
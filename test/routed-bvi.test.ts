@@ -0,0 +1,53 @@
+import { it, expect } from "vitest";
+import { wasm } from "./helpers";
+
+it("does not route an already routed BVI again", () => {
+  const baseConfig = `
+interface BVI100
+  description old-description
+
+l2vpn
+  bridge group VLAN
+    bridge-domain VLAN100
+      description test
+      routed interface BVI100
+`.trim();
+
+  const result = wasm.generate_change_config(baseConfig, "interface BVI100");
+
+  expect(result.changeOutput).not.toContain("routed interface BVI100");
+  expect(result.changeOutput).not.toContain("l2vpn");
+});
+
+it("exposes the routed BVI of a bridge-domain", () => {
+  const config = `
+interface BVI100
+l2vpn
+  bridge group VLAN
+    bridge-domain VLAN100
+      routed interface BVI100
+`.trim();
+
+  const result = wasm.analyze_config(config);
+
+  expect(result.domains[0].routedInterface).toEqual("BVI100");
+  expect(result.lintOutput).toEqual("");
+});
+
+it("lints routed BVIs that are missing or misnumbered", () => {
+  const config = `
+l2vpn
+  bridge group VLAN
+    bridge-domain VLAN100
+      routed interface BVI101
+`.trim();
+
+  const diagnostics = wasm.lint_config_diagnostics(config);
+
+  expect(diagnostics.map((d) => d.code)).toEqual([
+    "bvi-number-mismatch",
+    "bvi-not-configured",
+  ]);
+  expect(diagnostics[1].line).toEqual(4);
+  expect(diagnostics[1].severity).toEqual("warning");
+});
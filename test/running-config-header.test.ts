@@ -0,0 +1,28 @@
+import { it, expect } from "vitest";
+import { wasm } from "./helpers";
+
+it("strips show running-config output and keeps its metadata", () => {
+  const baseConfig = `
+RP/0/RP0/CPU0:sw1#show running-config
+Thu Oct 17 10:00:00.123 JST
+Building configuration...
+!! IOS XR Configuration 7.3.2
+!! Last configuration change at Wed Oct 16 09:12:45 2024 by admin
+!
+interface FortyGigE0/0/0/46
+ description To:server1
+!
+end
+`.trim();
+
+  const result = wasm.analyze_config(baseConfig);
+
+  expect(result.header.hostname).toBe("sw1");
+  expect(result.header.version).toBe("7.3.2");
+  expect(result.header.lastChange).toBe("Wed Oct 16 09:12:45 2024");
+  expect(result.header.lastChangeBy).toBe("admin");
+  expect(result.lintOutput).toBe("");
+  expect(result.simplifiedConfig).toEqual(
+    ["interface FortyGigE0/0/0/46", "  description To:server1"].join("\n"),
+  );
+});
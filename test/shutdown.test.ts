@@ -0,0 +1,42 @@
+import { it, expect } from "vitest";
+import { wasm } from "./helpers";
+
+const baseConfig = `
+interface FortyGigE0/0/0/46
+  description To:server1
+  shutdown
+`.trim();
+
+it("warns when VLANs are added to a shut down port", () => {
+  const changeInput = `
+vlan database
+  vlan 300 name web
+
+interface FortyGigE0/0/0/46
+  switchport trunk allowed vlan add 300
+`.trim();
+
+  const result = wasm.generate_change_report(baseConfig, changeInput);
+
+  expect(result.changeOutput).toContain("interface FortyGigE0/0/0/46.300 l2transport");
+  expect(result.diagnostics).toHaveLength(1);
+  expect(result.diagnostics[0].code).toEqual("vlans-on-shutdown-interface");
+  expect(result.diagnostics[0].severity).toEqual("warning");
+  expect(result.diagnostics[0].line).toEqual(5);
+});
+
+it("brings a port up with no shutdown", () => {
+  const changeInput = `
+vlan database
+  vlan 300 name web
+
+interface FortyGigE0/0/0/46
+  no shutdown
+  switchport trunk allowed vlan add 300
+`.trim();
+
+  const result = wasm.generate_change_report(baseConfig, changeInput);
+
+  expect(result.diagnostics).toHaveLength(0);
+  expect(result.changeOutput).toContain("interface FortyGigE0/0/0/46\n  no shutdown\nexit\n");
+});
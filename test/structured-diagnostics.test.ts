@@ -0,0 +1,62 @@
+import { it, expect } from "vitest";
+import { wasm } from "./helpers";
+
+it("reports change diagnostics with code, severity and column range", () => {
+  const baseConfig = `
+interface FortyGigE0/0/0/46
+  description To:server1
+`.trim();
+
+  const changeInput = `
+interface FortyGigE0/0/0/46
+  switchport trunk allowed vlan remove 100
+`.trim();
+
+  const result = wasm.generate_change_report(baseConfig, changeInput);
+
+  expect(result.changeOutput).toEqual("");
+  expect(result.diagnostics).toHaveLength(1);
+  const [diag] = result.diagnostics;
+  expect(diag.code).toEqual("vlan-not-present");
+  expect(diag.severity).toEqual("error");
+  expect(diag.line).toEqual(2);
+  expect(diag.colStart).toEqual(2);
+  expect(diag.colEnd).toEqual(42);
+  expect(diag.message).toContain("VLAN 100");
+});
+
+it("returns no diagnostics when generation succeeds", () => {
+  const baseConfig = `
+interface FortyGigE0/0/0/46
+  description To:server1
+`.trim();
+
+  const changeInput = `
+vlan database
+  vlan 300 name servers
+
+interface FortyGigE0/0/0/46
+  switchport trunk allowed vlan add 300
+`.trim();
+
+  const result = wasm.generate_change_report(baseConfig, changeInput);
+
+  expect(result.diagnostics).toHaveLength(0);
+  expect(result.changeOutput).toContain("interface FortyGigE0/0/0/46.300 l2transport");
+});
+
+it("reports lint findings as warnings with hints", () => {
+  const config = `
+interface FortyGigE0/0/0/46.300 l2transport
+  encapsulation dot1q 300
+`.trim();
+
+  const diagnostics = wasm.lint_config_diagnostics(config);
+
+  expect(diagnostics.map((d) => d.code)).toEqual(["missing-rewrite"]);
+  expect(diagnostics[0].severity).toEqual("warning");
+  expect(diagnostics[0].line).toEqual(1);
+  expect(diagnostics[0].colStart).toEqual(0);
+  expect(diagnostics[0].colEnd).toEqual(43);
+  expect(diagnostics[0].hint).toContain("rewrite ingress tag pop 1 symmetric");
+});
@@ -0,0 +1,57 @@
+import { it, expect } from "vitest";
+import { wasm } from "./helpers";
+
+it("renames existing subinterfaces and BVIs with their VLAN", () => {
+  const baseConfig = `
+interface FortyGigE0/0/0/46
+ description To:server1
+!
+interface FortyGigE0/0/0/46.300 l2transport
+ description web,To:server1
+ encapsulation dot1q 300
+ rewrite ingress tag pop 1 symmetric
+!
+interface BVI300
+ description web
+!
+l2vpn
+ bridge group VLAN
+  bridge-domain VLAN300
+   description web
+   interface FortyGigE0/0/0/46.300
+   !
+   routed interface BVI300
+   !
+  !
+ !
+!
+`.trim();
+
+  const changeInput = `
+vlan database
+  vlan 300 name www
+`.trim();
+
+  const result = wasm.generate_change_config(baseConfig, changeInput);
+
+  expect(result.changeOutput).toEqual(
+    [
+      "interface BVI300",
+      "  description www",
+      "exit",
+      "",
+      "interface FortyGigE0/0/0/46.300 l2transport",
+      "  description www,To:server1",
+      "exit",
+      "",
+      "l2vpn",
+      "  bridge group VLAN",
+      "    bridge-domain VLAN300",
+      "      description www",
+      "    exit",
+      "  exit",
+      "exit",
+    ].join("\n") + "\n",
+  );
+  expect(result.appliedSimplifiedConfig).toContain("  vlan 300 name www");
+});
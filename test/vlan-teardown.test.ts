@@ -0,0 +1,67 @@
+import { it, expect } from "vitest";
+import { wasm } from "./helpers";
+
+const baseConfig = `
+interface FortyGigE0/0/0/46
+ description To:server1
+!
+interface FortyGigE0/0/0/46.300 l2transport
+ description web,To:server1
+ encapsulation dot1q 300
+ rewrite ingress tag pop 1 symmetric
+!
+interface BVI300
+ description web
+ ipv4 address 192.0.2.1 255.255.255.0
+!
+l2vpn
+ bridge group VLAN
+  bridge-domain VLAN300
+   description web
+   interface FortyGigE0/0/0/46.300
+   !
+   routed interface BVI300
+   !
+  !
+ !
+!
+`.trim();
+
+it("tears down the subinterfaces, BVI and bridge-domain of a removed VLAN", () => {
+  const changeInput = `
+vlan database
+  no vlan 300
+`.trim();
+
+  const result = wasm.generate_change_report(baseConfig, changeInput);
+
+  expect(result.changeOutput).toEqual(
+    [
+      "no interface FortyGigE0/0/0/46.300 l2transport",
+      "",
+      "no interface BVI300",
+      "",
+      "l2vpn",
+      "  bridge group VLAN",
+      "    no bridge-domain VLAN300",
+      "  exit",
+      "exit",
+    ].join("\n") + "\n",
+  );
+  expect(result.diagnostics).toHaveLength(1);
+  const [diag] = result.diagnostics;
+  expect(diag.code).toEqual("bvi-has-address");
+  expect(diag.severity).toEqual("warning");
+  expect(diag.line).toEqual(2);
+});
+
+it("rejects removing a VLAN that is not in the base config", () => {
+  const changeInput = `
+vlan database
+  no vlan 999
+`.trim();
+
+  expect(() => wasm.generate_change_config(baseConfig, changeInput)).toThrow(
+    /VLAN 999 はベース設定に存在しないため削除できません/,
+  );
+});
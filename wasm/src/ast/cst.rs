@@ -0,0 +1,429 @@
+//! Concrete syntax tree that keeps every source line verbatim.
+//!
+//! [`SpannedNode`] trees drop `!` separators, comments and blank lines and
+//! trim indentation. The concrete tree built by
+//! [`parse_cst`](crate::parse::parser::parse_cst) keeps them, so
+//! [`print_cst`] reproduces the input byte-for-byte and [`print_edited`] can
+//! re-emit an edited [`SpannedNode`] tree with minimal textual changes.
+
+use crate::ast::node::{SpannedNode, SpannedNodeBlock, SpannedNodeStmt};
+use crate::ast::printer::opaque_lines;
+use crate::ast::span::Span;
+use crate::parse::opaque::Opaque;
+use crate::parse::parser::indent_width;
+
+/// A source line kept verbatim.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CstLine {
+    /// Line number in the source (1-indexed).
+    pub line: u32,
+    /// Raw line text, including indentation, trailing whitespace and the
+    /// line terminator when there is one.
+    pub raw: String,
+}
+
+impl CstLine {
+    /// Line text without its terminator (`\n` or `\r\n`).
+    pub fn content(&self) -> &str {
+        let line = self.raw.strip_suffix('\n').unwrap_or(&self.raw);
+        if self.raw.ends_with("\r\n") {
+            line.strip_suffix('\r').unwrap_or(line)
+        } else {
+            line
+        }
+    }
+
+    /// Line text without indentation and surrounding whitespace.
+    pub fn text(&self) -> &str {
+        self.content().trim()
+    }
+
+    /// Width of the indentation (see [`indent_width`]).
+    pub fn indent(&self) -> usize {
+        indent_width(self.content())
+    }
+
+    /// Span covering the non-whitespace content of the line.
+    pub fn span(&self) -> Span {
+        Span::for_line(self.line, self.content())
+    }
+
+    /// Whether the line carries no configuration (blank, `!` or a comment).
+    pub fn is_trivia(&self) -> bool {
+        let text = self.text();
+        text.is_empty() || text.starts_with('!')
+    }
+
+    /// Whether the line is a bare `!` closing the node it follows.
+    pub fn is_closer(&self) -> bool {
+        self.text() == "!"
+    }
+}
+
+/// A statement together with the `!` line that closes it, if any.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CstStmt {
+    /// The statement line.
+    pub line: CstLine,
+    /// `!` at the same indentation right after the statement.
+    pub closer: Option<CstLine>,
+}
+
+/// A block header, its children and the `!` line that closes it, if any.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CstBlock {
+    /// The block header line.
+    pub header: CstLine,
+    /// Nested nodes, including trivia.
+    pub children: Vec<CstNode>,
+    /// `!` at the header's indentation right after the block.
+    pub closer: Option<CstLine>,
+}
+
+/// A node of the concrete syntax tree.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CstNode {
+    /// Blank line, `!` separator or comment.
+    Trivia(CstLine),
+    Stmt(CstStmt),
+    Block(CstBlock),
+}
+
+impl CstNode {
+    /// Header of a block or text of a statement; `None` for trivia.
+    fn key(&self) -> Option<&str> {
+        match self {
+            CstNode::Trivia(_) => None,
+            CstNode::Stmt(stmt) => Some(stmt.line.text()),
+            CstNode::Block(block) => Some(block.header.text()),
+        }
+    }
+
+    /// First line of the node.
+    fn first_line(&self) -> &CstLine {
+        match self {
+            CstNode::Trivia(line) => line,
+            CstNode::Stmt(stmt) => &stmt.line,
+            CstNode::Block(block) => &block.header,
+        }
+    }
+}
+
+/// Convert a concrete syntax tree to the [`SpannedNode`] tree used for analysis.
+pub fn to_spanned(nodes: &[CstNode]) -> Vec<SpannedNode> {
+    nodes
+        .iter()
+        .filter_map(|node| match node {
+            CstNode::Trivia(_) => None,
+            CstNode::Stmt(stmt) => Some(SpannedNode::Stmt(SpannedNodeStmt {
+                stmt: stmt.line.text().to_string(),
+                span: stmt.line.span(),
+            })),
+            CstNode::Block(block) => Some(SpannedNode::Block(SpannedNodeBlock {
+                name: block.header.text().to_string(),
+                span: block.header.span(),
+                stmts: if is_opaque(block) {
+                    opaque_body(&block.children)
+                } else {
+                    to_spanned(&block.children)
+                },
+            })),
+        })
+        .collect()
+}
+
+/// Whether a block is a route policy, set or banner read up to its
+/// terminator (see [`Opaque`]).
+fn is_opaque(block: &CstBlock) -> bool {
+    block.header.indent() == 0
+        && Opaque::detect(block.header.text()).is_some_and(|opaque| {
+            block
+                .children
+                .last()
+                .is_some_and(|last| opaque.ends_at(last.first_line().text()))
+        })
+}
+
+/// Lines of an opaque block body as statements, keeping their leading
+/// whitespace and blank lines.
+fn opaque_body(children: &[CstNode]) -> Vec<SpannedNode> {
+    lines(children)
+        .into_iter()
+        .map(|line| {
+            SpannedNode::Stmt(SpannedNodeStmt {
+                stmt: line.content().trim_end().to_string(),
+                span: line.span(),
+            })
+        })
+        .collect()
+}
+
+/// Reproduce the source text of a concrete syntax tree.
+pub fn print_cst(nodes: &[CstNode]) -> String {
+    let mut out = String::new();
+    for line in lines(nodes) {
+        out.push_str(&line.raw);
+    }
+    out
+}
+
+/// Every line of the tree in source order.
+fn lines(nodes: &[CstNode]) -> Vec<&CstLine> {
+    let mut res = Vec::new();
+    for node in nodes {
+        match node {
+            CstNode::Trivia(line) => res.push(line),
+            CstNode::Stmt(stmt) => {
+                res.push(&stmt.line);
+                res.extend(&stmt.closer);
+            }
+            CstNode::Block(block) => {
+                res.push(&block.header);
+                res.extend(lines(&block.children));
+                res.extend(&block.closer);
+            }
+        }
+    }
+    res
+}
+
+/// Layout conventions of a configuration, used to print nodes that are not
+/// in the source.
+struct Style {
+    /// Indentation added per nesting level.
+    unit: usize,
+    /// Whether blocks are closed with `!` lines.
+    closers: bool,
+    newline: &'static str,
+}
+
+impl Style {
+    /// Detect the style of a configuration, defaulting to
+    /// `show running-config` style (one space per level, `!` closers).
+    fn detect(nodes: &[CstNode]) -> Self {
+        let lines = lines(nodes);
+        Style {
+            unit: indent_unit(nodes).unwrap_or(1),
+            closers: nodes.is_empty() || has_closers(nodes),
+            newline: if lines.iter().any(|l| l.raw.ends_with("\r\n")) {
+                "\r\n"
+            } else {
+                "\n"
+            },
+        }
+    }
+}
+
+/// Indentation of the first nested node relative to its parent.
+fn indent_unit(nodes: &[CstNode]) -> Option<usize> {
+    nodes.iter().find_map(|node| {
+        let CstNode::Block(block) = node else {
+            return None;
+        };
+        block
+            .children
+            .iter()
+            .find(|child| child.key().is_some())
+            .map(|child| child.first_line().indent())
+            .and_then(|indent| indent.checked_sub(block.header.indent()))
+            .filter(|&unit| unit > 0)
+            .or_else(|| indent_unit(&block.children))
+    })
+}
+
+fn has_closers(nodes: &[CstNode]) -> bool {
+    nodes.iter().any(|node| match node {
+        CstNode::Block(block) => block.closer.is_some() || has_closers(&block.children),
+        _ => false,
+    })
+}
+
+/// Print an edited version of a parsed configuration.
+///
+/// Nodes of `edited` that match a node of `original` at the same position
+/// (by statement text or block header) keep their original lines, including
+/// indentation, closers and the comments and blank lines around them. Nodes
+/// missing from `original` are printed in the layout of the surrounding
+/// configuration, and original nodes missing from `edited` are dropped.
+pub fn print_edited(original: &[CstNode], edited: &[SpannedNode]) -> String {
+    let style = Style::detect(original);
+    let mut out = String::new();
+    print_level(original, edited, 0, &style, &mut out);
+    out
+}
+
+fn print_level(
+    original: &[CstNode],
+    edited: &[SpannedNode],
+    parent_indent: usize,
+    style: &Style,
+    out: &mut String,
+) {
+    let indent = original
+        .iter()
+        .find(|node| node.key().is_some())
+        .map_or(parent_indent, |node| node.first_line().indent());
+    let mut cursor = 0;
+
+    for node in edited {
+        let (key, children) = match node {
+            SpannedNode::Stmt(stmt) => (stmt.stmt.as_str(), None),
+            SpannedNode::Block(block) => (block.name.as_str(), Some(block.stmts.as_slice())),
+        };
+        let found = original[cursor..]
+            .iter()
+            .position(|n| n.key() == Some(key))
+            .map(|i| cursor + i);
+        let Some(at) = found else {
+            print_new(node, indent, style, out);
+            continue;
+        };
+
+        // Keep comments and blank lines up to the match; drop removed nodes.
+        for skipped in &original[cursor..at] {
+            if let CstNode::Trivia(line) = skipped {
+                push_raw(&line.raw, style, out);
+            }
+        }
+        cursor = at + 1;
+
+        match (&original[at], children) {
+            (CstNode::Stmt(stmt), None) => {
+                push_raw(&stmt.line.raw, style, out);
+                if let Some(closer) = &stmt.closer {
+                    push_raw(&closer.raw, style, out);
+                }
+            }
+            // Opaque constructs are never edited; keep them as written.
+            (CstNode::Block(block), _) if is_opaque(block) => {
+                for line in lines(std::slice::from_ref(&original[at])) {
+                    push_raw(&line.raw, style, out);
+                }
+            }
+            (CstNode::Block(block), children) => {
+                push_raw(&block.header.raw, style, out);
+                let unit = style.unit;
+                print_level(
+                    &block.children,
+                    children.unwrap_or_default(),
+                    block.header.indent() + unit,
+                    style,
+                    out,
+                );
+                if let Some(closer) = &block.closer {
+                    push_raw(&closer.raw, style, out);
+                }
+            }
+            (CstNode::Stmt(stmt), Some(children)) => {
+                // A bodiless statement that became a block.
+                push_raw(&stmt.line.raw, style, out);
+                for child in children {
+                    print_new(child, stmt.line.indent() + style.unit, style, out);
+                }
+                match &stmt.closer {
+                    Some(closer) => push_raw(&closer.raw, style, out),
+                    None if style.closers => {
+                        push_line(stmt.line.indent(), "!", style, out);
+                    }
+                    None => {}
+                }
+            }
+            (CstNode::Trivia(_), _) => unreachable!("trivia has no key"),
+        }
+    }
+
+    for rest in &original[cursor..] {
+        if let CstNode::Trivia(line) = rest {
+            push_raw(&line.raw, style, out);
+        }
+    }
+}
+
+/// Print a node that is not in the original configuration.
+fn print_new(node: &SpannedNode, indent: usize, style: &Style, out: &mut String) {
+    match node {
+        SpannedNode::Stmt(stmt) => push_line(indent, &stmt.stmt, style, out),
+        SpannedNode::Block(block) => {
+            match opaque_lines(block).filter(|_| indent == 0) {
+                Some(lines) => {
+                    for line in lines {
+                        push_line(0, &line, style, out);
+                    }
+                }
+                None => {
+                    push_line(indent, &block.name, style, out);
+                    for child in &block.stmts {
+                        print_new(child, indent + style.unit, style, out);
+                    }
+                }
+            }
+            if style.closers {
+                push_line(indent, "!", style, out);
+            }
+        }
+    }
+}
+
+fn push_line(indent: usize, text: &str, style: &Style, out: &mut String) {
+    let line = format!("{}{}{}", " ".repeat(indent), text, style.newline);
+    push_raw(&line, style, out);
+}
+
+/// Append a raw line, terminating the previous one if it was the unterminated
+/// last line of the source.
+fn push_raw(raw: &str, style: &Style, out: &mut String) {
+    if !out.is_empty() && !out.ends_with('\n') {
+        out.push_str(style.newline);
+    }
+    out.push_str(raw);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse::parser::{parse_cst, tokenize_spanned};
+
+    const RUNNING_CONFIG: &str = "!! IOS XR Configuration 7.3.2\r\nhostname demo  \r\n!\r\ninterface FortyGigE0/0/0/46\r\n description To:server1\r\n!\r\n\r\nl2vpn\r\n bridge group VLAN\r\n  bridge-domain VLAN300\r\n   interface FortyGigE0/0/0/46.300\r\n   !\r\n  !\r\n !\r\n!\r\nend";
+
+    #[test]
+    fn prints_source_byte_for_byte() {
+        let cst = parse_cst(RUNNING_CONFIG);
+        assert_eq!(print_cst(&cst), RUNNING_CONFIG);
+        // hostname, interface and l2vpn; `end` is not configuration.
+        assert_eq!(to_spanned(&cst).len(), 3);
+        assert_eq!(
+            print_edited(&cst, &tokenize_spanned(RUNNING_CONFIG)),
+            RUNNING_CONFIG
+        );
+    }
+
+    #[test]
+    fn prints_edits_in_the_surrounding_layout() {
+        let source = "interface A\n  description a\n  mtu 9000\n\ninterface B\n  description b\n";
+        let mut nodes = tokenize_spanned(source);
+        let SpannedNode::Block(a) = &mut nodes[0] else {
+            panic!("expected block");
+        };
+        a.stmts[1] = SpannedNode::Stmt(SpannedNodeStmt {
+            stmt: "mtu 9216".to_string(),
+            span: Span::line_only(1),
+        });
+        nodes.insert(
+            1,
+            SpannedNode::Block(SpannedNodeBlock {
+                name: "interface A.300 l2transport".to_string(),
+                span: Span::line_only(1),
+                stmts: vec![SpannedNode::Stmt(SpannedNodeStmt {
+                    stmt: "encapsulation dot1q 300".to_string(),
+                    span: Span::line_only(1),
+                })],
+            }),
+        );
+        nodes.pop();
+
+        assert_eq!(
+            print_edited(&parse_cst(source), &nodes),
+            "interface A\n  description a\n  mtu 9216\ninterface A.300 l2transport\n  encapsulation dot1q 300\n\n"
+        );
+    }
+}
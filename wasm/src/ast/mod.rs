@@ -1,7 +1,11 @@
 //! AST types and utilities for representing parsed configuration with position information.
 
+pub mod cst;
 pub mod node;
+pub mod printer;
 pub mod span;
 
+pub use cst::{print_cst, print_edited, to_spanned, CstBlock, CstLine, CstNode, CstStmt};
 pub use node::{SpannedNode, SpannedNodeBlock, SpannedNodeStmt};
+pub use printer::{print_formal, print_nodes};
 pub use span::{Span, Spanned};
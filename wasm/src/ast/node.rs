@@ -9,6 +9,13 @@ pub struct SpannedNodeStmt {
     pub span: Span,
 }
 
+impl SpannedNodeStmt {
+    /// Return the statement text as a `&str`.
+    pub fn stmt(&self) -> &str {
+        &self.stmt
+    }
+}
+
 /// A block node with span information.
 #[derive(Debug, Clone)]
 pub struct SpannedNodeBlock {
@@ -0,0 +1,139 @@
+//! Render node trees back into IOS XR configuration text.
+
+use crate::ast::node::{SpannedNode, SpannedNodeBlock};
+use crate::parse::opaque::Opaque;
+
+/// Render nodes in `show running-config` style.
+///
+/// Each nesting level is indented by one space and every block is closed with
+/// a `!` line, so the output can be fed back into the tokenizer.
+pub fn print_nodes(nodes: &[SpannedNode]) -> String {
+    let mut out = String::new();
+    print_level(nodes, 0, &mut out);
+    out
+}
+
+fn print_level(nodes: &[SpannedNode], depth: usize, out: &mut String) {
+    let indent = " ".repeat(depth);
+    for node in nodes {
+        match node {
+            SpannedNode::Stmt(stmt) => {
+                out.push_str(&indent);
+                out.push_str(&stmt.stmt);
+                out.push('\n');
+            }
+            SpannedNode::Block(block) => {
+                match opaque_lines(block).filter(|_| depth == 0) {
+                    Some(lines) => {
+                        for line in lines {
+                            out.push_str(&line);
+                            out.push('\n');
+                        }
+                    }
+                    None => {
+                        out.push_str(&indent);
+                        out.push_str(&block.name);
+                        out.push('\n');
+                        print_level(&block.stmts, depth + 1, out);
+                    }
+                }
+                out.push_str(&indent);
+                out.push_str("!\n");
+            }
+        }
+    }
+}
+
+/// Render nodes in `show running-config formal` style.
+///
+/// Every statement is printed on one line after the headers of its enclosing
+/// blocks; blocks without statements are printed as their headers.
+pub fn print_formal(nodes: &[SpannedNode]) -> String {
+    let mut out = String::new();
+    print_formal_level(nodes, "", &mut out);
+    out
+}
+
+fn print_formal_level(nodes: &[SpannedNode], prefix: &str, out: &mut String) {
+    for node in nodes {
+        match node {
+            SpannedNode::Stmt(stmt) => {
+                out.push_str(prefix);
+                out.push_str(&stmt.stmt);
+                out.push('\n');
+            }
+            SpannedNode::Block(block) if prefix.is_empty() && opaque_lines(block).is_some() => {
+                for line in opaque_lines(block).unwrap_or_default() {
+                    out.push_str(&line);
+                    out.push('\n');
+                }
+            }
+            SpannedNode::Block(block) if block.stmts.is_empty() => {
+                out.push_str(prefix);
+                out.push_str(&block.name);
+                out.push('\n');
+            }
+            SpannedNode::Block(block) => {
+                let prefix = format!("{}{} ", prefix, block.name);
+                print_formal_level(&block.stmts, &prefix, out);
+            }
+        }
+    }
+}
+
+/// Lines of a route policy, set or banner, which keep their own layout
+/// instead of being indented by nesting level.
+pub(crate) fn opaque_lines(block: &SpannedNodeBlock) -> Option<Vec<String>> {
+    Opaque::detect(&block.name).map(|opaque| opaque.render(block))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse::parser::tokenize_spanned;
+
+    #[test]
+    fn printed_config_tokenizes_to_the_same_tree() {
+        let input = r#"hostname demo
+interface FortyGigE0/0/0/46
+  description To:server1
+l2vpn
+  bridge group VLAN
+    bridge-domain VLAN300
+      interface FortyGigE0/0/0/46.300
+"#;
+        let printed = print_nodes(&tokenize_spanned(input));
+
+        assert_eq!(
+            printed,
+            [
+                "hostname demo",
+                "interface FortyGigE0/0/0/46",
+                " description To:server1",
+                "!",
+                "l2vpn",
+                " bridge group VLAN",
+                "  bridge-domain VLAN300",
+                "   interface FortyGigE0/0/0/46.300",
+                "  !",
+                " !",
+                "!",
+                "",
+            ]
+            .join("\n")
+        );
+        assert_eq!(print_nodes(&tokenize_spanned(&printed)), printed);
+    }
+
+    #[test]
+    fn opaque_constructs_keep_their_layout() {
+        let policy =
+            "route-policy PASS\n  if destination in SERVERS then\n    pass\n  endif\nend-policy\n";
+        let banner = "banner motd #\n hello !\n\n  Welcome\n#\n";
+        let nodes = tokenize_spanned(&format!("{}!\n{}", policy, banner));
+        let expected = format!("{}!\n{}!\n", policy, banner);
+        assert_eq!(print_nodes(&nodes), expected);
+        assert_eq!(print_formal(&nodes), format!("{}{}", policy, banner));
+        assert_eq!(print_nodes(&tokenize_spanned(&expected)), expected);
+    }
+}
@@ -28,6 +28,32 @@ pub struct Span {
 }
 
 impl Span {
+    /// Construct a span covering `col_start..col_end` on the given line.
+    pub fn new(line: u32, col_start: u32, col_end: u32) -> Self {
+        Span {
+            line: LineNumber(line),
+            col_start,
+            col_end,
+        }
+    }
+
+    /// Construct a span covering the non-whitespace content of a source line.
+    pub fn for_line(line: u32, text: &str) -> Self {
+        let trimmed_start = text.trim_start();
+        let col_start = (text.chars().count() - trimmed_start.chars().count()) as u32;
+        let col_end = col_start + trimmed_start.trim_end().chars().count() as u32;
+        Span::new(line, col_start, col_end)
+    }
+
+    /// Return the same span moved `offset` columns to the right.
+    pub fn shifted(self, offset: u32) -> Self {
+        Span {
+            col_start: self.col_start + offset,
+            col_end: self.col_end + offset,
+            ..self
+        }
+    }
+
     /// Construct a span that covers only a line (columns default to 0).
     pub fn line_only(line: u32) -> Self {
         Span {
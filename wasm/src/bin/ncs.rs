@@ -0,0 +1,637 @@
+//! Native command-line front-end for the config builder.
+//!
+//! Runs the same analysis and change generation pipeline as the WASM bindings
+//! so it can be driven from shell scripts and pre-commit hooks.
+
+use ncs_wasm::{
+    analyze_text, diff_configs, diff_simplified_configs, print_formal, tokenize_spanned,
+    ChangeEngine, ChangeOptions, Config, Diagnostic, Locale, MessageCatalog,
+};
+use std::fs;
+use std::io::{self, Read, Write};
+use std::process::ExitCode;
+
+const USAGE: &str = "\
+Usage: ncs [--locale <ja|en>] [--bridge-group <NAME>] <COMMAND> [ARGS]
+
+Commands:
+  analyze [CONFIG]          Show bridge-domains discovered in the base config
+  lint [CONFIG]             Lint the base config
+  simplify [CONFIG]         Print the simplified config
+  formal [CONFIG]           Print the config in `show running-config formal` style
+  change <CONFIG> [CHANGE]  Generate IOS XR commands from change input
+  rollback <CONFIG> [CHANGE]
+                            Generate the commands that undo that change
+  apply <CONFIG> [CHANGE]   Print the config as it will be after the change
+  change-input <SIMPLIFIED> [EDITED]
+                            Derive change input from an edited simplified config
+  diff <OLD> [NEW]          Show drift between two running-configs
+
+Inputs given as `-` or omitted are read from stdin (at most one per run).
+Messages are printed in Japanese unless `--locale en` is given.
+Bridge-domains for new VLANs are created in `bridge group VLAN` unless
+`--bridge-group` names another group; existing ones are edited in place.
+
+Exit status:
+  0  success
+  1  lint findings or change errors or warnings were produced, or `diff` found drift
+  2  usage or I/O error
+";
+
+/// Exit status when nothing had to be reported.
+const EXIT_OK: u8 = 0;
+/// Exit status when lint findings or change diagnostics were produced.
+const EXIT_DIAGNOSTICS: u8 = 1;
+/// Exit status for invalid arguments or unreadable inputs.
+const EXIT_USAGE: u8 = 2;
+
+/// Where an input document is read from.
+#[derive(Debug, PartialEq, Eq)]
+enum Input {
+    Stdin,
+    File(String),
+}
+
+impl Input {
+    /// Interpret a positional argument, treating `-` and absence as stdin.
+    fn from_arg(arg: Option<&str>) -> Self {
+        match arg {
+            None | Some("-") => Input::Stdin,
+            Some(path) => Input::File(path.to_string()),
+        }
+    }
+
+    /// Read the whole document into memory.
+    fn read(&self, stdin: &mut dyn Read) -> Result<String, String> {
+        match self {
+            Input::Stdin => {
+                let mut buf = String::new();
+                stdin
+                    .read_to_string(&mut buf)
+                    .map_err(|e| format!("failed to read stdin: {}", e))?;
+                Ok(buf)
+            }
+            Input::File(path) => {
+                fs::read_to_string(path).map_err(|e| format!("failed to read {}: {}", path, e))
+            }
+        }
+    }
+}
+
+/// Which result of change generation to print.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ChangeResult {
+    Commands,
+    Rollback,
+    Applied,
+}
+
+/// Subcommand selected on the command line.
+#[derive(Debug, PartialEq, Eq)]
+enum Command {
+    Analyze {
+        config: Input,
+    },
+    Lint {
+        config: Input,
+    },
+    Simplify {
+        config: Input,
+    },
+    Formal {
+        config: Input,
+    },
+    ChangeInput {
+        original: Input,
+        edited: Input,
+    },
+    Diff {
+        old: Input,
+        new: Input,
+    },
+    Change {
+        config: Input,
+        change: Input,
+        result: ChangeResult,
+    },
+    Help,
+    Version,
+}
+
+/// Global options given before the command.
+#[derive(Debug, Default)]
+struct Options {
+    locale: Locale,
+    change: ChangeOptions,
+}
+
+/// Remove `--locale <tag>` and `--bridge-group <name>` (or their `--opt=value`
+/// forms) from the arguments.
+fn take_options(args: &[String]) -> Result<(Options, Vec<String>), String> {
+    let mut options = Options::default();
+    let mut rest = Vec::new();
+    let mut iter = args.iter();
+
+    while let Some(arg) = iter.next() {
+        let (name, value) = match arg.split_once('=') {
+            Some((name, value)) if name.starts_with("--") => (name, Some(value)),
+            _ => (arg.as_str(), None),
+        };
+        if name != "--locale" && name != "--bridge-group" {
+            rest.push(arg.clone());
+            continue;
+        }
+        let value = match value {
+            Some(value) => value,
+            None => iter
+                .next()
+                .ok_or_else(|| format!("`{}` requires a value", name))?
+                .as_str(),
+        };
+        if name == "--locale" {
+            options.locale =
+                Locale::from_tag(value).ok_or_else(|| format!("unknown locale `{}`", value))?;
+        } else {
+            options.change = ChangeOptions::with_default_bridge_group(value)?;
+        }
+    }
+
+    Ok((options, rest))
+}
+
+/// Parse command-line arguments (without the program name or options).
+fn parse_args(args: &[String]) -> Result<Command, String> {
+    let Some((command, rest)) = args.split_first() else {
+        return Err("missing command".to_string());
+    };
+    let rest: Vec<&str> = rest.iter().map(String::as_str).collect();
+
+    match command.as_str() {
+        "-h" | "--help" | "help" => Ok(Command::Help),
+        "-V" | "--version" => Ok(Command::Version),
+        "analyze" | "lint" | "simplify" | "formal" => {
+            if rest.len() > 1 {
+                return Err(format!("too many arguments for `{}`", command));
+            }
+            let config = Input::from_arg(rest.first().copied());
+            Ok(match command.as_str() {
+                "analyze" => Command::Analyze { config },
+                "lint" => Command::Lint { config },
+                "formal" => Command::Formal { config },
+                _ => Command::Simplify { config },
+            })
+        }
+        "change-input" | "diff" => {
+            if rest.is_empty() {
+                return Err(format!("`{}` requires two inputs", command));
+            }
+            if rest.len() > 2 {
+                return Err(format!("too many arguments for `{}`", command));
+            }
+            let first = Input::from_arg(rest.first().copied());
+            let second = Input::from_arg(rest.get(1).copied());
+            if first == Input::Stdin && second == Input::Stdin {
+                return Err("only one input can be read from stdin".to_string());
+            }
+            Ok(if command == "diff" {
+                Command::Diff {
+                    old: first,
+                    new: second,
+                }
+            } else {
+                Command::ChangeInput {
+                    original: first,
+                    edited: second,
+                }
+            })
+        }
+        "change" | "rollback" | "apply" => {
+            if rest.is_empty() {
+                return Err(format!("`{}` requires a base config", command));
+            }
+            if rest.len() > 2 {
+                return Err(format!("too many arguments for `{}`", command));
+            }
+            let config = Input::from_arg(rest.first().copied());
+            let change = Input::from_arg(rest.get(1).copied());
+            if config == Input::Stdin && change == Input::Stdin {
+                return Err("only one input can be read from stdin".to_string());
+            }
+            Ok(Command::Change {
+                config,
+                change,
+                result: match command.as_str() {
+                    "change" => ChangeResult::Commands,
+                    "rollback" => ChangeResult::Rollback,
+                    _ => ChangeResult::Applied,
+                },
+            })
+        }
+        other => Err(format!("unknown command `{}`", other)),
+    }
+}
+
+/// Analyze a base config read from the given input.
+fn analyze_input(
+    input: &Input,
+    stdin: &mut dyn Read,
+    catalog: &dyn MessageCatalog,
+) -> Result<Config, String> {
+    let text = input.read(stdin)?;
+    Ok(analyze_text(&text, catalog))
+}
+
+/// Write the bridge-domains of an analyzed config in IOS XR-like form,
+/// grouped by bridge group.
+///
+/// Domains whose name does not encode the VLAN are annotated with the VLAN
+/// they were mapped to.
+fn write_domains(config: &Config, out: &mut dyn Write) -> io::Result<()> {
+    let header = &config.header;
+    if let Some(hostname) = &header.hostname {
+        writeln!(out, "! hostname {}", hostname)?;
+    }
+    if let Some(version) = &header.version {
+        writeln!(out, "! IOS XR {}", version)?;
+    }
+    if let Some(last_change) = &header.last_change {
+        match &header.last_change_by {
+            Some(user) => writeln!(out, "! last change at {} by {}", last_change, user)?,
+            None => writeln!(out, "! last change at {}", last_change)?,
+        }
+    }
+    let mut group: Option<&str> = None;
+    for domain in &config.domains {
+        if group != Some(domain.group.as_str()) {
+            writeln!(out, "bridge group {}", domain.group)?;
+            group = Some(domain.group.as_str());
+        }
+        if domain.name == format!("VLAN{}", domain.vlan_tag) {
+            writeln!(out, "  bridge-domain {}", domain.name)?;
+        } else {
+            writeln!(
+                out,
+                "  bridge-domain {}  ! VLAN{}",
+                domain.name, domain.vlan_tag
+            )?;
+        }
+        if let Some(description) = domain.description() {
+            writeln!(out, "    description {}", description)?;
+        }
+        for interface in &domain.interfaces {
+            writeln!(out, "    interface {}", interface)?;
+        }
+    }
+    Ok(())
+}
+
+/// Write change diagnostics, one per line, prefixed with their severity.
+fn write_diagnostics(
+    diags: &[Diagnostic],
+    catalog: &dyn MessageCatalog,
+    out: &mut dyn Write,
+) -> io::Result<()> {
+    diags.iter().try_for_each(|diag| {
+        writeln!(
+            out,
+            "{}: {}",
+            diag.severity().as_str(),
+            diag.format_with(catalog)
+        )
+    })
+}
+
+/// Execute the CLI and return the process exit status.
+fn run(
+    args: &[String],
+    stdin: &mut dyn Read,
+    stdout: &mut dyn Write,
+    stderr: &mut dyn Write,
+) -> io::Result<u8> {
+    let parsed = take_options(args).and_then(|(options, rest)| Ok((options, parse_args(&rest)?)));
+    let (options, command) = match parsed {
+        Ok(parsed) => parsed,
+        Err(message) => {
+            writeln!(stderr, "error: {}\n\n{}", message, USAGE)?;
+            return Ok(EXIT_USAGE);
+        }
+    };
+    let catalog = options.locale.catalog();
+
+    let result = match command {
+        Command::Help => {
+            write!(stdout, "{}", USAGE)?;
+            return Ok(EXIT_OK);
+        }
+        Command::Version => {
+            writeln!(stdout, "ncs {}", env!("CARGO_PKG_VERSION"))?;
+            return Ok(EXIT_OK);
+        }
+        Command::Analyze { config } => analyze_input(&config, stdin, catalog).map(|config| {
+            write_domains(&config, stdout)?;
+            let lint = config.lint();
+            write!(stderr, "{}", lint)?;
+            Ok(lint.is_empty())
+        }),
+        Command::Lint { config } => analyze_input(&config, stdin, catalog).map(|config| {
+            let lint = config.lint();
+            write!(stdout, "{}", lint)?;
+            Ok(lint.is_empty())
+        }),
+        Command::Simplify { config } => analyze_input(&config, stdin, catalog).map(|config| {
+            writeln!(stdout, "{}", config.simplified_config)?;
+            let lint = config.lint();
+            write!(stderr, "{}", lint)?;
+            Ok(lint.is_empty())
+        }),
+        Command::Formal { config } => config
+            .read(stdin)
+            .map(|text| write!(stdout, "{}", print_formal(&tokenize_spanned(&text))).map(|_| true)),
+        Command::ChangeInput { original, edited } => original.read(stdin).and_then(|original| {
+            let edited = edited.read(stdin)?;
+            Ok(match diff_simplified_configs(&original, &edited) {
+                Ok(change_input) => write!(stdout, "{}", change_input).map(|_| true),
+                Err(diags) => write_diagnostics(&diags, catalog, stderr).map(|_| false),
+            })
+        }),
+        Command::Diff { old, new } => old.read(stdin).and_then(|old| {
+            let new = new.read(stdin)?;
+            let diff = diff_configs(&old, &new);
+            Ok(write!(stdout, "{}", diff.render()).map(|_| diff.is_empty()))
+        }),
+        Command::Change {
+            config,
+            change,
+            result,
+        } => config.read(stdin).and_then(|base| {
+            let change = change.read(stdin)?;
+            Ok(
+                match ChangeEngine::generate_with_options(&base, &change, &options.change) {
+                    Ok(output) => {
+                        // Warnings still fail the run so that scripts notice them.
+                        let clean = output.warnings.is_empty();
+                        let text = match result {
+                            ChangeResult::Commands => output.commands,
+                            ChangeResult::Rollback => output.rollback,
+                            ChangeResult::Applied => output.applied.text,
+                        };
+                        write_diagnostics(&output.warnings, catalog, stderr)
+                            .and_then(|_| write!(stdout, "{}", text))
+                            .map(|_| clean)
+                    }
+                    Err(diags) => write_diagnostics(&diags, catalog, stderr).map(|_| false),
+                },
+            )
+        }),
+    };
+
+    match result {
+        Ok(clean) => Ok(if clean? { EXIT_OK } else { EXIT_DIAGNOSTICS }),
+        Err(message) => {
+            writeln!(stderr, "error: {}", message)?;
+            Ok(EXIT_USAGE)
+        }
+    }
+}
+
+fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let status = run(
+        &args,
+        &mut io::stdin().lock(),
+        &mut io::stdout().lock(),
+        &mut io::stderr().lock(),
+    );
+    ExitCode::from(status.unwrap_or(EXIT_USAGE))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BASE_CONFIG: &str = r#"interface FortyGigE0/0/0/46
+  description To:server1
+interface FortyGigE0/0/0/46.300 l2transport
+  description servers,To:server1
+  encapsulation dot1q 300
+  rewrite ingress tag pop 1 symmetric
+l2vpn
+  bridge group VLAN
+    bridge-domain VLAN300
+      description servers
+      interface FortyGigE0/0/0/46.300
+"#;
+
+    fn run_with_stdin(args: &[&str], stdin: &str) -> (u8, String, String) {
+        let args: Vec<String> = args.iter().map(|s| s.to_string()).collect();
+        let mut stdout = Vec::new();
+        let mut stderr = Vec::new();
+        let status = run(&args, &mut stdin.as_bytes(), &mut stdout, &mut stderr)
+            .expect("writing to memory never fails");
+        (
+            status,
+            String::from_utf8(stdout).unwrap(),
+            String::from_utf8(stderr).unwrap(),
+        )
+    }
+
+    /// A file in the temp directory, removed when dropped.
+    struct TempFile(String);
+
+    impl TempFile {
+        fn path(&self) -> &str {
+            &self.0
+        }
+    }
+
+    impl Drop for TempFile {
+        fn drop(&mut self) {
+            let _ = fs::remove_file(&self.0);
+        }
+    }
+
+    fn write_temp(name: &str, contents: &str) -> TempFile {
+        let path = std::env::temp_dir().join(format!("ncs-{}-{}", std::process::id(), name));
+        fs::write(&path, contents).expect("temp file is writable");
+        TempFile(path.to_string_lossy().into_owned())
+    }
+
+    #[test]
+    fn analyze_shows_running_config_header() {
+        let config = format!(
+            "RP/0/RP0/CPU0:sw1#show running-config\nBuilding configuration...\n!! IOS XR Configuration 7.3.2\n{}end\n",
+            BASE_CONFIG
+        );
+        let (status, stdout, stderr) = run_with_stdin(&["analyze"], &config);
+        assert_eq!(status, EXIT_OK, "stderr: {}", stderr);
+        assert!(stdout.starts_with("! hostname sw1\n! IOS XR 7.3.2\nbridge group VLAN\n"));
+    }
+
+    #[test]
+    fn formal_prints_one_line_per_statement() {
+        let (status, stdout, stderr) = run_with_stdin(&["formal"], BASE_CONFIG);
+        assert_eq!(status, EXIT_OK, "stderr: {}", stderr);
+        assert!(stdout.starts_with(
+            "interface FortyGigE0/0/0/46 description To:server1\ninterface FortyGigE0/0/0/46.300 l2transport description servers,To:server1\n"
+        ));
+        assert!(stdout.ends_with(
+            "l2vpn bridge group VLAN bridge-domain VLAN300 interface FortyGigE0/0/0/46.300\n"
+        ));
+    }
+
+    #[test]
+    fn simplify_reads_stdin() {
+        let (status, stdout, stderr) = run_with_stdin(&["simplify"], BASE_CONFIG);
+        assert_eq!(status, EXIT_OK);
+        assert!(stdout.contains("switchport trunk allowed vlan 300"));
+        assert!(stderr.is_empty());
+    }
+
+    #[test]
+    fn lint_fails_on_findings() {
+        let config = BASE_CONFIG.replace("encapsulation dot1q 300", "encapsulation dot1q 301");
+        let (status, stdout, _) = run_with_stdin(&["lint", "-"], &config);
+        assert_eq!(status, EXIT_DIAGNOSTICS);
+        assert!(stdout.contains("interface FortyGigE0/0/0/46.300 l2transport"));
+    }
+
+    #[test]
+    fn change_generates_commands() {
+        let base = write_temp("base.cfg", BASE_CONFIG);
+        let change_input =
+            "interface FortyGigE0/0/0/46\n  switchport trunk allowed vlan remove 300\n";
+        let (status, stdout, stderr) = run_with_stdin(&["change", base.path()], change_input);
+        assert_eq!(status, EXIT_OK, "stderr: {}", stderr);
+        assert!(stdout.contains("no interface FortyGigE0/0/0/46.300 l2transport"));
+    }
+
+    #[test]
+    fn rollback_recreates_removed_subinterface() {
+        let base = write_temp("base-rollback.cfg", BASE_CONFIG);
+        let change_input =
+            "interface FortyGigE0/0/0/46\n  switchport trunk allowed vlan remove 300\n";
+        let (status, stdout, stderr) = run_with_stdin(&["rollback", base.path()], change_input);
+        assert_eq!(status, EXIT_OK, "stderr: {}", stderr);
+        assert!(stdout.contains("interface FortyGigE0/0/0/46.300 l2transport"));
+        assert!(stdout.contains("  encapsulation dot1q 300"));
+        assert!(stdout.contains("      interface FortyGigE0/0/0/46.300"));
+    }
+
+    #[test]
+    fn apply_prints_post_change_config() {
+        let base = write_temp("base-apply.cfg", BASE_CONFIG);
+        let change_input =
+            "interface FortyGigE0/0/0/46\n  switchport trunk allowed vlan remove 300\n";
+        let (status, stdout, stderr) = run_with_stdin(&["apply", base.path()], change_input);
+        assert_eq!(status, EXIT_OK, "stderr: {}", stderr);
+        assert!(
+            stdout.starts_with("interface FortyGigE0/0/0/46\n  description To:server1\nl2vpn\n")
+        );
+        assert!(!stdout.contains("FortyGigE0/0/0/46.300"));
+    }
+
+    #[test]
+    fn change_input_diffs_simplified_configs() {
+        let original = write_temp(
+            "simplified.txt",
+            "interface FortyGigE0/0/0/46\n  switchport trunk allowed vlan 300\n",
+        );
+        let edited = "interface FortyGigE0/0/0/46\n  switchport trunk allowed vlan 300-301\n\nvlan database\n  vlan 301 name web\n";
+        let (status, stdout, stderr) = run_with_stdin(&["change-input", original.path()], edited);
+        assert_eq!(status, EXIT_OK, "stderr: {}", stderr);
+        assert_eq!(
+            stdout,
+            "vlan database\n  vlan 301 name web\n\ninterface FortyGigE0/0/0/46\n  switchport trunk allowed vlan add 301\n"
+        );
+    }
+
+    #[test]
+    fn diff_reports_drift() {
+        let old = write_temp("old.cfg", BASE_CONFIG);
+        let (status, stdout, _) = run_with_stdin(&["diff", old.path(), "-"], BASE_CONFIG);
+        assert_eq!(status, EXIT_OK);
+        assert!(stdout.is_empty());
+
+        let new = BASE_CONFIG.replace("description servers\n", "description web\n");
+        let (status, stdout, _) = run_with_stdin(&["diff", old.path()], &new);
+        assert_eq!(status, EXIT_DIAGNOSTICS);
+        assert!(
+            stdout.contains("~ VLAN300 servers => web"),
+            "stdout: {}",
+            stdout
+        );
+    }
+
+    #[test]
+    fn change_reports_diagnostics() {
+        let base = write_temp("base-diag.cfg", BASE_CONFIG);
+        let change_input =
+            "interface FortyGigE0/0/0/46\n  switchport trunk allowed vlan remove 100\n";
+        let (status, stdout, stderr) = run_with_stdin(&["change", base.path(), "-"], change_input);
+        assert_eq!(status, EXIT_DIAGNOSTICS);
+        assert!(stdout.is_empty());
+        assert!(stderr.contains("VLAN 100"));
+    }
+
+    #[test]
+    fn change_fails_on_warnings() {
+        let base = write_temp("base-warn.cfg", BASE_CONFIG);
+        let change_input = "vlan database\n  vlan 301 name web\n\ninterface FortyGigE0/0/0/46\n  shutdown\n  switchport trunk allowed vlan add 301\n";
+        for command in ["change", "apply"] {
+            let (status, stdout, stderr) =
+                run_with_stdin(&[command, base.path(), "-"], change_input);
+            assert_eq!(status, EXIT_DIAGNOSTICS, "{}", command);
+            assert!(
+                stdout.contains("FortyGigE0/0/0/46.301 l2transport"),
+                "stdout: {}",
+                stdout
+            );
+            assert!(stderr.contains("warning"), "stderr: {}", stderr);
+        }
+    }
+
+    #[test]
+    fn renders_messages_in_requested_locale() {
+        let base = write_temp("base-locale.cfg", BASE_CONFIG);
+        let change_input =
+            "interface FortyGigE0/0/0/46\n  switchport trunk allowed vlan remove 100\n";
+        let (status, _, stderr) =
+            run_with_stdin(&["--locale", "en", "change", base.path()], change_input);
+        assert_eq!(status, EXIT_DIAGNOSTICS);
+        assert!(
+            stderr.contains("Cannot remove VLAN 100"),
+            "stderr: {}",
+            stderr
+        );
+        assert!(stderr.contains("(line 2)"), "stderr: {}", stderr);
+    }
+
+    #[test]
+    fn creates_new_domains_in_requested_bridge_group() {
+        let base = write_temp("base-group.cfg", BASE_CONFIG);
+        let change_input = "vlan database\n  vlan 400 name db\n\ninterface FortyGigE0/0/0/46\n  switchport trunk allowed vlan add 400\n";
+        let (status, stdout, stderr) = run_with_stdin(
+            &["--bridge-group=TENANT", "change", base.path()],
+            change_input,
+        );
+        assert_eq!(status, EXIT_OK, "stderr: {}", stderr);
+        assert!(stdout.contains("  bridge group TENANT\n    bridge-domain VLAN400\n"));
+    }
+
+    #[test]
+    fn rejects_invalid_arguments() {
+        assert_eq!(run_with_stdin(&[], "").0, EXIT_USAGE);
+        assert_eq!(run_with_stdin(&["frobnicate"], "").0, EXIT_USAGE);
+        assert_eq!(run_with_stdin(&["change"], "").0, EXIT_USAGE);
+        assert_eq!(run_with_stdin(&["change", "-", "-"], "").0, EXIT_USAGE);
+        assert_eq!(run_with_stdin(&["--locale=fr", "lint"], "").0, EXIT_USAGE);
+        assert_eq!(
+            run_with_stdin(&["lint", "--bridge-group"], "").0,
+            EXIT_USAGE
+        );
+        assert_eq!(
+            run_with_stdin(&["lint", "/nonexistent/ncs-config"], "").0,
+            EXIT_USAGE
+        );
+    }
+}
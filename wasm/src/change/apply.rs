@@ -0,0 +1,470 @@
+//! Apply a change plan to the base configuration tree.
+//!
+//! This mirrors the effect of the commands from `codegen::generate_commands` on
+//! the device, so the post-change running-config can be reviewed before commit.
+
+use crate::ast::{
+    print_edited, print_formal, CstNode, Span, SpannedNode, SpannedNodeBlock, SpannedNodeStmt,
+};
+use crate::change::model::{statement_setting, BaseIf, ChangePlan, ChangeSpec, VlanId};
+use crate::parse::formal::is_formal;
+use crate::parse::parser::{lower, tokenize_spanned};
+use crate::semantics::{analyze, split_subinterface_id};
+
+/// Configuration resulting from applying a change to the base config.
+#[derive(Debug, Clone, Default)]
+pub struct AppliedConfig {
+    /// Post-change configuration tree. Spans point into `text`.
+    pub nodes: Vec<SpannedNode>,
+    /// Post-change configuration text. Unchanged lines keep the base config's
+    /// formatting, comments and `!` separators; new lines follow its layout.
+    pub text: String,
+    /// Simplified config built from the post-change configuration.
+    pub simplified_config: String,
+}
+
+/// Apply a change plan to the parsed base config.
+pub fn apply_plan(base: &[CstNode], plan: &ChangePlan, change_spec: &ChangeSpec) -> AppliedConfig {
+    let mut nodes = lower(base);
+
+    // 1. Base interface configuration
+    for conversion in &plan.conversions {
+        let stmts = conversion
+            .statements
+            .iter()
+            .map(|stmt| new_stmt(stmt.clone()))
+            .collect();
+        let block = new_block(conversion.new_header(), stmts);
+        match nodes
+            .iter()
+            .position(|n| node_text(n) == conversion.old_header())
+        {
+            Some(i) => nodes[i] = block,
+            None => {
+                let at = after_last_interface(&nodes);
+                nodes.insert(at, block);
+            }
+        }
+    }
+    for (baseif, change) in &change_spec.interface_changes {
+        let statements = change.statements();
+        if statements.is_empty() || plan.conversion_for(baseif).is_some() {
+            continue;
+        }
+        let header = plan.interface_header(baseif);
+        let block = find_or_insert_block(&mut nodes, &header, after_last_interface);
+        for stmt in statements {
+            apply_statement(block, stmt);
+        }
+    }
+
+    // 2. Subinterface removals
+    for removal in &plan.removal_cmds {
+        let header = format!("interface {} l2transport", removal.iface);
+        nodes.retain(|node| node_text(node) != header);
+    }
+
+    // 3. Subinterface additions
+    for addition in &plan.additions {
+        let mut stmts = vec![new_stmt(format!("description {}", addition.description))];
+        stmts.extend(
+            addition
+                .encapsulation
+                .statements()
+                .into_iter()
+                .map(new_stmt),
+        );
+        let block = new_block(format!("interface {} l2transport", addition.iface()), stmts);
+        let at = subinterface_position(&nodes, &addition.baseif, addition.vlan);
+        nodes.insert(at, block);
+    }
+
+    for update in &plan.description_updates {
+        let block = find_or_insert_block(&mut nodes, &update.header, after_last_interface);
+        apply_statement(block, &format!("description {}", update.new));
+    }
+
+    // 4. BVI interface configuration
+    for (vlan, statements) in &change_spec.bvi_statements {
+        if statements.is_empty() {
+            continue;
+        }
+        let header = format!("interface BVI{}", vlan);
+        let block = find_or_insert_block(&mut nodes, &header, after_last_interface);
+        for stmt in statements {
+            apply_statement(block, &stmt.value);
+        }
+    }
+
+    for vlan in &plan.bvi_removals {
+        let header = format!("interface BVI{}", vlan);
+        nodes.retain(|node| node_text(node) != header);
+    }
+
+    // 5. Bridge-domains
+    if plan.domain_changes().next().is_some() {
+        let l2vpn = find_or_insert_block(&mut nodes, "l2vpn", |n| n.len());
+
+        for change in plan.domain_changes() {
+            let group =
+                find_or_insert_block(&mut l2vpn.stmts, &change.path.group_header(), |n| n.len());
+            if change.remove_domain {
+                let header = change.path.domain_header();
+                group.stmts.retain(|n| node_text(n) != header);
+                continue;
+            }
+            let domain =
+                find_or_insert_block(&mut group.stmts, &change.path.domain_header(), |n| n.len());
+
+            if change_spec.vlans.contains_key(&change.vlan) {
+                if let Some(desc) = change.description.as_ref().filter(|d| !d.value.is_empty()) {
+                    let stmt = format!("description {}", desc.value);
+                    match domain
+                        .stmts
+                        .iter()
+                        .position(|n| node_text(n).starts_with("description "))
+                    {
+                        Some(i) => domain.stmts[i] = new_stmt(stmt),
+                        None => domain.stmts.insert(0, new_stmt(stmt)),
+                    }
+                }
+            }
+
+            for removal in &change.removals {
+                let member = format!("interface {}", removal);
+                domain.stmts.retain(|n| node_text(n) != member);
+            }
+
+            for addition in &change.additions {
+                let member = format!("interface {}", addition.member());
+                if domain.stmts.iter().any(|n| node_text(n) == member) {
+                    continue;
+                }
+                let at = domain
+                    .stmts
+                    .iter()
+                    .rposition(|n| node_text(n).starts_with("interface "))
+                    .map(|i| i + 1)
+                    .or_else(|| {
+                        domain
+                            .stmts
+                            .iter()
+                            .position(|n| node_text(n).starts_with("routed interface "))
+                    })
+                    .unwrap_or(domain.stmts.len());
+                domain.stmts.insert(at, new_stmt(member));
+            }
+
+            if change.add_bvi {
+                let routed = format!("routed interface BVI{}", change.vlan);
+                if !domain.stmts.iter().any(|n| node_text(n) == routed) {
+                    domain.stmts.push(new_stmt(routed));
+                }
+            }
+            if change.remove_bvi {
+                let routed = format!("routed interface BVI{}", change.vlan);
+                domain.stmts.retain(|n| node_text(n) != routed);
+            }
+        }
+    }
+
+    let text = if is_formal(base) {
+        print_formal(&nodes)
+    } else {
+        print_edited(base, &nodes)
+    };
+    let nodes = tokenize_spanned(&text);
+    let simplified_config = analyze(&nodes).simplified_config;
+
+    AppliedConfig {
+        nodes,
+        text,
+        simplified_config,
+    }
+}
+
+/// Header of a block or text of a statement.
+fn node_text(node: &SpannedNode) -> &str {
+    match node {
+        SpannedNode::Block(block) => &block.name,
+        SpannedNode::Stmt(stmt) => &stmt.stmt,
+    }
+}
+
+/// Nodes created by the change have no source position until they are printed.
+fn new_stmt(stmt: String) -> SpannedNode {
+    SpannedNode::Stmt(SpannedNodeStmt {
+        stmt,
+        span: Span::line_only(1),
+    })
+}
+
+fn new_block(name: String, stmts: Vec<SpannedNode>) -> SpannedNode {
+    SpannedNode::Block(SpannedNodeBlock {
+        name,
+        span: Span::line_only(1),
+        stmts,
+    })
+}
+
+/// Find the block with the given header, inserting it at `position` if missing.
+///
+/// A bodiless statement with the same text (e.g. an empty `interface BVI10`)
+/// is turned into a block.
+fn find_or_insert_block<'a>(
+    nodes: &'a mut Vec<SpannedNode>,
+    header: &str,
+    position: impl FnOnce(&[SpannedNode]) -> usize,
+) -> &'a mut SpannedNodeBlock {
+    let index = match nodes.iter().position(|n| node_text(n) == header) {
+        Some(i) => {
+            if let SpannedNode::Stmt(stmt) = &nodes[i] {
+                nodes[i] = SpannedNode::Block(SpannedNodeBlock {
+                    name: stmt.stmt.clone(),
+                    span: stmt.span,
+                    stmts: Vec::new(),
+                });
+            }
+            i
+        }
+        None => {
+            let i = position(nodes);
+            nodes.insert(i, new_block(header.to_string(), Vec::new()));
+            i
+        }
+    };
+
+    match &mut nodes[index] {
+        SpannedNode::Block(block) => block,
+        SpannedNode::Stmt(_) => unreachable!("statement was converted to a block"),
+    }
+}
+
+/// Position right after the last top-level interface block.
+fn after_last_interface(nodes: &[SpannedNode]) -> usize {
+    nodes
+        .iter()
+        .rposition(|n| node_text(n).starts_with("interface "))
+        .map_or(0, |i| i + 1)
+}
+
+/// Position for a new subinterface: after the base interface and any of its
+/// subinterfaces with a lower number.
+fn subinterface_position(nodes: &[SpannedNode], baseif: &BaseIf, vlan: VlanId) -> usize {
+    nodes
+        .iter()
+        .rposition(|n| {
+            let Some(name) = node_text(n).strip_prefix("interface ") else {
+                return false;
+            };
+            let name = name.strip_suffix(" l2transport").unwrap_or(name);
+            if name == baseif.as_str() {
+                return true;
+            }
+            matches!(
+                split_subinterface_id(name),
+                Ok((base, Some(sub))) if base == baseif.as_str() && sub < vlan.get()
+            )
+        })
+        .map_or_else(|| after_last_interface(nodes), |i| i + 1)
+}
+
+/// Apply a statement to an interface block, replacing statements that
+/// configure the same setting. `no <stmt>` removes the setting.
+fn apply_statement(block: &mut SpannedNodeBlock, stmt: &str) {
+    let stmt = stmt.trim();
+    let setting = statement_setting(stmt);
+    let existing = block
+        .stmts
+        .iter()
+        .position(|n| statement_setting(node_text(n)) == setting);
+    block
+        .stmts
+        .retain(|n| statement_setting(node_text(n)) != setting);
+
+    if stmt.starts_with("no ") {
+        return;
+    }
+    let at = existing.unwrap_or(block.stmts.len());
+    block.stmts.insert(at, new_stmt(stmt.to_string()));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::change::input_parser::parse_change_input;
+    use crate::change::model::BaseContext;
+    use crate::change::planner::ChangePlanner;
+    use crate::parse::parser::parse_cst;
+
+    fn apply(base_config: &str, change_input: &str) -> AppliedConfig {
+        let base = parse_cst(base_config);
+        let base_nodes = lower(&base);
+        let analysis = analyze(&base_nodes);
+        let base_ctx = BaseContext::from_analysis(&analysis.domains, &base_nodes);
+        let mut diags = Vec::new();
+        let change_spec = parse_change_input(change_input, &mut diags);
+        let plan = ChangePlanner::new(&change_spec, &base_ctx).plan(&mut diags);
+        assert!(diags.is_empty(), "diagnostics: {:?}", diags);
+        apply_plan(&base, &plan, &change_spec)
+    }
+
+    #[test]
+    fn applies_change_to_base_config() {
+        let base_config = r#"interface FortyGigE0/0/0/46
+ description To:demo-port
+ mtu 9000
+!
+interface FortyGigE0/0/0/46.300 l2transport
+ description demo-web,To:demo-port
+ encapsulation dot1q 300
+ rewrite ingress tag pop 1 symmetric
+!
+interface FortyGigE0/0/0/47
+ description To:other
+!
+l2vpn
+ bridge group VLAN
+  bridge-domain VLAN300
+   description demo-web
+   interface FortyGigE0/0/0/46.300
+  !
+ !
+!
+"#;
+
+        let change_input = [
+            "interface FortyGigE0/0/0/46",
+            "  mtu 9216",
+            "  switchport mode trunk",
+            "  switchport trunk allowed vlan add 350",
+            "  switchport trunk allowed vlan remove 300",
+            "",
+            "interface BVI350",
+            "  ipv4 address 192.0.2.1 255.255.255.0",
+            "",
+            "vlan database",
+            "  vlan 350 name demo-servers",
+        ]
+        .join("\n");
+
+        let applied = apply(base_config, &change_input);
+
+        let expected = [
+            "interface FortyGigE0/0/0/46",
+            " description To:demo-port",
+            " mtu 9216",
+            "!",
+            "interface FortyGigE0/0/0/46.350 l2transport",
+            " description demo-servers,To:demo-port",
+            " encapsulation dot1q 350",
+            " rewrite ingress tag pop 1 symmetric",
+            "!",
+            "interface FortyGigE0/0/0/47",
+            " description To:other",
+            "!",
+            "interface BVI350",
+            " ipv4 address 192.0.2.1 255.255.255.0",
+            "!",
+            "l2vpn",
+            " bridge group VLAN",
+            "  bridge-domain VLAN300",
+            "   description demo-web",
+            "  !",
+            "  bridge-domain VLAN350",
+            "   description demo-servers",
+            "   interface FortyGigE0/0/0/46.350",
+            "   routed interface BVI350",
+            "  !",
+            " !",
+            "!",
+            "",
+        ]
+        .join("\n");
+
+        assert_eq!(applied.text, expected);
+        assert!(applied
+            .simplified_config
+            .contains("switchport trunk allowed vlan 350"));
+        assert!(applied
+            .simplified_config
+            .contains("vlan 350 name demo-servers"));
+
+        let bvi = applied.nodes[3].as_block().expect("BVI block");
+        assert_eq!(bvi.name, "interface BVI350");
+        assert_eq!(bvi.span.line.get(), 13);
+    }
+
+    #[test]
+    fn keeps_base_config_formatting() {
+        let base_config = [
+            "!! Last configuration change by admin",
+            "interface FortyGigE0/0/0/46",
+            "  description To:demo-port",
+            "  mtu 9000   ",
+            "",
+            "! uplink",
+            "interface FortyGigE0/0/0/47",
+            "  description To:other",
+        ]
+        .join("\n");
+
+        let applied = apply(
+            &base_config,
+            "interface FortyGigE0/0/0/46\n  mtu 9216\n  switchport trunk allowed vlan add 350\n\nvlan database\n  vlan 350 name demo-servers\n",
+        );
+
+        let expected = [
+            "!! Last configuration change by admin",
+            "interface FortyGigE0/0/0/46",
+            "  description To:demo-port",
+            "  mtu 9216",
+            "interface FortyGigE0/0/0/46.350 l2transport",
+            "  description demo-servers,To:demo-port",
+            "  encapsulation dot1q 350",
+            "  rewrite ingress tag pop 1 symmetric",
+            "",
+            "! uplink",
+            "interface FortyGigE0/0/0/47",
+            "  description To:other",
+            "l2vpn",
+            "  bridge group VLAN",
+            "    bridge-domain VLAN350",
+            "      description demo-servers",
+            "      interface FortyGigE0/0/0/46.350",
+            "",
+        ]
+        .join("\n");
+        assert_eq!(applied.text, expected);
+    }
+
+    #[test]
+    fn replaces_only_statements_of_the_same_setting() {
+        let base_config = [
+            "interface FortyGigE0/0/0/46",
+            " description To:demo-port",
+            " ipv6 address 2001:db8::1/64",
+            " service-policy output SHAPE",
+            " service-policy input OLD",
+            "!",
+        ]
+        .join("\n");
+
+        let applied = apply(
+            &base_config,
+            "interface FortyGigE0/0/0/46\n  ipv6 enable\n  service-policy input POLICE\n",
+        );
+
+        let expected = [
+            "interface FortyGigE0/0/0/46",
+            " description To:demo-port",
+            " ipv6 address 2001:db8::1/64",
+            " service-policy output SHAPE",
+            " service-policy input POLICE",
+            " ipv6 enable",
+            "!",
+        ]
+        .join("\n");
+        assert_eq!(applied.text, expected);
+    }
+}
@@ -3,7 +3,9 @@
 //! This module generates the actual IOS XR CLI commands needed to apply
 //! the planned changes to the switch configuration.
 
-use crate::change::model::{BaseIf, ChangePlan, ChangeSpec};
+use crate::change::model::{
+    statement_setting, BaseContext, BaseIf, ChangePlan, ChangeSpec, VlanChange,
+};
 use std::collections::BTreeSet;
 
 pub fn generate_commands(plan: &ChangePlan, change_spec: &ChangeSpec) -> String {
@@ -20,15 +22,27 @@ pub fn generate_commands(plan: &ChangePlan, change_spec: &ChangeSpec) -> String
     for addition in &plan.additions {
         affected_interfaces.insert(&addition.baseif);
     }
+    for update in &plan.description_updates {
+        affected_interfaces.insert(&update.baseif);
+    }
 
     // Output changes grouped by physical interface
     for baseif in affected_interfaces {
         // 1. Base interface configuration
-        if let Some(change) = change_spec.interface_changes.get(baseif) {
-            if !change.other_statements.is_empty() {
-                lines.push(format!("interface {}", baseif));
-                for stmt in &change.other_statements {
-                    lines.push(format!("  {}", stmt.stmt));
+        if let Some(conversion) = plan.conversion_for(baseif) {
+            lines.push(format!("no {}", conversion.old_header()));
+            lines.push(conversion.new_header());
+            for stmt in &conversion.statements {
+                lines.push(format!("  {}", stmt));
+            }
+            lines.push("exit".to_string());
+            lines.push(String::new());
+        } else if let Some(change) = change_spec.interface_changes.get(baseif) {
+            let statements = change.statements();
+            if !statements.is_empty() {
+                lines.push(plan.interface_header(baseif));
+                for stmt in statements {
+                    lines.push(format!("  {}", stmt));
                 }
                 lines.push("exit".to_string());
                 lines.push(String::new());
@@ -50,13 +64,21 @@ pub fn generate_commands(plan: &ChangePlan, change_spec: &ChangeSpec) -> String
         // 3. Subinterface additions
         for addition in &plan.additions {
             if &addition.baseif == baseif {
-                lines.push(format!(
-                    "interface {}.{} l2transport",
-                    addition.baseif, addition.vlan
-                ));
+                lines.push(format!("interface {} l2transport", addition.iface()));
                 lines.push(format!("  description {}", addition.description));
-                lines.push(format!("  encapsulation dot1q {}", addition.vlan));
-                lines.push("  rewrite ingress tag pop 1 symmetric".to_string());
+                for stmt in addition.encapsulation.statements() {
+                    lines.push(format!("  {}", stmt));
+                }
+                lines.push("exit".to_string());
+                lines.push(String::new());
+            }
+        }
+
+        // 4. Description updates of existing interfaces
+        for update in &plan.description_updates {
+            if &update.baseif == baseif {
+                lines.push(update.header.clone());
+                lines.push(format!("  description {}", update.new));
                 lines.push("exit".to_string());
                 lines.push(String::new());
             }
@@ -75,46 +97,352 @@ pub fn generate_commands(plan: &ChangePlan, change_spec: &ChangeSpec) -> String
         }
     }
 
-    if !plan.vlan_changes.is_empty() {
+    // Output BVI interface removals
+    for vlan in &plan.bvi_removals {
+        lines.push(format!("no interface BVI{}", vlan));
+        lines.push(String::new());
+    }
+
+    if plan.domain_changes().next().is_some() {
         lines.push("l2vpn".to_string());
-        lines.push("  bridge group VLAN".to_string());
-        for (_, change) in &plan.vlan_changes {
-            lines.push(format!("    bridge-domain VLAN{}", change.vlan));
-            // Only output description if explicitly specified in change_spec
-            if change_spec.vlans.contains_key(&change.vlan) {
-                if let Some(desc) = &change.description {
-                    if !desc.value.is_empty() {
-                        lines.push(format!("      description {}", &desc.value));
+        for (group, changes) in plan.changes_by_group() {
+            lines.push(format!("  bridge group {}", group));
+            for change in changes {
+                if change.remove_domain {
+                    lines.push(format!("    no {}", change.path.domain_header()));
+                    continue;
+                }
+                lines.push(format!("    {}", change.path.domain_header()));
+                // Only output description if explicitly specified in change_spec
+                if change_spec.vlans.contains_key(&change.vlan) {
+                    if let Some(desc) = &change.description {
+                        if !desc.value.is_empty() {
+                            lines.push(format!("      description {}", &desc.value));
+                        }
+                    }
+                }
+                for removal in &change.removals {
+                    lines.push(format!("      no interface {}", removal));
+                }
+                for addition in &change.additions {
+                    lines.push(format!("      interface {}", addition.member()));
+                    lines.push("      exit".to_string());
+                }
+                if change.add_bvi {
+                    lines.push(format!("      routed interface BVI{}", change.vlan));
+                    lines.push("      exit".to_string());
+                }
+                if change.remove_bvi {
+                    lines.push(format!("      no routed interface BVI{}", change.vlan));
+                }
+                lines.push("    exit".to_string());
+            }
+            lines.push("  exit".to_string());
+        }
+        lines.push("exit".to_string());
+    }
+
+    trim_trailing_empty_lines(&mut lines);
+
+    let mut res = lines.join("\n");
+    res.push('\n'); // 最後の exit を確定させるため
+    res
+}
+
+/// Generate the commands that undo `generate_commands` and restore the base config.
+///
+/// Statements applied to existing interfaces are reverted to their original
+/// values (or negated when the base config did not have them), removed
+/// subinterfaces are recreated from their original statements, and created
+/// subinterfaces, Bundle-Ether interfaces, BVIs and bridge-domains are removed
+/// again.
+pub fn generate_rollback_commands(
+    plan: &ChangePlan,
+    change_spec: &ChangeSpec,
+    base_ctx: &BaseContext,
+) -> String {
+    let mut lines: Vec<String> = Vec::new();
+
+    let mut affected_interfaces: BTreeSet<&BaseIf> = BTreeSet::new();
+    for baseif in change_spec.interface_changes.keys() {
+        affected_interfaces.insert(baseif);
+    }
+    for removal in &plan.removal_cmds {
+        affected_interfaces.insert(&removal.baseif);
+    }
+    for addition in &plan.additions {
+        affected_interfaces.insert(&addition.baseif);
+    }
+    for update in &plan.description_updates {
+        affected_interfaces.insert(&update.baseif);
+    }
+
+    // Bundle-Ether interfaces created by the change are removed as a whole
+    // once their members have left them.
+    let mut created_bundles: Vec<&BaseIf> = Vec::new();
+
+    for baseif in affected_interfaces {
+        let created =
+            baseif.as_str().starts_with("Bundle-Ether") && base_ctx.interface(baseif).is_none();
+        if created {
+            created_bundles.push(baseif);
+        }
+
+        // 1. Revert base interface configuration
+        if created {
+            // Removed below together with the interface.
+        } else if let Some(conversion) = plan.conversion_for(baseif) {
+            lines.push(format!("no {}", conversion.new_header()));
+            lines.push(conversion.old_header());
+            for stmt in base_ctx
+                .interface(baseif)
+                .map(|iface| iface.statements.as_slice())
+                .unwrap_or_default()
+            {
+                lines.push(format!("  {}", stmt));
+            }
+            lines.push("exit".to_string());
+            lines.push(String::new());
+        } else if let Some(change) = change_spec.interface_changes.get(baseif) {
+            let applied = change.statements();
+            let base = base_ctx
+                .interface(baseif)
+                .map(|iface| iface.statements.as_slice())
+                .unwrap_or_default();
+            let restored = restore_statements(&applied, base);
+            if !restored.is_empty() {
+                lines.push(plan.interface_header(baseif));
+                for stmt in restored {
+                    lines.push(format!("  {}", stmt));
+                }
+                lines.push("exit".to_string());
+                lines.push(String::new());
+            }
+        }
+
+        // 2. Remove subinterfaces created by the change
+        let mut had_removals = false;
+        for addition in &plan.additions {
+            if &addition.baseif == baseif {
+                lines.push(format!("no interface {} l2transport", addition.iface()));
+                had_removals = true;
+            }
+        }
+        if had_removals {
+            lines.push(String::new());
+        }
+
+        // 3. Recreate subinterfaces removed by the change
+        for removal in &plan.removal_cmds {
+            if &removal.baseif == baseif {
+                lines.push(format!("interface {} l2transport", removal.iface));
+                match base_ctx.subinterface_statements(&removal.iface) {
+                    Some(stmts) => {
+                        for stmt in stmts {
+                            lines.push(format!("  {}", stmt));
+                        }
+                    }
+                    None => {
+                        let vlan = removal.iface.rsplit('.').next().unwrap_or_default();
+                        lines.push(format!("  encapsulation dot1q {}", vlan));
+                        lines.push("  rewrite ingress tag pop 1 symmetric".to_string());
                     }
                 }
+                lines.push("exit".to_string());
+                lines.push(String::new());
             }
-            for removal in &change.removals {
-                lines.push(format!("      no interface {}", removal));
+        }
+
+        // 4. Restore descriptions rewritten by the change
+        for update in &plan.description_updates {
+            if &update.baseif == baseif && !created {
+                lines.push(update.header.clone());
+                match &update.old {
+                    Some(old) => lines.push(format!("  description {}", old)),
+                    None => lines.push("  no description".to_string()),
+                }
+                lines.push("exit".to_string());
+                lines.push(String::new());
             }
-            for addition in &change.additions {
-                lines.push(format!(
-                    "      interface {}.{}",
-                    addition.baseif, addition.vlan
-                ));
-                lines.push("      exit".to_string());
+        }
+    }
+
+    for bundle in created_bundles {
+        lines.push(format!("no interface {}", bundle));
+        lines.push(String::new());
+    }
+
+    // Revert BVI interface configuration
+    let bvi_vlans: BTreeSet<_> = change_spec
+        .bvi_statements
+        .iter()
+        .filter(|(_, statements)| !statements.is_empty())
+        .map(|(vlan, _)| vlan)
+        .chain(&change_spec.bvi_additions)
+        .collect();
+    for vlan in bvi_vlans {
+        let bvi = BaseIf::new(format!("BVI{}", vlan));
+        match base_ctx.interface(&bvi) {
+            None => {
+                lines.push(format!("no interface {}", bvi));
+                lines.push(String::new());
             }
-            if change.add_bvi {
-                lines.push(format!("      routed interface BVI{}", change.vlan));
-                lines.push("      exit".to_string());
+            Some(iface) => {
+                let applied: Vec<&str> = change_spec
+                    .bvi_statements
+                    .get(vlan)
+                    .into_iter()
+                    .flatten()
+                    .map(|s| s.value.as_str())
+                    .collect();
+                let restored = restore_statements(&applied, &iface.statements);
+                if !restored.is_empty() {
+                    lines.push(format!("interface {}", bvi));
+                    for stmt in restored {
+                        lines.push(format!("  {}", stmt));
+                    }
+                    lines.push("exit".to_string());
+                    lines.push(String::new());
+                }
             }
-            lines.push("    exit".to_string());
         }
-        lines.push("  exit".to_string());
+    }
+
+    // Recreate BVI interfaces removed by the change
+    for vlan in &plan.bvi_removals {
+        let bvi = BaseIf::new(format!("BVI{}", vlan));
+        lines.push(format!("interface {}", bvi));
+        for stmt in base_ctx
+            .interface(&bvi)
+            .map(|iface| iface.statements.as_slice())
+            .unwrap_or_default()
+        {
+            lines.push(format!("  {}", stmt));
+        }
+        lines.push("exit".to_string());
+        lines.push(String::new());
+    }
+
+    let mut group_lines: Vec<String> = Vec::new();
+    for (group, changes) in plan.changes_by_group() {
+        let domain_lines = rollback_domains(&changes, change_spec, base_ctx);
+        if !domain_lines.is_empty() {
+            group_lines.push(format!("  bridge group {}", group));
+            group_lines.extend(domain_lines);
+            group_lines.push("  exit".to_string());
+        }
+    }
+
+    if !group_lines.is_empty() {
+        lines.push("l2vpn".to_string());
+        lines.append(&mut group_lines);
         lines.push("exit".to_string());
     }
 
     trim_trailing_empty_lines(&mut lines);
 
+    if lines.is_empty() {
+        return String::new();
+    }
     let mut res = lines.join("\n");
-    res.push('\n'); // 最後の exit を確定させるため
+    res.push('\n');
     res
 }
 
+/// Rollback commands for the bridge-domains of one bridge group: domains
+/// created by the change are removed and existing ones are restored.
+fn rollback_domains(
+    changes: &[&VlanChange],
+    change_spec: &ChangeSpec,
+    base_ctx: &BaseContext,
+) -> Vec<String> {
+    let mut domain_lines: Vec<String> = Vec::new();
+    for change in changes {
+        if change.remove_domain {
+            domain_lines.push(format!("    {}", change.path.domain_header()));
+            for stmt in base_ctx.domain_statements(&change.path) {
+                domain_lines.push(format!("      {}", stmt));
+                if stmt.starts_with("interface ") || stmt.starts_with("routed interface ") {
+                    domain_lines.push("      exit".to_string());
+                }
+            }
+            domain_lines.push("    exit".to_string());
+            continue;
+        }
+
+        let base_desc = base_ctx.domain_descriptions.get(&change.vlan);
+        let Some(base_desc) = base_desc.filter(|_| base_ctx.has_domain(&change.path)) else {
+            domain_lines.push(format!("    no {}", change.path.domain_header()));
+            continue;
+        };
+
+        let mut body: Vec<String> = Vec::new();
+        if change_spec.vlans.contains_key(&change.vlan) {
+            if let Some(desc) = change.description.as_ref().filter(|d| !d.value.is_empty()) {
+                match base_desc {
+                    Some(base) if base == &desc.value => {}
+                    Some(base) => body.push(format!("      description {}", base)),
+                    None => body.push("      no description".to_string()),
+                }
+            }
+        }
+        for addition in &change.additions {
+            body.push(format!("      no interface {}", addition.member()));
+        }
+        for removal in &change.removals {
+            body.push(format!("      interface {}", removal));
+            body.push("      exit".to_string());
+        }
+        if change.add_bvi {
+            body.push(format!("      no routed interface BVI{}", change.vlan));
+        }
+        if change.remove_bvi {
+            body.push(format!("      routed interface BVI{}", change.vlan));
+            body.push("      exit".to_string());
+        }
+
+        if !body.is_empty() {
+            domain_lines.push(format!("    {}", change.path.domain_header()));
+            domain_lines.append(&mut body);
+            domain_lines.push("    exit".to_string());
+        }
+    }
+    domain_lines
+}
+
+/// Compute the statements that revert `applied` on an interface whose original
+/// statements were `base`.
+///
+/// Statements are matched by the setting they configure (see
+/// [`statement_setting`]): the original statements of that setting are
+/// re-applied, and applied statements the base config did not have are
+/// negated.
+fn restore_statements(applied: &[&str], base: &[String]) -> Vec<String> {
+    let mut restored: Vec<String> = Vec::new();
+    let mut seen: BTreeSet<&str> = BTreeSet::new();
+
+    for stmt in applied {
+        let setting = statement_setting(stmt);
+        if !seen.insert(setting) {
+            continue;
+        }
+
+        let originals: Vec<&String> = base
+            .iter()
+            .filter(|b| statement_setting(b) == setting)
+            .collect();
+        if originals.is_empty() {
+            if !stmt.trim().starts_with("no ") {
+                restored.push(format!("no {}", stmt.trim()));
+            }
+        } else if !(originals.len() == 1 && originals[0] == stmt.trim()) {
+            restored.extend(originals.into_iter().cloned());
+        }
+    }
+
+    restored
+}
+
 fn trim_trailing_empty_lines(lines: &mut Vec<String>) {
     while matches!(lines.last(), Some(last) if last.is_empty()) {
         lines.pop();
@@ -1,27 +1,118 @@
+use crate::ast::Span;
+use crate::change::apply::{apply_plan, AppliedConfig};
 use crate::change::codegen;
 use crate::change::input_parser::parse_change_input;
-use crate::change::model::BaseContext;
-use crate::change::planner::ChangePlanner;
-use crate::error::Diagnostic;
-use crate::parse::tokenize;
+use crate::change::model::{BaseContext, DEFAULT_BRIDGE_GROUP};
+use crate::change::planner::{move_bundled_vlans, ChangePlanner};
+use crate::error::{Diagnostic, Severity};
+use crate::parse::parser::{lower, parse_cst};
 use crate::semantics::analyze;
 
+/// Commands generated for a change, together with their inverse and the
+/// resulting configuration.
+#[derive(Clone, Debug, Default)]
+pub struct ChangeOutput {
+    /// Commands that apply the change to the base configuration.
+    pub commands: String,
+    /// Commands that undo `commands` and restore the base configuration.
+    pub rollback: String,
+    /// Base configuration with the change applied.
+    pub applied: AppliedConfig,
+    /// Warnings about the change that did not prevent generation.
+    pub warnings: Vec<Diagnostic>,
+}
+
+/// Options controlling how change commands are generated.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ChangeOptions {
+    /// Bridge group that receives bridge-domains for VLANs that do not have
+    /// one yet. Existing bridge-domains are always edited in place.
+    pub default_bridge_group: String,
+}
+
+impl Default for ChangeOptions {
+    fn default() -> Self {
+        Self {
+            default_bridge_group: DEFAULT_BRIDGE_GROUP.to_string(),
+        }
+    }
+}
+
+impl ChangeOptions {
+    /// Options that create bridge-domains for new VLANs in bridge group `group`.
+    ///
+    /// The name must be non-empty and free of whitespace to form a valid
+    /// `l2vpn bridge group <name>` command.
+    pub fn with_default_bridge_group(group: &str) -> Result<Self, String> {
+        if group.is_empty() || group.contains(char::is_whitespace) {
+            return Err(format!("invalid bridge group `{}`", group));
+        }
+        Ok(Self {
+            default_bridge_group: group.to_string(),
+        })
+    }
+}
+
 /// High-level entry point for generating IOS XR change commands.
 pub struct ChangeEngine;
 
 impl ChangeEngine {
-    /// Generate CLI commands from base configuration text and simplified change input.
-    pub fn generate(base_config: &str, change_input: &str) -> Result<String, Diagnostic> {
-        let base_nodes = tokenize(base_config);
+    /// Generate CLI commands and their rollback from base configuration text and
+    /// simplified change input.
+    ///
+    /// Diagnostics are ordered by source position. Generation fails when any
+    /// of them is an error, returning all of them; warnings alone are
+    /// reported in [`ChangeOutput::warnings`].
+    pub fn generate(
+        base_config: &str,
+        change_input: &str,
+    ) -> Result<ChangeOutput, Vec<Diagnostic>> {
+        Self::generate_with_options(base_config, change_input, &ChangeOptions::default())
+    }
+
+    /// Like [`generate`](Self::generate), with explicit options.
+    pub fn generate_with_options(
+        base_config: &str,
+        change_input: &str,
+        options: &ChangeOptions,
+    ) -> Result<ChangeOutput, Vec<Diagnostic>> {
+        let base = parse_cst(base_config);
+        let base_nodes = lower(&base);
         let analysis = analyze(&base_nodes);
         let base_ctx = BaseContext::from_analysis(&analysis.domains, &base_nodes);
 
-        let change_spec = parse_change_input(change_input)?;
-        let planner = ChangePlanner::new(&change_spec, &base_ctx);
-        let plan = planner.plan()?;
-        let rendered_change = codegen::generate_commands(&plan, &change_spec);
+        let mut diags = Vec::new();
+        let mut change_spec = parse_change_input(change_input, &mut diags);
+        move_bundled_vlans(&mut change_spec, &base_ctx);
+        let planner = ChangePlanner::new(&change_spec, &base_ctx)
+            .with_default_bridge_group(&options.default_bridge_group);
+        let plan = planner.plan(&mut diags);
 
-        Ok(rendered_change)
+        diags.sort_by_key(|diag| {
+            diag.span
+                .map_or((u32::MAX, 0), |s| (s.line.get(), s.col_start))
+        });
+        // Ports of an `interface range` share their statements, so the same
+        // finding on the same line is reported once.
+        let mut seen: Vec<(Option<Span>, String)> = Vec::new();
+        diags.retain(|diag| {
+            let key = (diag.span, diag.format());
+            let first = !seen.contains(&key);
+            if first {
+                seen.push(key);
+            }
+            first
+        });
+        if diags.iter().any(|diag| diag.severity() == Severity::Error) {
+            return Err(diags);
+        }
+
+        Ok(ChangeOutput {
+            commands: codegen::generate_commands(&plan, &change_spec),
+            rollback: codegen::generate_rollback_commands(&plan, &change_spec, &base_ctx),
+            applied: apply_plan(&base, &plan, &change_spec),
+            warnings: diags,
+        })
     }
 }
 
@@ -59,8 +150,9 @@ exit
         ]
         .join("\n");
 
-        let rendered =
-            ChangeEngine::generate(base_config, &change_input).expect("generation succeeds");
+        let rendered = ChangeEngine::generate(base_config, &change_input)
+            .expect("generation succeeds")
+            .commands;
 
         assert!(
             rendered.contains("no interface FortyGigE0/0/0/46.300 l2transport"),
@@ -79,4 +171,1558 @@ exit
             "bridge domain for new vlan emitted"
         );
     }
+
+    #[test]
+    fn change_engine_generates_rollback() {
+        let base_config = r#"
+interface FortyGigE0/0/0/46
+  description To:demo-port
+  mtu 9000
+interface FortyGigE0/0/0/46.300 l2transport
+  description demo-web,To:demo-port
+  encapsulation dot1q 300
+  rewrite ingress tag pop 1 symmetric
+l2vpn
+  bridge group VLAN
+    bridge-domain VLAN300
+      description demo-web
+      interface FortyGigE0/0/0/46.300
+    exit
+  exit
+exit
+"#;
+
+        let change_input = [
+            "interface FortyGigE0/0/0/46",
+            "  description To:demo-port-new",
+            "  load-interval 30",
+            "  switchport mode trunk",
+            "  switchport trunk allowed vlan add 350",
+            "  switchport trunk allowed vlan remove 300",
+            "",
+            "interface BVI350",
+            "  ipv4 address 192.0.2.1 255.255.255.0",
+            "",
+            "vlan database",
+            "  vlan 350 name demo-servers",
+        ]
+        .join("\n");
+
+        let output =
+            ChangeEngine::generate(base_config, &change_input).expect("generation succeeds");
+
+        let expected = [
+            "interface FortyGigE0/0/0/46",
+            "  description To:demo-port",
+            "  no load-interval 30",
+            "exit",
+            "",
+            "no interface FortyGigE0/0/0/46.350 l2transport",
+            "",
+            "interface FortyGigE0/0/0/46.300 l2transport",
+            "  description demo-web,To:demo-port",
+            "  encapsulation dot1q 300",
+            "  rewrite ingress tag pop 1 symmetric",
+            "exit",
+            "",
+            "no interface BVI350",
+            "",
+            "l2vpn",
+            "  bridge group VLAN",
+            "    bridge-domain VLAN300",
+            "      interface FortyGigE0/0/0/46.300",
+            "      exit",
+            "    exit",
+            "    no bridge-domain VLAN350",
+            "  exit",
+            "exit",
+            "",
+        ]
+        .join("\n");
+
+        assert_eq!(output.rollback, expected);
+    }
+
+    #[test]
+    fn change_engine_rolls_back_statements_sharing_a_keyword() {
+        let base_config = r#"
+interface FortyGigE0/0/0/46
+  description To:demo-port
+  ipv6 address 2001:db8::1/64
+  service-policy output SHAPE
+  service-policy input OLD
+"#;
+
+        let change_input = [
+            "interface FortyGigE0/0/0/46",
+            "  ipv6 enable",
+            "  service-policy input POLICE",
+        ]
+        .join("\n");
+
+        let output =
+            ChangeEngine::generate(base_config, &change_input).expect("generation succeeds");
+
+        let expected = [
+            "interface FortyGigE0/0/0/46",
+            "  no ipv6 enable",
+            "  service-policy input OLD",
+            "exit",
+            "",
+        ]
+        .join("\n");
+        assert_eq!(output.rollback, expected);
+
+        let output = ChangeEngine::generate(
+            "interface FortyGigE0/0/0/46\n  description To:demo-port\n  service-policy output SHAPE\n",
+            &change_input,
+        )
+        .expect("generation succeeds");
+        assert!(output
+            .rollback
+            .contains("  no ipv6 enable\n  no service-policy input POLICE\n"));
+    }
+
+    #[test]
+    fn change_engine_converts_trunk_to_access() {
+        let base_config = r#"
+interface FortyGigE0/0/0/46
+  description To:demo-port
+interface FortyGigE0/0/0/46.300 l2transport
+  description demo-web,To:demo-port
+  encapsulation dot1q 300
+  rewrite ingress tag pop 1 symmetric
+l2vpn
+  bridge group VLAN
+    bridge-domain VLAN300
+      description demo-web
+      interface FortyGigE0/0/0/46.300
+    exit
+  exit
+exit
+"#;
+
+        let change_input = [
+            "interface FortyGigE0/0/0/46",
+            "  switchport mode access",
+            "  switchport access vlan 350",
+            "",
+            "vlan database",
+            "  vlan 350 name demo-servers",
+        ]
+        .join("\n");
+
+        let output =
+            ChangeEngine::generate(base_config, &change_input).expect("generation succeeds");
+
+        let expected = [
+            "no interface FortyGigE0/0/0/46",
+            "interface FortyGigE0/0/0/46 l2transport",
+            "  description To:demo-port",
+            "exit",
+            "",
+            "no interface FortyGigE0/0/0/46.300 l2transport",
+            "",
+            "l2vpn",
+            "  bridge group VLAN",
+            "    bridge-domain VLAN300",
+            "      no interface FortyGigE0/0/0/46.300",
+            "    exit",
+            "    bridge-domain VLAN350",
+            "      description demo-servers",
+            "      interface FortyGigE0/0/0/46",
+            "      exit",
+            "    exit",
+            "  exit",
+            "exit",
+            "",
+        ]
+        .join("\n");
+        assert_eq!(output.commands, expected);
+
+        assert!(output
+            .rollback
+            .starts_with("no interface FortyGigE0/0/0/46 l2transport\ninterface FortyGigE0/0/0/46\n  description To:demo-port\nexit\n"));
+        assert!(output
+            .applied
+            .simplified_config
+            .contains("  switchport mode access\n  switchport access vlan 350"));
+    }
+
+    #[test]
+    fn change_engine_keeps_unrelated_statements_when_converting() {
+        let base_config = r#"
+interface FortyGigE0/0/0/46
+  description To:demo-port
+  ipv6 address 2001:db8::1/64
+  service-policy output SHAPE
+"#;
+
+        let change_input = [
+            "interface FortyGigE0/0/0/46",
+            "  switchport mode access",
+            "  switchport access vlan 350",
+            "  ipv6 enable",
+            "  service-policy input POLICE",
+            "",
+            "vlan database",
+            "  vlan 350 name demo-servers",
+        ]
+        .join("\n");
+
+        let output =
+            ChangeEngine::generate(base_config, &change_input).expect("generation succeeds");
+
+        assert!(output.commands.starts_with(
+            &[
+                "no interface FortyGigE0/0/0/46",
+                "interface FortyGigE0/0/0/46 l2transport",
+                "  description To:demo-port",
+                "  ipv6 address 2001:db8::1/64",
+                "  service-policy output SHAPE",
+                "  ipv6 enable",
+                "  service-policy input POLICE",
+                "exit",
+                "",
+            ]
+            .join("\n")
+        ));
+    }
+
+    #[test]
+    fn change_engine_moves_access_vlan_and_converts_back() {
+        let base_config = r#"
+interface FortyGigE0/0/0/46 l2transport
+  description To:demo-port
+l2vpn
+  bridge group VLAN
+    bridge-domain VLAN300
+      description demo-web
+      interface FortyGigE0/0/0/46
+    exit
+    bridge-domain VLAN301
+      description demo-db
+    exit
+  exit
+exit
+"#;
+
+        let moved = ChangeEngine::generate(
+            base_config,
+            "interface FortyGigE0/0/0/46\n  switchport access vlan 301",
+        )
+        .expect("generation succeeds");
+        assert_eq!(
+            moved.commands,
+            [
+                "l2vpn",
+                "  bridge group VLAN",
+                "    bridge-domain VLAN300",
+                "      no interface FortyGigE0/0/0/46",
+                "    exit",
+                "    bridge-domain VLAN301",
+                "      interface FortyGigE0/0/0/46",
+                "      exit",
+                "    exit",
+                "  exit",
+                "exit",
+                "",
+            ]
+            .join("\n")
+        );
+
+        let trunk = ChangeEngine::generate(
+            base_config,
+            "interface FortyGigE0/0/0/46\n  switchport mode trunk\n  switchport trunk allowed vlan add 301",
+        )
+        .expect("generation succeeds");
+        assert!(trunk.commands.starts_with(
+            "no interface FortyGigE0/0/0/46 l2transport\ninterface FortyGigE0/0/0/46\n"
+        ));
+        assert!(trunk
+            .commands
+            .contains("interface FortyGigE0/0/0/46.301 l2transport"));
+        assert!(trunk
+            .applied
+            .simplified_config
+            .contains("switchport trunk allowed vlan 301"));
+
+        let diags = ChangeEngine::generate(
+            base_config,
+            "interface FortyGigE0/0/0/46\n  switchport trunk allowed vlan add 301",
+        )
+        .expect_err("implicit conversion is rejected");
+        assert_eq!(diags[0].kind.code(), "switchport-mode-conflict");
+    }
+
+    #[test]
+    fn change_engine_moves_native_vlan() {
+        let base_config = r#"
+interface FortyGigE0/0/0/46
+  description To:demo-port
+interface FortyGigE0/0/0/46.300 l2transport
+  description demo-web,To:demo-port
+  encapsulation untagged
+l2vpn
+  bridge group VLAN
+    bridge-domain VLAN300
+      description demo-web
+      interface FortyGigE0/0/0/46.300
+    exit
+    bridge-domain VLAN301
+      description demo-db
+    exit
+  exit
+exit
+"#;
+
+        let output = ChangeEngine::generate(
+            base_config,
+            "interface FortyGigE0/0/0/46\n  switchport trunk native vlan 301",
+        )
+        .expect("generation succeeds");
+
+        let expected = [
+            "no interface FortyGigE0/0/0/46.300 l2transport",
+            "",
+            "interface FortyGigE0/0/0/46.301 l2transport",
+            "  description demo-db,To:demo-port",
+            "  encapsulation untagged",
+            "exit",
+            "",
+            "l2vpn",
+            "  bridge group VLAN",
+            "    bridge-domain VLAN300",
+            "      no interface FortyGigE0/0/0/46.300",
+            "    exit",
+            "    bridge-domain VLAN301",
+            "      interface FortyGigE0/0/0/46.301",
+            "      exit",
+            "    exit",
+            "  exit",
+            "exit",
+            "",
+        ]
+        .join("\n");
+        assert_eq!(output.commands, expected);
+        assert!(output
+            .rollback
+            .contains("interface FortyGigE0/0/0/46.300 l2transport\n  description demo-web,To:demo-port\n  encapsulation untagged\nexit\n"));
+        assert!(output
+            .applied
+            .simplified_config
+            .contains("  switchport trunk native vlan 301"));
+
+        let removed = ChangeEngine::generate(
+            base_config,
+            "interface FortyGigE0/0/0/46\n  no switchport trunk native vlan",
+        )
+        .expect("generation succeeds");
+        assert!(removed
+            .commands
+            .starts_with("no interface FortyGigE0/0/0/46.300 l2transport\n"));
+
+        let diags = ChangeEngine::generate(
+            base_config,
+            "interface FortyGigE0/0/0/46\n  switchport trunk allowed vlan add 300",
+        )
+        .expect_err("native VLAN cannot also be tagged");
+        assert_eq!(diags[0].kind.code(), "native-vlan-tagged");
+    }
+
+    #[test]
+    fn change_engine_adds_and_removes_qinq_services() {
+        let base_config = r#"
+interface FortyGigE0/0/0/46
+  description To:provider
+interface FortyGigE0/0/0/46.200 l2transport
+  description cust-a,To:provider
+  encapsulation dot1q 100 second-dot1q 200
+  rewrite ingress tag pop 2 symmetric
+interface FortyGigE0/0/0/46.300 l2transport
+  description web,To:provider
+  encapsulation dot1q 300
+  rewrite ingress tag pop 1 symmetric
+l2vpn
+  bridge group VLAN
+    bridge-domain VLAN200
+      description cust-a
+      interface FortyGigE0/0/0/46.200
+    exit
+    bridge-domain VLAN300
+      description web
+      interface FortyGigE0/0/0/46.300
+    exit
+  exit
+exit
+"#;
+
+        let change_input = [
+            "vlan database",
+            "  vlan 201 name cust-b",
+            "",
+            "interface FortyGigE0/0/0/46",
+            "  switchport dot1q-tunnel vlan 100 inner 201",
+            "  no switchport dot1q-tunnel vlan 100 inner 200",
+        ]
+        .join("\n");
+
+        let output =
+            ChangeEngine::generate(base_config, &change_input).expect("generation succeeds");
+
+        let expected = [
+            "no interface FortyGigE0/0/0/46.200 l2transport",
+            "",
+            "interface FortyGigE0/0/0/46.201 l2transport",
+            "  description cust-b,To:provider",
+            "  encapsulation dot1q 100 second-dot1q 201",
+            "  rewrite ingress tag pop 2 symmetric",
+            "exit",
+            "",
+            "l2vpn",
+            "  bridge group VLAN",
+            "    bridge-domain VLAN200",
+            "      no interface FortyGigE0/0/0/46.200",
+            "    exit",
+            "    bridge-domain VLAN201",
+            "      description cust-b",
+            "      interface FortyGigE0/0/0/46.201",
+            "      exit",
+            "    exit",
+            "  exit",
+            "exit",
+            "",
+        ]
+        .join("\n");
+        assert_eq!(output.commands, expected);
+        assert!(output.applied.simplified_config.contains(
+            "  switchport trunk allowed vlan 300\n  switchport dot1q-tunnel vlan 100 inner 201\n"
+        ));
+
+        let diags = ChangeEngine::generate(
+            base_config,
+            "interface FortyGigE0/0/0/46\n  switchport dot1q-tunnel vlan 300 inner 300\n  no switchport dot1q-tunnel vlan 100 inner 999",
+        )
+        .expect_err("conflicting and unknown services are rejected");
+        let codes: Vec<&str> = diags.iter().map(|diag| diag.kind.code()).collect();
+        assert_eq!(codes, vec!["subinterface-conflict", "qinq-not-present"]);
+    }
+
+    #[test]
+    fn change_engine_separates_qinq_services_sharing_an_inner_tag() {
+        let base_config = r#"
+interface FortyGigE0/0/0/46
+  description To:provider
+interface FortyGigE0/0/0/46.200 l2transport
+  description cust-a,To:provider
+  encapsulation dot1q 100 second-dot1q 200
+  rewrite ingress tag pop 2 symmetric
+l2vpn
+  bridge group VLAN
+    bridge-domain VLAN200
+      description cust-a
+      interface FortyGigE0/0/0/46.200
+    exit
+  exit
+exit
+"#;
+
+        let output = ChangeEngine::generate(
+            base_config,
+            "interface FortyGigE0/0/0/46\n  switchport dot1q-tunnel vlan 101 inner 200\n",
+        )
+        .expect("generation succeeds");
+
+        let expected = [
+            "interface FortyGigE0/0/0/46.101 l2transport",
+            "  description cust-a,To:provider",
+            "  encapsulation dot1q 101 second-dot1q 200",
+            "  rewrite ingress tag pop 2 symmetric",
+            "exit",
+            "",
+            "l2vpn",
+            "  bridge group VLAN",
+            "    bridge-domain VLAN101-200",
+            "      interface FortyGigE0/0/0/46.101",
+            "      exit",
+            "    exit",
+            "  exit",
+            "exit",
+            "",
+        ]
+        .join("\n");
+        assert_eq!(output.commands, expected);
+        assert!(output
+            .rollback
+            .contains("    no bridge-domain VLAN101-200\n"));
+        assert!(output.applied.simplified_config.contains(
+            "  switchport dot1q-tunnel vlan 100 inner 200\n  switchport dot1q-tunnel vlan 101 inner 200\n"
+        ));
+
+        // Each service keeps its own bridge-domain once both exist.
+        let diags = analyze(&lower(&parse_cst(&output.applied.text))).diagnostics;
+        assert!(diags.is_empty(), "{:?}", diags);
+        let output = ChangeEngine::generate(
+            &output.applied.text,
+            "interface FortyGigE0/0/0/46\n  no switchport dot1q-tunnel vlan 101 inner 200\n",
+        )
+        .expect("generation succeeds");
+        assert_eq!(
+            output.commands,
+            [
+                "no interface FortyGigE0/0/0/46.101 l2transport",
+                "",
+                "l2vpn",
+                "  bridge group VLAN",
+                "    bridge-domain VLAN101-200",
+                "      no interface FortyGigE0/0/0/46.101",
+                "    exit",
+                "  exit",
+                "exit",
+                "",
+            ]
+            .join("\n")
+        );
+    }
+
+    #[test]
+    fn change_engine_keeps_existing_routed_bvi() {
+        let base_config = r#"
+interface BVI300
+  description demo-web
+l2vpn
+  bridge group VLAN
+    bridge-domain VLAN300
+      description demo-web
+      routed interface BVI300
+    exit
+  exit
+exit
+"#;
+
+        let change_input = "interface BVI300\n  ipv4 address 192.0.2.1 255.255.255.0";
+
+        let output =
+            ChangeEngine::generate(base_config, change_input).expect("generation succeeds");
+
+        assert_eq!(
+            output.commands,
+            "interface BVI300\n  ipv4 address 192.0.2.1 255.255.255.0\nexit\n"
+        );
+        assert_eq!(
+            output.rollback,
+            "interface BVI300\n  no ipv4 address 192.0.2.1 255.255.255.0\nexit\n"
+        );
+    }
+
+    #[test]
+    fn change_engine_reports_all_diagnostics() {
+        let base_config = r#"
+interface FortyGigE0/0/0/46
+  description To:demo-port
+interface FortyGigE0/0/0/47
+"#;
+
+        let change_input = [
+            "vlan database",
+            "  vlan 350",
+            "",
+            "interface FortyGigE0/0/0/46",
+            "  switchport trunk allowed vlan add 350 400",
+            "  switchport trunk allowed vlan remove 300",
+            "  switchport trunk allowed vlan add 10-5",
+            "",
+            "interface FortyGigE0/0/0/47",
+            "  switchport trunk allowed vlan add 350",
+        ]
+        .join("\n");
+
+        let diags = ChangeEngine::generate(base_config, &change_input)
+            .expect_err("generation reports errors");
+        let lines: Vec<u32> = diags
+            .iter()
+            .map(|diag| diag.span.expect("every diagnostic has a span").line.get())
+            .collect();
+
+        assert_eq!(lines, vec![2, 5, 6, 7, 9], "diagnostics: {:?}", diags);
+    }
+
+    #[test]
+    fn diagnostic_columns_match_original_input() {
+        let base_config = "interface FortyGigE0/0/0/46\n  description To:demo-port\n";
+        let change_input = [
+            "    interface FortyGigE0/0/0/46",
+            "      switchport trunk allowed vlan remove 300",
+        ]
+        .join("\n");
+
+        let diags = ChangeEngine::generate(base_config, &change_input)
+            .expect_err("generation reports errors");
+
+        assert_eq!(diags.len(), 1);
+        assert_eq!(diags[0].span, Some(Span::new(2, 6, 46)));
+    }
+
+    #[test]
+    fn change_engine_targets_existing_bridge_groups() {
+        let base_config = r#"
+interface FortyGigE0/0/0/46
+  description To:server1
+interface FortyGigE0/0/0/47
+  description To:server2
+interface FortyGigE0/0/0/47.500 l2transport
+  description web,To:server2
+  encapsulation dot1q 500
+  rewrite ingress tag pop 1 symmetric
+l2vpn
+  bridge group CUSTOMER
+    bridge-domain web-servers
+      description web
+      interface FortyGigE0/0/0/47.500
+    exit
+  exit
+exit
+"#;
+
+        let change_input = [
+            "vlan database",
+            "  vlan 600 name db",
+            "",
+            "interface FortyGigE0/0/0/46",
+            "  switchport trunk allowed vlan add 500",
+            "  switchport trunk allowed vlan add 600",
+        ]
+        .join("\n");
+
+        let options = ChangeOptions::with_default_bridge_group("TENANT").expect("valid name");
+        let output = ChangeEngine::generate_with_options(base_config, &change_input, &options)
+            .expect("generation succeeds");
+
+        let l2vpn = [
+            "l2vpn",
+            "  bridge group CUSTOMER",
+            "    bridge-domain web-servers",
+            "      interface FortyGigE0/0/0/46.500",
+            "      exit",
+            "    exit",
+            "  exit",
+            "  bridge group TENANT",
+            "    bridge-domain VLAN600",
+            "      description db",
+            "      interface FortyGigE0/0/0/46.600",
+            "      exit",
+            "    exit",
+            "  exit",
+            "exit",
+        ]
+        .join("\n");
+        assert!(
+            output.commands.ends_with(&format!("{}\n", l2vpn)),
+            "{}",
+            output.commands
+        );
+        assert!(output.rollback.contains(
+            "  bridge group CUSTOMER\n    bridge-domain web-servers\n      no interface FortyGigE0/0/0/46.500\n"
+        ));
+        assert!(output
+            .rollback
+            .contains("  bridge group TENANT\n    no bridge-domain VLAN600\n"));
+        // The applied config keeps the base config's two-space layout.
+        assert!(output.applied.text.contains(
+            "  bridge group CUSTOMER\n    bridge-domain web-servers\n      description web\n      interface FortyGigE0/0/0/47.500\n      interface FortyGigE0/0/0/46.500\n    exit\n"
+        ));
+
+        let default =
+            ChangeEngine::generate(base_config, &change_input).expect("generation succeeds");
+        assert!(default
+            .commands
+            .contains("  bridge group VLAN\n    bridge-domain VLAN600\n"));
+    }
+
+    #[test]
+    fn change_options_reject_invalid_bridge_group_names() {
+        for name in ["", "MY GROUP", "TENANT\n"] {
+            assert_eq!(
+                ChangeOptions::with_default_bridge_group(name).unwrap_err(),
+                format!("invalid bridge group `{}`", name)
+            );
+        }
+    }
+
+    #[test]
+    fn change_engine_uses_existing_subinterface_names() {
+        let base_config = r#"
+interface FortyGigE0/0/0/46
+  description To:server1
+interface FortyGigE0/0/0/46.10 l2transport
+  description acme,To:server1
+  encapsulation dot1q 500
+  rewrite ingress tag pop 1 symmetric
+l2vpn
+  bridge group CUSTOMER
+    bridge-domain ACME
+      description acme
+      interface FortyGigE0/0/0/46.10
+    exit
+  exit
+exit
+"#;
+
+        let change_input = [
+            "interface FortyGigE0/0/0/46",
+            "  switchport trunk allowed vlan remove 500",
+        ]
+        .join("\n");
+        let output =
+            ChangeEngine::generate(base_config, &change_input).expect("generation succeeds");
+        assert!(output
+            .commands
+            .starts_with("no interface FortyGigE0/0/0/46.10 l2transport\n"));
+        assert!(output
+            .commands
+            .contains("    bridge-domain ACME\n      no interface FortyGigE0/0/0/46.10\n"));
+        assert!(output.rollback.starts_with(
+            "interface FortyGigE0/0/0/46.10 l2transport\n  description acme,To:server1\n  encapsulation dot1q 500\n"
+        ));
+        assert!(!output.rollback.contains(".500"));
+
+        let output = ChangeEngine::generate(base_config, "vlan database\n  vlan 500 name acme2\n")
+            .expect("generation succeeds");
+        assert!(output.commands.starts_with(
+            "interface FortyGigE0/0/0/46.10 l2transport\n  description acme2,To:server1\nexit\n"
+        ));
+        assert!(!output.commands.contains(".500"));
+
+        let change_input = [
+            "vlan database",
+            "  vlan 10 name other",
+            "",
+            "interface FortyGigE0/0/0/46",
+            "  switchport trunk allowed vlan add 10",
+        ]
+        .join("\n");
+        let diags = ChangeEngine::generate(base_config, &change_input)
+            .expect_err("subinterface name is taken");
+        assert_eq!(diags[0].kind.code(), "subinterface-conflict");
+        assert_eq!(diags[0].span.unwrap().line.get(), 5);
+    }
+
+    #[test]
+    fn change_engine_tears_down_vlans_and_bvis() {
+        let base_config = r#"
+interface FortyGigE0/0/0/46
+ description To:server1
+!
+interface FortyGigE0/0/0/46.300 l2transport
+ description web,To:server1
+ encapsulation dot1q 300
+ rewrite ingress tag pop 1 symmetric
+!
+interface FortyGigE0/0/0/47
+ description To:server2
+!
+interface FortyGigE0/0/0/47.300 l2transport
+ description web,To:server2
+ encapsulation untagged
+!
+interface BVI300
+ description web
+ ipv4 address 192.0.2.1 255.255.255.0
+!
+interface BVI400
+ description db
+!
+l2vpn
+ bridge group VLAN
+  bridge-domain VLAN300
+   description web
+   interface FortyGigE0/0/0/46.300
+   !
+   interface FortyGigE0/0/0/47.300
+   !
+   routed interface BVI300
+   !
+  !
+  bridge-domain VLAN400
+   description db
+   routed interface BVI400
+   !
+  !
+ !
+!
+"#;
+
+        let change_input = "vlan database\n  no vlan 300\n\nno interface BVI400\n";
+        let output =
+            ChangeEngine::generate(base_config, change_input).expect("generation succeeds");
+
+        let expected = [
+            "no interface FortyGigE0/0/0/46.300 l2transport",
+            "",
+            "no interface FortyGigE0/0/0/47.300 l2transport",
+            "",
+            "no interface BVI300",
+            "",
+            "no interface BVI400",
+            "",
+            "l2vpn",
+            "  bridge group VLAN",
+            "    no bridge-domain VLAN300",
+            "    bridge-domain VLAN400",
+            "      no routed interface BVI400",
+            "    exit",
+            "  exit",
+            "exit",
+            "",
+        ]
+        .join("\n");
+        assert_eq!(output.commands, expected);
+
+        let codes: Vec<&str> = output.warnings.iter().map(|d| d.kind.code()).collect();
+        assert_eq!(codes, vec!["bvi-has-address"]);
+        assert_eq!(output.warnings[0].span.map(|s| s.line.get()), Some(2));
+
+        assert!(output.rollback.contains(
+            "interface BVI300\n  description web\n  ipv4 address 192.0.2.1 255.255.255.0\nexit\n"
+        ));
+        assert!(output.rollback.contains(
+            "    bridge-domain VLAN300\n      description web\n      interface FortyGigE0/0/0/46.300\n      exit\n      interface FortyGigE0/0/0/47.300\n      exit\n      routed interface BVI300\n      exit\n    exit\n"
+        ));
+        assert!(output
+            .rollback
+            .contains("    bridge-domain VLAN400\n      routed interface BVI400\n      exit\n"));
+
+        assert!(!output.applied.text.contains("VLAN300"));
+        assert!(!output.applied.text.contains("BVI"));
+        assert!(output
+            .applied
+            .text
+            .contains("  bridge-domain VLAN400\n   description db\n  !\n"));
+    }
+
+    #[test]
+    fn change_engine_rejects_invalid_teardowns() {
+        let base_config = r#"
+interface FortyGigE0/0/0/46
+  description To:server1
+interface FortyGigE0/0/0/46.300 l2transport
+  description web,To:server1
+  encapsulation dot1q 300
+  rewrite ingress tag pop 1 symmetric
+l2vpn
+  bridge group VLAN
+    bridge-domain VLAN300
+      description web
+      interface FortyGigE0/0/0/46.300
+"#;
+
+        let change_input = [
+            "vlan database",
+            "  no vlan 300",
+            "  no vlan 999",
+            "",
+            "no interface BVI300",
+            "",
+            "interface FortyGigE0/0/0/47",
+            "  description To:server2",
+            "  switchport trunk allowed vlan add 300",
+        ]
+        .join("\n");
+
+        let diags =
+            ChangeEngine::generate(base_config, &change_input).expect_err("teardowns are invalid");
+        let codes: Vec<&str> = diags.iter().map(|d| d.kind.code()).collect();
+        assert_eq!(
+            codes,
+            vec!["vlan-not-found", "vlan-removal-conflict"],
+            "{:?}",
+            diags
+        );
+    }
+
+    #[test]
+    fn change_engine_propagates_vlan_renames() {
+        let base_config = r#"
+interface FortyGigE0/0/0/46
+ description To:server1
+!
+interface FortyGigE0/0/0/46.300 l2transport
+ description web,To:server1
+ encapsulation dot1q 300
+ rewrite ingress tag pop 1 symmetric
+!
+interface FortyGigE0/0/0/47
+ description To:server2
+!
+interface FortyGigE0/0/0/47.300 l2transport
+ description web,To:server2
+ encapsulation untagged
+!
+interface BVI300
+ description web
+!
+l2vpn
+ bridge group VLAN
+  bridge-domain VLAN300
+   description web
+   interface FortyGigE0/0/0/46.300
+   !
+   interface FortyGigE0/0/0/47.300
+   !
+   routed interface BVI300
+   !
+  !
+ !
+!
+"#;
+
+        let output = ChangeEngine::generate(base_config, "vlan database\n  vlan 300 name www\n")
+            .expect("generation succeeds");
+
+        let expected = [
+            "interface BVI300",
+            "  description www",
+            "exit",
+            "",
+            "interface FortyGigE0/0/0/46.300 l2transport",
+            "  description www,To:server1",
+            "exit",
+            "",
+            "interface FortyGigE0/0/0/47.300 l2transport",
+            "  description www,To:server2",
+            "exit",
+            "",
+            "l2vpn",
+            "  bridge group VLAN",
+            "    bridge-domain VLAN300",
+            "      description www",
+            "    exit",
+            "  exit",
+            "exit",
+            "",
+        ]
+        .join("\n");
+        assert_eq!(output.commands, expected);
+        assert!(output.rollback.starts_with(
+            "interface BVI300\n  description web\nexit\n\ninterface FortyGigE0/0/0/46.300 l2transport\n  description web,To:server1\nexit\n"
+        ));
+        assert!(output
+            .applied
+            .text
+            .contains("interface FortyGigE0/0/0/47.300 l2transport\n description www,To:server2\n encapsulation untagged\n"));
+
+        let unchanged = ChangeEngine::generate(base_config, "vlan database\n  vlan 300 name web\n")
+            .expect("generation succeeds");
+        assert!(!unchanged
+            .commands
+            .contains("interface FortyGigE0/0/0/46.300"));
+    }
+
+    #[test]
+    fn change_engine_propagates_interface_descriptions() {
+        let base_config = r#"
+interface FortyGigE0/0/0/46
+ description To:server1
+!
+interface FortyGigE0/0/0/46.300 l2transport
+ description web,To:server1
+ encapsulation dot1q 300
+ rewrite ingress tag pop 1 symmetric
+!
+interface FortyGigE0/0/0/46.310 l2transport
+ description cust,To:server1
+ encapsulation dot1q 100 second-dot1q 310
+ rewrite ingress tag pop 2 symmetric
+!
+l2vpn
+ bridge group VLAN
+  bridge-domain VLAN300
+   description web
+   interface FortyGigE0/0/0/46.300
+   !
+  !
+  bridge-domain VLAN310
+   description cust
+   interface FortyGigE0/0/0/46.310
+   !
+  !
+ !
+!
+"#;
+
+        let output = ChangeEngine::generate(
+            base_config,
+            "interface FortyGigE0/0/0/46\n  description To:server9\n",
+        )
+        .expect("generation succeeds");
+
+        let expected = [
+            "interface FortyGigE0/0/0/46",
+            "  description To:server9",
+            "exit",
+            "",
+            "interface FortyGigE0/0/0/46.300 l2transport",
+            "  description web,To:server9",
+            "exit",
+            "",
+            "interface FortyGigE0/0/0/46.310 l2transport",
+            "  description cust,To:server9",
+            "exit",
+            "",
+        ]
+        .join("\n");
+        assert_eq!(output.commands, expected);
+        assert!(output.rollback.contains(
+            "interface FortyGigE0/0/0/46.310 l2transport\n  description cust,To:server1\nexit\n"
+        ));
+
+        let removed = ChangeEngine::generate(
+            base_config,
+            "interface FortyGigE0/0/0/46\n  description To:server9\n  switchport trunk allowed vlan remove 300\n",
+        )
+        .expect("generation succeeds");
+        assert!(!removed.commands.contains("web,To:server9"));
+    }
+
+    #[test]
+    fn change_engine_moves_trunk_vlans_onto_new_bundle() {
+        let base_config = r#"
+interface FortyGigE0/0/0/46
+ description To:server1
+!
+interface FortyGigE0/0/0/46.300 l2transport
+ description web,To:server1
+ encapsulation dot1q 300
+ rewrite ingress tag pop 1 symmetric
+!
+interface FortyGigE0/0/0/47
+ description To:server1-2
+!
+l2vpn
+ bridge group VLAN
+  bridge-domain VLAN300
+   description web
+   interface FortyGigE0/0/0/46.300
+   !
+  !
+ !
+!
+"#;
+
+        let change_input = [
+            "interface Bundle-Ether1",
+            "  description To:server1",
+            "  switchport trunk allowed vlan add 300",
+            "",
+            "interface FortyGigE0/0/0/46",
+            "  switchport trunk allowed vlan none",
+            "  channel-group 1 mode active",
+            "",
+            "interface FortyGigE0/0/0/47",
+            "  channel-group 1 mode active",
+        ]
+        .join("\n");
+
+        let output =
+            ChangeEngine::generate(base_config, &change_input).expect("generation succeeds");
+
+        let expected = [
+            "interface Bundle-Ether1",
+            "  description To:server1",
+            "exit",
+            "",
+            "interface Bundle-Ether1.300 l2transport",
+            "  description web,To:server1",
+            "  encapsulation dot1q 300",
+            "  rewrite ingress tag pop 1 symmetric",
+            "exit",
+            "",
+            "interface FortyGigE0/0/0/46",
+            "  bundle id 1 mode active",
+            "exit",
+            "",
+            "no interface FortyGigE0/0/0/46.300 l2transport",
+            "",
+            "interface FortyGigE0/0/0/47",
+            "  bundle id 1 mode active",
+            "exit",
+            "",
+            "l2vpn",
+            "  bridge group VLAN",
+            "    bridge-domain VLAN300",
+            "      no interface FortyGigE0/0/0/46.300",
+            "      interface Bundle-Ether1.300",
+            "      exit",
+            "    exit",
+            "  exit",
+            "exit",
+            "",
+        ]
+        .join("\n");
+        assert_eq!(output.commands, expected);
+        assert!(output
+            .rollback
+            .contains("interface FortyGigE0/0/0/47\n  no bundle id 1 mode active\nexit\n"));
+        assert!(output.applied.text.contains(
+            "interface FortyGigE0/0/0/47\n description To:server1-2\n bundle id 1 mode active\n"
+        ));
+    }
+
+    #[test]
+    fn change_engine_rolls_back_created_bundles() {
+        let base_config = r#"
+interface FortyGigE0/0/0/46
+ description To:server1
+!
+interface FortyGigE0/0/0/46.300 l2transport
+ description web,To:server1
+ encapsulation dot1q 300
+ rewrite ingress tag pop 1 symmetric
+!
+l2vpn
+ bridge group VLAN
+  bridge-domain VLAN300
+   description web
+   interface FortyGigE0/0/0/46.300
+   !
+  !
+ !
+!
+"#;
+
+        let change_input = [
+            "interface Bundle-Ether1",
+            "  description To:server1",
+            "  mtu 9000",
+            "",
+            "interface FortyGigE0/0/0/46",
+            "  channel-group 1 mode active",
+        ]
+        .join("\n");
+
+        let output =
+            ChangeEngine::generate(base_config, &change_input).expect("generation succeeds");
+
+        let expected = [
+            "no interface Bundle-Ether1.300 l2transport",
+            "",
+            "interface FortyGigE0/0/0/46",
+            "  no bundle id 1 mode active",
+            "exit",
+            "",
+            "interface FortyGigE0/0/0/46.300 l2transport",
+            "  description web,To:server1",
+            "  encapsulation dot1q 300",
+            "  rewrite ingress tag pop 1 symmetric",
+            "exit",
+            "",
+            "no interface Bundle-Ether1",
+            "",
+            "l2vpn",
+            "  bridge group VLAN",
+            "    bridge-domain VLAN300",
+            "      no interface Bundle-Ether1.300",
+            "      interface FortyGigE0/0/0/46.300",
+            "      exit",
+            "    exit",
+            "  exit",
+            "exit",
+            "",
+        ]
+        .join("\n");
+        assert_eq!(output.rollback, expected);
+    }
+
+    #[test]
+    fn change_engine_moves_port_vlans_onto_bundle_automatically() {
+        let base_config = r#"
+interface Bundle-Ether1
+ description To:server1
+!
+interface Bundle-Ether1.301 l2transport
+ description db,To:server1
+ encapsulation dot1q 301
+ rewrite ingress tag pop 1 symmetric
+!
+interface FortyGigE0/0/0/46
+ description To:server1-1
+!
+interface FortyGigE0/0/0/46.300 l2transport
+ description web,To:server1-1
+ encapsulation dot1q 300
+ rewrite ingress tag pop 1 symmetric
+!
+interface FortyGigE0/0/0/46.301 l2transport
+ description db,To:server1-1
+ encapsulation dot1q 301
+ rewrite ingress tag pop 1 symmetric
+!
+l2vpn
+ bridge group VLAN
+  bridge-domain VLAN300
+   description web
+   interface FortyGigE0/0/0/46.300
+   !
+  !
+  bridge-domain VLAN301
+   description db
+   interface Bundle-Ether1.301
+   !
+   interface FortyGigE0/0/0/46.301
+   !
+  !
+ !
+!
+"#;
+
+        let output = ChangeEngine::generate(
+            base_config,
+            "interface FortyGigE0/0/0/46\n  channel-group 1 mode active\n",
+        )
+        .expect("generation succeeds");
+
+        let expected = [
+            "interface Bundle-Ether1.300 l2transport",
+            "  description web,To:server1",
+            "  encapsulation dot1q 300",
+            "  rewrite ingress tag pop 1 symmetric",
+            "exit",
+            "",
+            "interface FortyGigE0/0/0/46",
+            "  bundle id 1 mode active",
+            "exit",
+            "",
+            "no interface FortyGigE0/0/0/46.300 l2transport",
+            "no interface FortyGigE0/0/0/46.301 l2transport",
+            "",
+            "l2vpn",
+            "  bridge group VLAN",
+            "    bridge-domain VLAN300",
+            "      no interface FortyGigE0/0/0/46.300",
+            "      interface Bundle-Ether1.300",
+            "      exit",
+            "    exit",
+            "    bridge-domain VLAN301",
+            "      no interface FortyGigE0/0/0/46.301",
+            "    exit",
+            "  exit",
+            "exit",
+            "",
+        ]
+        .join("\n");
+        assert_eq!(output.commands, expected);
+    }
+
+    #[test]
+    fn change_engine_removes_bundle_members() {
+        let base_config = r#"
+interface Bundle-Ether1
+ description To:server1
+!
+interface HundredGigE0/0/0/10
+ description To:server1-1
+ bundle id 1 mode active
+!
+interface HundredGigE0/0/0/11
+ description To:server1-2
+ bundle id 1 mode active
+!
+"#;
+
+        let output = ChangeEngine::generate(
+            base_config,
+            "interface HundredGigE0/0/0/11\n  no channel-group\n",
+        )
+        .expect("generation succeeds");
+        assert_eq!(
+            output.commands,
+            "interface HundredGigE0/0/0/11\n  no bundle id\nexit\n"
+        );
+        assert_eq!(
+            output.rollback,
+            "interface HundredGigE0/0/0/11\n  bundle id 1 mode active\nexit\n"
+        );
+    }
+
+    #[test]
+    fn change_engine_rejects_invalid_bundle_membership() {
+        let base_config = r#"
+interface Bundle-Ether1
+ description To:server1
+!
+interface HundredGigE0/0/0/10
+ description To:server1-1
+ bundle id 1 mode active
+!
+interface FortyGigE0/0/0/46
+ description To:server2
+!
+interface FortyGigE0/0/0/46.300 l2transport
+ description web,To:server2
+ encapsulation dot1q 300
+ rewrite ingress tag pop 1 symmetric
+!
+interface FortyGigE0/0/0/47
+ description To:server3
+!
+interface FortyGigE0/0/0/48
+ description To:server4
+!
+l2vpn
+ bridge group VLAN
+  bridge-domain VLAN300
+   description web
+   interface FortyGigE0/0/0/46.300
+   !
+  !
+ !
+!
+"#;
+
+        let change_input = [
+            "interface FortyGigE0/0/0/46",
+            "  channel-group 2 mode active",
+            "",
+            "interface FortyGigE0/0/0/47",
+            "  channel-group 1 mode active",
+            "",
+            "interface FortyGigE0/0/0/48",
+            "  no channel-group",
+            "  channel-group 1 mode desirable",
+        ]
+        .join("\n");
+
+        let diags = ChangeEngine::generate(base_config, &change_input)
+            .expect_err("memberships are invalid");
+        let codes: Vec<&str> = diags.iter().map(|d| d.kind.code()).collect();
+        assert_eq!(
+            codes,
+            vec![
+                "bundle-not-found",
+                "bundle-speed-mismatch",
+                "not-bundle-member",
+                "unsupported-bundle-mode",
+            ],
+            "{:?}",
+            diags
+        );
+
+        let diags = ChangeEngine::generate(
+            base_config,
+            "interface Bundle-Ether2\n  description To:server2\n\ninterface FortyGigE0/0/0/46\n  switchport trunk allowed vlan add 300\n  channel-group 2 mode active\n",
+        )
+        .expect_err("member still carries VLANs");
+        assert_eq!(diags[0].kind.code(), "bundled-interface-vlans");
+    }
+
+    #[test]
+    fn change_engine_expands_interface_ranges() {
+        let base_config = r#"
+interface FortyGigE0/0/0/10
+ description To:server10
+!
+interface FortyGigE0/0/0/11
+ description To:server11
+!
+interface FortyGigE0/0/0/12
+ description To:server12
+!
+"#;
+
+        let change_input = [
+            "vlan database",
+            "  vlan 300 name web",
+            "",
+            "interface range FortyGigE0/0/0/10 - 11, FortyGigE0/0/0/12",
+            "  switchport trunk allowed vlan add 300",
+        ]
+        .join("\n");
+
+        let output =
+            ChangeEngine::generate(base_config, &change_input).expect("generation succeeds");
+        for port in 10..=12 {
+            assert!(output.commands.contains(&format!(
+                "interface FortyGigE0/0/0/{}.300 l2transport\n  description web,To:server{}\n",
+                port, port
+            )));
+        }
+        assert!(output
+            .commands
+            .contains("      interface FortyGigE0/0/0/10.300\n      exit\n      interface FortyGigE0/0/0/11.300\n"));
+
+        let diags = ChangeEngine::generate(
+            base_config,
+            "interface range FortyGigE0/0/0/10 - 12\n  switchport trunk allowed vlan add 999 x\n",
+        )
+        .expect_err("range statement is invalid");
+        let codes: Vec<&str> = diags.iter().map(|d| d.kind.code()).collect();
+        assert_eq!(codes, vec!["invalid-vlan-number"], "{:?}", diags);
+
+        let diags = ChangeEngine::generate(
+            base_config,
+            "interface range FortyGigE0/0/0/10 - 13\n  switchport trunk allowed vlan add 999\n",
+        )
+        .expect_err("VLAN is undefined and a port has no description");
+        let codes: Vec<&str> = diags.iter().map(|d| d.kind.code()).collect();
+        assert_eq!(
+            codes,
+            vec!["missing-description", "vlan-not-defined"],
+            "{:?}",
+            diags
+        );
+        assert!(diags.iter().all(|d| d.span.unwrap().line.get() <= 2));
+        assert_eq!(diags[0].span.unwrap().line.get(), 1);
+
+        let diags = ChangeEngine::generate(base_config, "interface range FortyGigE0/0/0/12 - 10\n")
+            .expect_err("range is reversed");
+        assert_eq!(diags[0].kind.code(), "invalid-interface-range");
+
+        let diags =
+            ChangeEngine::generate(base_config, "interface range FortyGigE0/0/0/1 - 50000000\n")
+                .expect_err("range is too large");
+        assert_eq!(diags[0].kind.code(), "invalid-interface-range");
+    }
+
+    #[test]
+    fn change_engine_warns_about_vlans_on_shutdown_interfaces() {
+        let base_config = r#"
+interface FortyGigE0/0/0/46
+ description To:server1
+ shutdown
+!
+"#;
+
+        let change_input = [
+            "vlan database",
+            "  vlan 300 name web",
+            "",
+            "interface FortyGigE0/0/0/46",
+            "  switchport trunk allowed vlan add 300",
+        ]
+        .join("\n");
+        let output = ChangeEngine::generate(base_config, &change_input).expect("warnings only");
+        let codes: Vec<&str> = output.warnings.iter().map(|d| d.kind.code()).collect();
+        assert_eq!(codes, vec!["vlans-on-shutdown-interface"]);
+        assert_eq!(output.warnings[0].span.unwrap().line.get(), 5);
+
+        let output =
+            ChangeEngine::generate(base_config, &format!("{}\n  no shutdown", change_input))
+                .expect("generation succeeds");
+        assert!(output.warnings.is_empty());
+        assert!(output
+            .commands
+            .starts_with("interface FortyGigE0/0/0/46\n  no shutdown\nexit\n"));
+        assert!(output
+            .rollback
+            .starts_with("interface FortyGigE0/0/0/46\n  shutdown\nexit\n"));
+
+        let output = ChangeEngine::generate(
+            "interface FortyGigE0/0/0/46\n description To:server1\n",
+            &format!("{}\n  shutdown", change_input),
+        )
+        .expect("warnings only");
+        assert_eq!(output.warnings.len(), 1);
+
+        let output = ChangeEngine::generate(
+            base_config,
+            &format!("{}\n  shutdown\n  no shutdown", change_input),
+        )
+        .expect("generation succeeds");
+        assert!(output
+            .commands
+            .starts_with("interface FortyGigE0/0/0/46\n  no shutdown\nexit\n"));
+        assert!(output
+            .rollback
+            .starts_with("interface FortyGigE0/0/0/46\n  shutdown\nexit\n"));
+    }
+
+    #[test]
+    fn change_engine_reads_formal_base_config() {
+        let indented = r#"interface FortyGigE0/0/0/46
+ description To:server1
+!
+interface FortyGigE0/0/0/46.300 l2transport
+ description servers,To:server1
+ encapsulation dot1q 300
+ rewrite ingress tag pop 1 symmetric
+!
+l2vpn
+ bridge group VLAN
+  bridge-domain VLAN300
+   description servers
+   interface FortyGigE0/0/0/46.300
+   !
+  !
+ !
+!
+"#;
+        let formal = [
+            "interface FortyGigE0/0/0/46 description To:server1",
+            "interface FortyGigE0/0/0/46.300 l2transport description servers,To:server1",
+            "interface FortyGigE0/0/0/46.300 l2transport encapsulation dot1q 300",
+            "interface FortyGigE0/0/0/46.300 l2transport rewrite ingress tag pop 1 symmetric",
+            "l2vpn bridge group VLAN bridge-domain VLAN300 description servers",
+            "l2vpn bridge group VLAN bridge-domain VLAN300 interface FortyGigE0/0/0/46.300",
+        ]
+        .join("\n");
+        let change_input = [
+            "vlan database",
+            "  vlan 400 name web",
+            "",
+            "interface FortyGigE0/0/0/46",
+            "  switchport trunk allowed vlan add 400",
+            "  switchport trunk allowed vlan remove 300",
+        ]
+        .join("\n");
+
+        let expected =
+            ChangeEngine::generate(indented, &change_input).expect("generation succeeds");
+        let output = ChangeEngine::generate(&formal, &change_input).expect("generation succeeds");
+
+        assert_eq!(output.commands, expected.commands);
+        assert_eq!(output.rollback, expected.rollback);
+        assert_eq!(
+            output.applied.simplified_config,
+            expected.applied.simplified_config
+        );
+        // The applied config is written in the format of the base config.
+        assert!(output.applied.text.contains(
+            "l2vpn bridge group VLAN bridge-domain VLAN400 interface FortyGigE0/0/0/46.400\n"
+        ));
+        assert!(!output.applied.text.contains("FortyGigE0/0/0/46.300"));
+    }
+
+    #[test]
+    fn change_engine_tolerates_tabs_and_inconsistent_indentation() {
+        let base_config = "interface FortyGigE0/0/0/46\n description To:server1\n!\n";
+        let spaces = [
+            "vlan database",
+            "  vlan 300 name web",
+            "",
+            "interface FortyGigE0/0/0/46",
+            "  switchport trunk allowed vlan add 300",
+        ]
+        .join("\n");
+        let expected = ChangeEngine::generate(base_config, &spaces).expect("generation succeeds");
+
+        let tabs = spaces.replace("  ", "\t");
+        let output = ChangeEngine::generate(base_config, &tabs).expect("generation succeeds");
+        assert!(output.warnings.is_empty());
+        assert_eq!(output.commands, expected.commands);
+
+        let ragged = [
+            "vlan database",
+            "    vlan 300 name web",
+            "",
+            "interface FortyGigE0/0/0/46",
+            "    switchport mode trunk",
+            "  switchport trunk allowed vlan add 300",
+        ]
+        .join("\n");
+        let output = ChangeEngine::generate(base_config, &ragged).expect("warnings only");
+        let codes: Vec<&str> = output.warnings.iter().map(|d| d.kind.code()).collect();
+        assert_eq!(codes, vec!["inconsistent-indentation"]);
+        assert_eq!(output.warnings[0].span, Some(Span::new(6, 2, 39)));
+        assert_eq!(output.commands, expected.commands);
+    }
 }
@@ -3,79 +3,208 @@
 //! This module parses simplified change input syntax and converts it into
 //! a structured ChangeSpec intermediate representation.
 
-use crate::ast::{Span, Spanned, SpannedNode, SpannedNodeBlock, SpannedNodeStmt};
-use crate::change::model::{BaseIf, ChangeSpec, InterfaceChange, VlanId};
+use crate::ast::{to_spanned, Span, Spanned, SpannedNode, SpannedNodeBlock, SpannedNodeStmt};
+use crate::change::model::{BaseIf, ChangeSpec, InterfaceChange, QinqTags, SwitchportMode, VlanId};
 use crate::error::{Diagnostic, ErrorKind};
-use crate::parse::parser::tokenize_spanned;
+use crate::parse::parser::parse_cst_with_diagnostics;
 use crate::regex;
 use std::collections::BTreeSet;
 
 /// Parse simplified change input text into a `ChangeSpec` structure.
-pub fn parse_change_input(input: &str) -> Result<ChangeSpec, Diagnostic> {
-    let normalized_input = normalize_indent(input);
-    let nodes = tokenize_spanned(&normalized_input);
+///
+/// Problems are pushed to `diags` and the offending statement is skipped, so a
+/// single pass reports every error in the input.
+pub fn parse_change_input(input: &str, diags: &mut Vec<Diagnostic>) -> ChangeSpec {
+    // Change input is always indented, even when a line looks qualified
+    // (such as `interface range ...`). Input indented as a whole (e.g. pasted
+    // from a document) parses the same as unindented input.
+    let nodes = to_spanned(&parse_cst_with_diagnostics(input, diags));
 
     let mut spec = ChangeSpec::default();
 
     for node in nodes {
         match node {
-            SpannedNode::Block(block) => handle_block(block, &mut spec)?,
-            SpannedNode::Stmt(stmt) => handle_stmt(stmt, &mut spec)?,
+            SpannedNode::Block(block) => handle_block(block, &mut spec, diags),
+            SpannedNode::Stmt(stmt) => handle_stmt(stmt, &mut spec, diags),
         }
     }
 
-    Ok(spec)
+    spec
+}
+
+/// Record a diagnostic, attaching the statement span when it has none.
+fn report(diags: &mut Vec<Diagnostic>, mut diag: Diagnostic, span: Span) {
+    diag.span.get_or_insert(span);
+    diags.push(diag);
 }
 
 /// Process a parsed block node and update the change spec.
-fn handle_block(block: SpannedNodeBlock, spec: &mut ChangeSpec) -> Result<(), Diagnostic> {
+fn handle_block(block: SpannedNodeBlock, spec: &mut ChangeSpec, diags: &mut Vec<Diagnostic>) {
     if block.name == "vlan database" {
-        parse_vlan_block(&block, spec)?;
-        return Ok(());
+        parse_vlan_block(&block, spec, diags);
+        return;
+    }
+
+    if let Some(list) = block.name.strip_prefix("interface range ") {
+        let names = match expand_interface_range(list) {
+            Ok(names) => names,
+            Err(diag) => return report(diags, diag, block.span),
+        };
+        // Problems in the shared statements are reported once, not per port.
+        let mut repeated = Vec::new();
+        for (i, baseif) in names.iter().enumerate() {
+            spec.interface_spans.insert(baseif.clone(), block.span);
+            let sink = if i == 0 { &mut *diags } else { &mut repeated };
+            parse_interface_block(baseif, &block, spec, sink);
+        }
+        return;
     }
 
     if let Some(ifname) = block.name.strip_prefix("interface ") {
         let baseif = BaseIf::from(ifname);
         spec.interface_spans.insert(baseif.clone(), block.span);
-        parse_interface_block(&baseif, &block, spec)?;
+        parse_interface_block(&baseif, &block, spec, diags);
     }
+}
 
-    Ok(())
+/// Largest number of interfaces an `interface range` header may expand to.
+const MAX_RANGE_INTERFACES: usize = 1024;
+
+/// Expand the interface list of an `interface range` header into interface
+/// names, e.g. `FortyGigE0/0/0/10 - 12, FortyGigE0/0/0/20`.
+///
+/// A range replaces the last number of the first interface with every number
+/// up to the given end. Lists longer than [`MAX_RANGE_INTERFACES`] are
+/// rejected before they are expanded.
+fn expand_interface_range(list: &str) -> Result<Vec<BaseIf>, Diagnostic> {
+    let invalid = |text: &str| {
+        Diagnostic::new(ErrorKind::InvalidInterfaceRange {
+            text: text.trim().to_string(),
+        })
+    };
+
+    let mut names = Vec::new();
+    for item in list.split(',').map(str::trim) {
+        if let Some(caps) = regex!(r"^(.*?)(\d+)\s*-\s*(\d+)$").captures(item) {
+            let prefix = caps
+                .get(1)
+                .map(|m| m.as_str().to_string())
+                .unwrap_or_default();
+            let number = |i: usize| caps.get(i)?.as_str().parse::<u32>().ok();
+            let bounds = number(2)
+                .zip(number(3))
+                .filter(|(start, end)| start <= end && !prefix.is_empty())
+                .filter(|(start, end)| {
+                    ((end - start) as usize) < MAX_RANGE_INTERFACES - names.len()
+                });
+            let Some((start, end)) = bounds else {
+                return Err(invalid(item));
+            };
+            names.extend((start..=end).map(|port| BaseIf::new(format!("{}{}", prefix, port))));
+        } else if item.is_empty()
+            || item.contains(char::is_whitespace)
+            || names.len() >= MAX_RANGE_INTERFACES
+        {
+            return Err(invalid(item));
+        } else {
+            names.push(BaseIf::from(item));
+        }
+    }
+    Ok(names)
 }
 
 /// Process a standalone statement node and update the change spec.
-fn handle_stmt(stmt: crate::ast::SpannedNodeStmt, spec: &mut ChangeSpec) -> Result<(), Diagnostic> {
+fn handle_stmt(stmt: SpannedNodeStmt, spec: &mut ChangeSpec, diags: &mut Vec<Diagnostic>) {
     let text = stmt.stmt.trim();
     if text.is_empty() {
-        return Ok(());
+        return;
     }
 
     if text.starts_with("vlan ") {
-        parse_vlan_line(text, stmt.span)?.map(|(vlan, name)| {
+        if let Some((vlan, name)) = parse_vlan_line(text, stmt.span, diags) {
             spec.vlans.insert(vlan, name);
-        });
-        return Ok(());
+        }
+        return;
+    }
+
+    if parse_vlan_removal(text, stmt.span, spec, diags) {
+        return;
+    }
+
+    if let Some(bvi) = text.strip_prefix("no interface BVI") {
+        match bvi.trim().parse::<u32>() {
+            Ok(vlan) => {
+                spec.bvi_removals.insert(VlanId::new(vlan), stmt.span);
+            }
+            Err(_) => {
+                let diag = Diagnostic::new(ErrorKind::InvalidBviNumber {
+                    text: bvi.trim().to_string(),
+                });
+                report(diags, diag, stmt.span);
+            }
+        }
+        return;
+    }
+
+    if let Some(list) = text.strip_prefix("interface range ") {
+        match expand_interface_range(list) {
+            Ok(names) => {
+                for baseif in names {
+                    spec.interface_spans.insert(baseif, stmt.span);
+                }
+            }
+            Err(diag) => report(diags, diag, stmt.span),
+        }
+        return;
     }
 
     if let Some(ifname) = text.strip_prefix("interface ") {
         let baseif = BaseIf::from(ifname);
         spec.interface_spans.insert(baseif.clone(), stmt.span);
         if baseif.as_str().starts_with("BVI") {
-            parse_interface_stmt(&baseif, spec)?;
+            if let Err(diag) = parse_interface_stmt(&baseif, spec) {
+                report(diags, diag, stmt.span);
+            }
         }
     }
-
-    Ok(())
 }
 
 /// Parse a `vlan database` block, capturing VLAN names when present.
-fn parse_vlan_block(block: &SpannedNodeBlock, spec: &mut ChangeSpec) -> Result<(), Diagnostic> {
+fn parse_vlan_block(block: &SpannedNodeBlock, spec: &mut ChangeSpec, diags: &mut Vec<Diagnostic>) {
     for stmt in block.stmts().filter_map(|s| s.as_stmt()) {
-        if let Some((vlan, name)) = parse_vlan_line(&stmt.stmt, stmt.span)? {
+        if parse_vlan_removal(&stmt.stmt, stmt.span, spec, diags) {
+            continue;
+        }
+        if let Some((vlan, name)) = parse_vlan_line(&stmt.stmt, stmt.span, diags) {
             spec.vlans.insert(vlan, name);
         }
     }
-    Ok(())
+}
+
+/// Parse a `no vlan <id>` statement, recording the VLAN to tear down.
+///
+/// Returns whether the line was a VLAN removal (valid or not).
+fn parse_vlan_removal(
+    line: &str,
+    span: Span,
+    spec: &mut ChangeSpec,
+    diags: &mut Vec<Diagnostic>,
+) -> bool {
+    let Some(vlan) = line.trim().strip_prefix("no vlan ") else {
+        return false;
+    };
+    match vlan.trim().parse::<u32>() {
+        Ok(vlan) => {
+            spec.vlan_removals.insert(VlanId::new(vlan), span);
+        }
+        Err(_) => {
+            let diag = Diagnostic::new(ErrorKind::InvalidVlanId {
+                text: line.trim().to_string(),
+            });
+            report(diags, diag, span);
+        }
+    }
+    true
 }
 
 /// Parse an interface block and populate interface-specific changes.
@@ -83,19 +212,27 @@ fn parse_interface_block(
     ifname: &BaseIf,
     block: &SpannedNodeBlock,
     spec: &mut ChangeSpec,
-) -> Result<(), Diagnostic> {
-    parse_interface_stmt(ifname, spec)?;
+    diags: &mut Vec<Diagnostic>,
+) {
+    if let Err(diag) = parse_interface_stmt(ifname, spec) {
+        report(diags, diag, block.span);
+        return;
+    }
 
     if ifname.as_str().starts_with("BVI") {
-        parse_bvi_block(ifname, block, spec)?;
-        return Ok(());
+        parse_bvi_block(ifname, block, spec);
+        return;
     }
 
     let mut interface_change = spec.interface_changes.remove(ifname).unwrap_or_default();
 
     let desc_re = regex!(r"^description\s+(.+)$");
-    let mode_re = regex!(r"^switchport mode\s+(trunk)$");
-    let mode_any_re = regex!(r"^switchport mode\s+(.+)$");
+    let mode_re = regex!(r"^switchport mode\s+(.+)$");
+    let access_re = regex!(r"^switchport access vlan\s+(.+)$");
+    let native_re = regex!(r"^switchport trunk native vlan\s+(.+)$");
+    let qinq_re = regex!(r"^(no\s+)?switchport dot1q-tunnel vlan\s+(\S+)\s+inner\s+(\S+)$");
+    let bundle_re = regex!(r"^(?:channel-group|bundle id)\s+(\S+)(?:\s+mode\s+(\S+))?$");
+    let no_bundle_re = regex!(r"^no\s+(?:channel-group|bundle id)\b");
     let trunk_none_re = regex!(r"^switchport trunk allowed vlan none\s*$");
     let trunk_re = regex!(r"^switchport trunk allowed vlan (add|remove)\s+(.+)$");
     let trunk_set_re = regex!(r"^switchport trunk allowed vlan\s+(.+)$");
@@ -118,25 +255,125 @@ fn parse_interface_block(
             continue;
         }
 
-        if mode_re.captures(stmt_text).is_some() {
-            // Ignore "switchport mode trunk" - all ports are trunk by default
+        if matches!(stmt_text, "shutdown" | "no shutdown") {
+            interface_change.shutdown = Some(Spanned::new(stmt_text == "shutdown", stmt.span));
             continue;
         }
 
-        if let Some(caps) = mode_any_re.captures(stmt_text) {
+        if let Some(caps) = mode_re.captures(stmt_text) {
             let mode = caps
+                .get(1)
+                .map(|m| m.as_str().trim().to_string())
+                .unwrap_or_default();
+            match mode.as_str() {
+                "trunk" => {
+                    interface_change.mode = Some(Spanned::new(SwitchportMode::Trunk, stmt.span))
+                }
+                "access" => {
+                    interface_change.mode = Some(Spanned::new(SwitchportMode::Access, stmt.span))
+                }
+                _ => {
+                    let diag = Diagnostic::new(ErrorKind::UnsupportedSwitchportMode { mode });
+                    report(diags, diag, stmt.span);
+                }
+            }
+            continue;
+        }
+
+        if let Some(caps) = access_re.captures(stmt_text) {
+            let text = caps
+                .get(1)
+                .map(|m| m.as_str().trim().to_string())
+                .unwrap_or_default();
+            match text.parse::<u32>() {
+                Ok(vlan) => {
+                    interface_change.access_vlan = Some(Spanned::new(VlanId::new(vlan), stmt.span))
+                }
+                Err(_) => {
+                    let diag = Diagnostic::new(ErrorKind::InvalidVlanNumber { text });
+                    report(diags, diag, stmt.span);
+                }
+            }
+            continue;
+        }
+
+        if let Some(caps) = native_re.captures(stmt_text) {
+            let text = caps
+                .get(1)
+                .map(|m| m.as_str().trim().to_string())
+                .unwrap_or_default();
+            match text.parse::<u32>() {
+                Ok(vlan) => {
+                    interface_change.native_vlan =
+                        Some(Spanned::new(Some(VlanId::new(vlan)), stmt.span))
+                }
+                Err(_) => {
+                    let diag = Diagnostic::new(ErrorKind::InvalidVlanNumber { text });
+                    report(diags, diag, stmt.span);
+                }
+            }
+            continue;
+        }
+
+        if stmt_text.starts_with("no switchport trunk native vlan") {
+            interface_change.native_vlan = Some(Spanned::new(None, stmt.span));
+            continue;
+        }
+
+        if let Some(caps) = qinq_re.captures(stmt_text) {
+            let tag = |i: usize| {
+                let text = caps
+                    .get(i)
+                    .map(|m| m.as_str().to_string())
+                    .unwrap_or_default();
+                match text.parse::<u32>() {
+                    Ok(vlan) => Ok(VlanId::new(vlan)),
+                    Err(_) => Err(Diagnostic::new(ErrorKind::InvalidVlanNumber { text })),
+                }
+            };
+            match tag(2).and_then(|outer| {
+                Ok(QinqTags {
+                    outer,
+                    inner: tag(3)?,
+                })
+            }) {
+                Ok(tags) if caps.get(1).is_some() => {
+                    interface_change.qinq_remove.insert(tags, stmt.span);
+                }
+                Ok(tags) => {
+                    interface_change.qinq_add.insert(tags, stmt.span);
+                }
+                Err(diag) => report(diags, diag, stmt.span),
+            }
+            continue;
+        }
+
+        if let Some(caps) = bundle_re.captures(stmt_text) {
+            let id = caps
                 .get(1)
                 .map(|m| m.as_str().to_string())
                 .unwrap_or_default();
-            let mut diag = Diagnostic::new(ErrorKind::UnsupportedSwitchportMode { mode });
-            diag.span = Some(stmt.span);
-            return Err(diag);
+            let mode = caps.get(2).map(|m| m.as_str().to_string());
+            match parse_bundle_line(&id, mode.as_deref()) {
+                Ok((bundle_id, stmt_out)) => {
+                    interface_change.bundle = Some(Spanned::new(Some(bundle_id), stmt.span));
+                    interface_change.other_statements.push(SpannedNodeStmt {
+                        stmt: stmt_out,
+                        span: stmt.span,
+                    });
+                }
+                Err(diag) => report(diags, diag, stmt.span),
+            }
+            continue;
         }
 
-        if stmt_text.starts_with("switchport access vlan") {
-            let mut diag = Diagnostic::new(ErrorKind::AccessModeNotSupported);
-            diag.span = Some(stmt.span);
-            return Err(diag);
+        if no_bundle_re.captures(stmt_text).is_some() {
+            interface_change.bundle = Some(Spanned::new(None, stmt.span));
+            interface_change.other_statements.push(SpannedNodeStmt {
+                stmt: "no bundle id".to_string(),
+                span: stmt.span,
+            });
+            continue;
         }
 
         if trunk_none_re.captures(stmt_text).is_some() {
@@ -154,7 +391,10 @@ fn parse_interface_block(
                 .map(|m| m.as_str().to_string())
                 .unwrap_or_default();
 
-            apply_trunk_action(&mut interface_change, &action, &list, stmt.span)?;
+            if let Err(diag) = apply_trunk_action(&mut interface_change, &action, &list, stmt.span)
+            {
+                report(diags, diag, stmt.span);
+            }
             continue;
         }
 
@@ -169,9 +409,13 @@ fn parse_interface_block(
                 continue;
             }
 
-            let vlans = parse_vlan_list(&list)?;
-            let vlan_set: BTreeSet<VlanId> = vlans.into_iter().collect();
-            interface_change.trunk_set = Some(Spanned::new(vlan_set, stmt.span));
+            match parse_vlan_list(&list) {
+                Ok(vlans) => {
+                    let vlan_set: BTreeSet<VlanId> = vlans.into_iter().collect();
+                    interface_change.trunk_set = Some(Spanned::new(vlan_set, stmt.span));
+                }
+                Err(diag) => report(diags, diag, stmt.span),
+            }
             continue;
         }
 
@@ -185,23 +429,38 @@ fn parse_interface_block(
 
     spec.interface_changes
         .insert(ifname.clone(), interface_change);
+}
 
-    Ok(())
+/// Parse the bundle number and LACP mode of a `channel-group <id> mode <mode>`
+/// (or `bundle id <id> mode <mode>`) statement.
+///
+/// Returns the bundle number and the equivalent IOS XR statement.
+fn parse_bundle_line(id: &str, mode: Option<&str>) -> Result<(u32, String), Diagnostic> {
+    let bundle_id = id.parse::<u32>().map_err(|_| {
+        Diagnostic::new(ErrorKind::InvalidBundleId {
+            text: id.to_string(),
+        })
+    })?;
+    match mode {
+        None => Ok((bundle_id, format!("bundle id {}", bundle_id))),
+        Some(mode @ ("active" | "passive" | "on")) => {
+            Ok((bundle_id, format!("bundle id {} mode {}", bundle_id, mode)))
+        }
+        Some(mode) => Err(Diagnostic::new(ErrorKind::UnsupportedBundleMode {
+            mode: mode.to_string(),
+        })),
+    }
 }
 
 /// Parse statements under a BVI interface block.
-fn parse_bvi_block(
-    ifname: &BaseIf,
-    block: &SpannedNodeBlock,
-    spec: &mut ChangeSpec,
-) -> Result<(), Diagnostic> {
-    if let Some(vlan) = ifname.as_str().strip_prefix("BVI") {
-        let vlan_id = vlan.parse::<u32>().map_err(|_| {
-            Diagnostic::new(ErrorKind::InvalidBviNumber {
-                text: vlan.to_string(),
-            })
-        })?;
-
+///
+/// The BVI number has already been validated by `parse_interface_stmt`.
+fn parse_bvi_block(ifname: &BaseIf, block: &SpannedNodeBlock, spec: &mut ChangeSpec) {
+    if let Some(vlan_id) = ifname
+        .as_str()
+        .strip_prefix("BVI")
+        .and_then(|vlan| vlan.parse::<u32>().ok())
+    {
         let mut statements = Vec::new();
         for stmt in block.stmts().filter_map(|s| s.as_stmt()) {
             let stmt_text = stmt.stmt.trim_end();
@@ -212,8 +471,6 @@ fn parse_bvi_block(
 
         spec.bvi_statements.insert(VlanId::new(vlan_id), statements);
     }
-
-    Ok(())
 }
 
 /// Handle an interface declaration that is not part of a block (e.g., BVI lines).
@@ -230,43 +487,49 @@ fn parse_interface_stmt(ifname: &BaseIf, spec: &mut ChangeSpec) -> Result<(), Di
     Ok(())
 }
 
+/// VLAN ID and optional name parsed from a `vlan` statement.
+type VlanLine = (VlanId, Option<Spanned<String>>);
+
 /// Parse a single `vlan` statement, returning the VLAN ID and optional name.
-fn parse_vlan_line(
-    line: &str,
-    span: Span,
-) -> Result<Option<(VlanId, Option<Spanned<String>>)>, Diagnostic> {
+///
+/// A VLAN whose name is missing is still returned (without a name) after the
+/// error is reported, so later references to it do not cascade into more errors.
+fn parse_vlan_line(line: &str, span: Span, diags: &mut Vec<Diagnostic>) -> Option<VlanLine> {
     if !line.starts_with("vlan ") {
-        return Ok(None);
+        return None;
     }
 
     if let Some(caps) = regex!(r"^vlan\s+(\d+)\s+name\s+(.+)$").captures(line) {
-        let vlan = caps
-            .get(1)
-            .and_then(|m| m.as_str().parse::<u32>().ok())
-            .ok_or_else(|| {
-                Diagnostic::new(ErrorKind::InvalidVlanId {
-                    text: line.to_string(),
-                })
-            })?;
+        let Some(vlan) = caps.get(1).and_then(|m| m.as_str().parse::<u32>().ok()) else {
+            let diag = Diagnostic::new(ErrorKind::InvalidVlanId {
+                text: line.to_string(),
+            });
+            report(diags, diag, span);
+            return None;
+        };
         let name = caps
             .get(2)
             .map(|m| m.as_str().trim().to_string())
             .unwrap_or_default();
         if name.is_empty() {
-            let mut diag = Diagnostic::new(ErrorKind::VlanNameRequired { vlan: Some(vlan) });
-            diag.span = Some(span);
-            return Err(diag);
+            let diag = Diagnostic::new(ErrorKind::VlanNameRequired { vlan: Some(vlan) });
+            report(diags, diag, span);
+            return Some((VlanId::new(vlan), None));
         }
-        return Ok(Some((VlanId::new(vlan), Some(Spanned::new(name, span)))));
+        return Some((VlanId::new(vlan), Some(Spanned::new(name, span))));
     }
 
-    if regex!(r"^vlan\s+\d+\s*$").captures(line).is_some() {
-        let mut diag = Diagnostic::new(ErrorKind::VlanNameRequired { vlan: None });
-        diag.span = Some(span);
-        return Err(diag);
+    if let Some(caps) = regex!(r"^vlan\s+(\d+)\s*$").captures(line) {
+        report(
+            diags,
+            Diagnostic::new(ErrorKind::VlanNameRequired { vlan: None }),
+            span,
+        );
+        let vlan = caps.get(1)?.as_str().parse::<u32>().ok()?;
+        return Some((VlanId::new(vlan), None));
     }
 
-    Ok(None)
+    None
 }
 
 /// Apply trunk add/remove actions to an interface change.
@@ -351,35 +614,3 @@ fn parse_vlan_list(list: &str) -> Result<Vec<VlanId>, Diagnostic> {
 
     Ok(vlans)
 }
-
-/// Normalize indentation so parsing is independent of leading spaces.
-fn normalize_indent(input: &str) -> String {
-    let lines: Vec<&str> = input.lines().collect();
-    let min_indent = lines
-        .iter()
-        .filter_map(|line| {
-            let trimmed_end = line.trim_end();
-            if trimmed_end.trim().is_empty() {
-                None
-            } else {
-                Some(count_leading_spaces(trimmed_end))
-            }
-        })
-        .min()
-        .unwrap_or(0);
-
-    lines
-        .iter()
-        .map(|line| {
-            let trimmed_end = line.trim_end();
-            let drop = min_indent.min(count_leading_spaces(trimmed_end));
-            trimmed_end.chars().skip(drop).collect::<String>()
-        })
-        .collect::<Vec<String>>()
-        .join("\n")
-}
-
-/// Count leading space characters on a line.
-fn count_leading_spaces(text: &str) -> usize {
-    text.chars().take_while(|&c| c == ' ').count()
-}
@@ -5,13 +5,18 @@
 //! 1. Parse change input (input_parser)
 //! 2. Validate changes (validator)
 //! 3. Plan diff operations (planner)
-//! 4. Generate commands (codegen)
+//! 4. Generate commands and their rollback (codegen)
+//! 5. Apply the plan to the base config for review (apply)
 
+pub mod apply;
 pub mod codegen;
 pub mod engine;
 pub mod input_parser;
 pub mod model;
 pub mod planner;
+pub mod simplified_diff;
 pub mod validator;
 
-pub use engine::ChangeEngine;
+pub use apply::AppliedConfig;
+pub use engine::{ChangeEngine, ChangeOptions, ChangeOutput};
+pub use simplified_diff::diff_simplified_configs;
@@ -1,6 +1,5 @@
-use crate::ast::{Span, Spanned, SpannedNodeStmt};
-use crate::parse::Node;
-use crate::semantics::{split_subinterface_id, BridgeDomain};
+use crate::ast::{Span, Spanned, SpannedNode, SpannedNodeStmt};
+use crate::semantics::{split_subinterface_id, BridgeDomain, Encapsulation};
 use std::collections::{BTreeMap, BTreeSet, HashMap};
 use std::fmt;
 
@@ -72,6 +71,24 @@ impl fmt::Display for VlanId {
     }
 }
 
+/// Switchport mode of a base interface.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum SwitchportMode {
+    /// VLANs are carried on tagged l2transport subinterfaces.
+    Trunk,
+    /// A single VLAN is carried untagged on the main interface in l2transport mode.
+    Access,
+}
+
+/// Outer and inner tag of a Q-in-Q service.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct QinqTags {
+    /// Outer (service) tag.
+    pub outer: VlanId,
+    /// Inner (customer) tag, also used as the subinterface number and bridge-domain VLAN.
+    pub inner: VlanId,
+}
+
 /// Desired changes for a single interface gathered from change input.
 #[derive(Clone, Debug, Default, PartialEq, Eq)]
 pub struct InterfaceChange {
@@ -85,11 +102,49 @@ pub struct InterfaceChange {
     pub trunk_clear: Option<Span>,
     /// Replace the allowed VLAN list entirely (`switchport trunk allowed vlan <list>`).
     pub trunk_set: Option<Spanned<BTreeSet<VlanId>>>,
+    /// Requested switchport mode (`switchport mode trunk|access`).
+    pub mode: Option<Spanned<SwitchportMode>>,
+    /// Access VLAN (`switchport access vlan <id>`).
+    pub access_vlan: Option<Spanned<VlanId>>,
+    /// Native VLAN (`switchport trunk native vlan <id>`); `None` inside for
+    /// `no switchport trunk native vlan`.
+    pub native_vlan: Option<Spanned<Option<VlanId>>>,
+    /// Q-in-Q services to add (`switchport dot1q-tunnel vlan <outer> inner <inner>`).
+    pub qinq_add: BTreeMap<QinqTags, Span>,
+    /// Q-in-Q services to remove (`no switchport dot1q-tunnel vlan <outer> inner <inner>`).
+    pub qinq_remove: BTreeMap<QinqTags, Span>,
+    /// Bundle-Ether to join (`channel-group <id> mode <mode>`); `None` inside for
+    /// `no channel-group`.
+    pub bundle: Option<Spanned<Option<u32>>>,
+    /// Administrative state (`shutdown` is `true`, `no shutdown` is `false`).
+    pub shutdown: Option<Spanned<bool>>,
     /// Additional statements to apply under the interface.
     pub other_statements: Vec<SpannedNodeStmt>,
 }
 
 impl InterfaceChange {
+    /// Whether any trunk VLAN operation was requested.
+    pub fn has_trunk_ops(&self) -> bool {
+        self.trunk_clear.is_some()
+            || self.trunk_set.is_some()
+            || !self.trunk_add.is_empty()
+            || !self.trunk_remove.is_empty()
+            || self.native_vlan.is_some()
+            || !self.qinq_add.is_empty()
+            || !self.qinq_remove.is_empty()
+    }
+
+    /// Span of the first trunk VLAN operation, if any.
+    pub fn trunk_ops_span(&self) -> Option<Span> {
+        self.trunk_clear
+            .or_else(|| self.trunk_set.as_ref().map(|s| s.span))
+            .or_else(|| self.trunk_add.values().copied().next())
+            .or_else(|| self.trunk_remove.values().copied().next())
+            .or_else(|| self.native_vlan.as_ref().map(|v| v.span))
+            .or_else(|| self.qinq_add.values().copied().next())
+            .or_else(|| self.qinq_remove.values().copied().next())
+    }
+
     /// Return the span associated with adding the given VLAN, whether via add or set.
     pub fn addition_span_for(&self, vlan: &VlanId) -> Option<Span> {
         if let Some(span) = self.trunk_add.get(vlan) {
@@ -104,6 +159,64 @@ impl InterfaceChange {
 
         None
     }
+
+    /// Statements to apply under the interface: `other_statements` followed
+    /// by the administrative state requested with `shutdown` / `no shutdown`.
+    pub fn statements(&self) -> Vec<&str> {
+        let mut stmts: Vec<&str> = self.other_statements.iter().map(|s| s.stmt()).collect();
+        match self.shutdown.as_ref().map(|s| s.value) {
+            Some(true) => stmts.push("shutdown"),
+            Some(false) => stmts.push("no shutdown"),
+            None => {}
+        }
+        stmts
+    }
+}
+
+/// Interface settings that take a single value.
+///
+/// A statement starting with one of these words replaces the other
+/// statements of the same setting; any other statement only configures
+/// itself.
+const SETTINGS: &[&str] = &[
+    "bandwidth",
+    "bundle id",
+    "carrier-delay down",
+    "carrier-delay up",
+    "channel-group",
+    "description",
+    "duplex",
+    "encapsulation",
+    "ipv4 address",
+    "ipv4 mtu",
+    "ipv6 mtu",
+    "load-interval",
+    "mac-address",
+    "mtu",
+    "negotiation",
+    "rewrite ingress",
+    "service-policy input",
+    "service-policy output",
+    "shutdown",
+    "speed",
+    "vrf",
+];
+
+/// Setting configured by an interface statement, ignoring a `no ` prefix.
+///
+/// This is the setting prefix for single-valued settings (e.g.
+/// `service-policy input` for `service-policy input POLICE`) and the whole
+/// statement otherwise (e.g. `ipv6 enable`).
+pub(crate) fn statement_setting(stmt: &str) -> &str {
+    let stmt = stmt.trim();
+    let stmt = stmt.strip_prefix("no ").map_or(stmt, str::trim_start);
+    SETTINGS
+        .iter()
+        .find(|setting| {
+            stmt.strip_prefix(**setting)
+                .is_some_and(|rest| rest.is_empty() || rest.starts_with(char::is_whitespace))
+        })
+        .map_or(stmt, |setting| &stmt[..setting.len()])
 }
 
 #[derive(Clone, Debug, Default)]
@@ -116,6 +229,10 @@ pub struct ChangeSpec {
     pub bvi_additions: BTreeSet<VlanId>,
     /// Statements to emit under each BVI interface.
     pub bvi_statements: BTreeMap<VlanId, Vec<Spanned<String>>>, // vlan -> statements
+    /// VLANs to tear down (`no vlan <id>`) along with their source span.
+    pub vlan_removals: BTreeMap<VlanId, Span>,
+    /// BVI interfaces to remove (`no interface BVI<id>`) along with their source span.
+    pub bvi_removals: BTreeMap<VlanId, Span>,
     /// Source span of each interface declaration (for error reporting).
     pub interface_spans: HashMap<BaseIf, Span>,
 }
@@ -141,6 +258,20 @@ pub struct InterfaceContext {
     pub bundle_id: Option<u32>,
     /// VLANs currently present on subinterfaces for this base interface.
     pub vlans: BTreeSet<VlanId>,
+    /// Subinterface carrying each tagged VLAN in `vlans`.
+    pub tagged: BTreeMap<VlanId, String>,
+    /// Statements configured under the interface, in source order.
+    pub statements: Vec<String>,
+    /// Whether the main interface itself is in l2transport mode.
+    pub l2transport: bool,
+    /// Whether the interface is administratively down (`shutdown`).
+    pub shutdown: bool,
+    /// VLAN of the bridge-domain the main interface belongs to (access mode).
+    pub access_vlan: Option<VlanId>,
+    /// Native VLAN carried on an untagged subinterface, with the subinterface name.
+    pub native_vlan: Option<(VlanId, String)>,
+    /// Q-in-Q services with their subinterface name and bridge-domain VLAN.
+    pub qinq: BTreeMap<QinqTags, (String, VlanId)>,
 }
 
 /// Snapshot of the base configuration used to validate and plan changes.
@@ -150,16 +281,47 @@ pub struct BaseContext {
     pub domain_descriptions: HashMap<VlanId, Option<String>>,
     /// Base interface contexts keyed by interface name.
     pub interfaces: HashMap<BaseIf, InterfaceContext>,
+    /// Statements of existing l2transport subinterfaces keyed by subinterface name.
+    pub subinterfaces: HashMap<String, Vec<String>>,
+    /// VLANs whose bridge-domain already has a `routed interface BVI<vlan>`.
+    pub routed_bvis: BTreeSet<VlanId>,
+    /// Bridge group and name of the existing bridge-domain of each VLAN.
+    pub domain_paths: HashMap<VlanId, DomainPath>,
+    /// Statements configured under each existing bridge-domain.
+    pub domain_statements: HashMap<DomainPath, Vec<String>>,
+    /// Bridge-domain of each existing Q-in-Q service.
+    pub qinq_domains: HashMap<QinqTags, DomainPath>,
 }
 
 impl BaseContext {
     /// Build base context from analyzed bridge-domains and parsed nodes.
-    pub fn from_analysis(domains: &[BridgeDomain], nodes: &[Node]) -> Self {
+    pub fn from_analysis(domains: &[BridgeDomain], nodes: &[SpannedNode]) -> Self {
         let mut domain_descriptions: HashMap<VlanId, Option<String>> = HashMap::new();
         let mut domain_interfaces: HashMap<VlanId, BTreeSet<String>> = HashMap::new();
         let mut interfaces: HashMap<BaseIf, InterfaceContext> = HashMap::new();
+        let mut subinterfaces: HashMap<String, Vec<String>> = HashMap::new();
+        let mut routed_bvis: BTreeSet<VlanId> = BTreeSet::new();
+        let mut domain_paths: HashMap<VlanId, DomainPath> = HashMap::new();
+        let mut member_paths: HashMap<&str, DomainPath> = HashMap::new();
+        let mut qinq_domains: HashMap<QinqTags, DomainPath> = HashMap::new();
 
         for domain in domains {
+            // A `VLAN<id>` domain wins over named domains mapped to the same
+            // VLAN, e.g. the own domain of a Q-in-Q service sharing its inner tag.
+            let path = DomainPath::new(&domain.group, &domain.name);
+            if domain.name == format!("VLAN{}", domain.vlan_tag) {
+                domain_paths.insert(VlanId::from(domain.vlan_tag), path.clone());
+            } else {
+                domain_paths
+                    .entry(VlanId::from(domain.vlan_tag))
+                    .or_insert_with(|| path.clone());
+            }
+            for iface in &domain.interfaces {
+                member_paths.insert(iface, path.clone());
+            }
+            if domain.routed_bvi() == Some(domain.vlan_tag) {
+                routed_bvis.insert(VlanId::from(domain.vlan_tag));
+            }
             domain_descriptions.insert(
                 VlanId::from(domain.vlan_tag),
                 domain.description().map(str::to_string),
@@ -174,11 +336,27 @@ impl BaseContext {
 
         for node in nodes.iter().filter_map(|n| n.as_block()) {
             if let Some(ifname) = node.name.strip_prefix("interface ") {
-                if ifname.contains('.') || node.name.ends_with(" l2transport") {
+                let statements: Vec<String> = node
+                    .stmts()
+                    .filter_map(|x| x.as_stmt())
+                    .map(|stmt| stmt.stmt().trim().to_string())
+                    .collect();
+
+                let (ifname, l2transport) = match ifname.strip_suffix(" l2transport") {
+                    Some(name) => (name.trim(), true),
+                    None => (ifname, false),
+                };
+                if ifname.contains('.') {
+                    if l2transport {
+                        subinterfaces.insert(ifname.to_string(), statements);
+                    }
                     continue;
                 }
 
                 let interface = interfaces.entry(BaseIf::from(ifname)).or_default();
+                interface.shutdown |= statements.iter().any(|stmt| stmt == "shutdown");
+                interface.statements.extend(statements);
+                interface.l2transport |= l2transport;
 
                 if let Some(desc) = node
                     .stmts()
@@ -204,14 +382,63 @@ impl BaseContext {
             }
         }
 
+        let mut domain_statements: HashMap<DomainPath, Vec<String>> = HashMap::new();
+        let groups = nodes
+            .iter()
+            .filter_map(|n| n.as_block())
+            .filter(|n| n.name == "l2vpn")
+            .flat_map(|l2vpn| l2vpn.stmts().filter_map(|n| n.as_block()));
+        for group in groups {
+            let Some(group_name) = group.name.strip_prefix("bridge group ") else {
+                continue;
+            };
+            for domain in group.stmts().filter_map(|n| n.as_block()) {
+                let Some(domain_name) = domain.name.strip_prefix("bridge-domain ") else {
+                    continue;
+                };
+                domain_statements
+                    .entry(DomainPath::new(group_name.trim(), domain_name.trim()))
+                    .or_insert_with(|| {
+                        domain
+                            .stmts()
+                            .filter_map(|x| x.as_stmt())
+                            .map(|stmt| stmt.stmt().trim().to_string())
+                            .collect()
+                    });
+            }
+        }
+
         for (vlan, interfaces_in_domain) in &domain_interfaces {
             for iface in interfaces_in_domain {
                 if let Ok((baseif, Some(_))) = split_subinterface_id(iface) {
+                    let interface = interfaces.entry(BaseIf::from(baseif)).or_default();
+                    let encap = subinterfaces
+                        .get(iface)
+                        .and_then(|stmts| Encapsulation::find(stmts.iter().map(String::as_str)));
+                    match encap {
+                        Some(Encapsulation::Untagged) => {
+                            interface.native_vlan = Some((*vlan, iface.clone()));
+                        }
+                        Some(Encapsulation::QinQ { outer, inner }) => {
+                            let tags = QinqTags {
+                                outer: VlanId::new(outer),
+                                inner: VlanId::new(inner),
+                            };
+                            interface.qinq.insert(tags, (iface.clone(), *vlan));
+                            if let Some(path) = member_paths.get(iface.as_str()) {
+                                qinq_domains.insert(tags, path.clone());
+                            }
+                        }
+                        _ => {
+                            interface.vlans.insert(*vlan);
+                            interface.tagged.insert(*vlan, iface.clone());
+                        }
+                    }
+                } else if !iface.contains('.') && !iface.starts_with("BVI") {
                     interfaces
-                        .entry(BaseIf::from(baseif))
+                        .entry(BaseIf::from(iface.as_str()))
                         .or_default()
-                        .vlans
-                        .insert(*vlan);
+                        .access_vlan = Some(*vlan);
                 }
             }
         }
@@ -219,14 +446,94 @@ impl BaseContext {
         BaseContext {
             domain_descriptions,
             interfaces,
+            subinterfaces,
+            routed_bvis,
+            domain_paths,
+            domain_statements,
+            qinq_domains,
         }
     }
 
+    /// Get the statements configured under an existing bridge-domain.
+    pub fn domain_statements(&self, path: &DomainPath) -> &[String] {
+        self.domain_statements
+            .get(path)
+            .map(Vec::as_slice)
+            .unwrap_or_default()
+    }
+
+    /// Existing l2transport subinterfaces carrying a VLAN, tagged, native or
+    /// as the inner tag of a Q-in-Q service, with their base interface.
+    pub fn subinterfaces_in(&self, vlan: &VlanId) -> BTreeSet<(BaseIf, String)> {
+        let mut found = BTreeSet::new();
+        for (baseif, iface) in &self.interfaces {
+            if let Some(subif) = iface.tagged.get(vlan) {
+                found.insert((baseif.clone(), subif.clone()));
+            }
+            if let Some((_, subif)) = iface.native_vlan.as_ref().filter(|(v, _)| v == vlan) {
+                found.insert((baseif.clone(), subif.clone()));
+            }
+            for (subif, _) in iface.qinq.values().filter(|(_, v)| v == vlan) {
+                found.insert((baseif.clone(), subif.clone()));
+            }
+        }
+        found
+    }
+
+    /// Existing l2transport subinterfaces of a base interface with the VLAN
+    /// of their bridge-domain.
+    pub fn subinterfaces_of(&self, name: &BaseIf) -> BTreeMap<String, VlanId> {
+        let mut found = BTreeMap::new();
+        if let Some(iface) = self.interface(name) {
+            for (vlan, subif) in &iface.tagged {
+                found.insert(subif.clone(), *vlan);
+            }
+            if let Some((vlan, subif)) = &iface.native_vlan {
+                found.insert(subif.clone(), *vlan);
+            }
+            for (subif, vlan) in iface.qinq.values() {
+                found.insert(subif.clone(), *vlan);
+            }
+        }
+        found
+    }
+
+    /// Whether the BVI of a VLAN has an IPv4 or IPv6 address in the base config.
+    pub fn bvi_has_address(&self, vlan: &VlanId) -> bool {
+        self.interface(&BaseIf::new(format!("BVI{}", vlan)))
+            .is_some_and(|iface| {
+                iface.statements.iter().any(|stmt| {
+                    stmt.starts_with("ipv4 address ") || stmt.starts_with("ipv6 address ")
+                })
+            })
+    }
+
+    /// Get the bridge group and name of the existing bridge-domain of a VLAN.
+    pub fn domain_path(&self, vlan: &VlanId) -> Option<&DomainPath> {
+        self.domain_paths.get(vlan)
+    }
+
+    /// Get the bridge group and name of the bridge-domain of an existing Q-in-Q service.
+    pub fn qinq_domain(&self, tags: &QinqTags) -> Option<&DomainPath> {
+        self.qinq_domains.get(tags)
+    }
+
+    /// Whether a bridge-domain exists in the base config.
+    pub fn has_domain(&self, path: &DomainPath) -> bool {
+        self.domain_paths.values().any(|p| p == path)
+            || self.qinq_domains.values().any(|p| p == path)
+    }
+
     /// Return context for a given base interface, if any.
     pub fn interface(&self, name: &BaseIf) -> Option<&InterfaceContext> {
         self.interfaces.get(name)
     }
 
+    /// Whether the bridge-domain of a VLAN already routes through its BVI.
+    pub fn has_routed_bvi(&self, vlan: &VlanId) -> bool {
+        self.routed_bvis.contains(vlan)
+    }
+
     /// Get the description of a base interface, if one exists.
     pub fn description_for(&self, name: &BaseIf) -> Option<&str> {
         self.interface(name)
@@ -238,10 +545,103 @@ impl BaseContext {
         self.interface(name).and_then(|iface| iface.bundle_id)
     }
 
+    /// Physical members of a Bundle-Ether in the base config.
+    pub fn bundle_members(&self, bundle_id: u32) -> BTreeSet<&BaseIf> {
+        self.interfaces
+            .iter()
+            .filter(|(_, iface)| iface.bundle_id == Some(bundle_id))
+            .map(|(name, _)| name)
+            .collect()
+    }
+
     /// Get the VLAN set already present on a base interface.
     pub fn vlans_for(&self, name: &BaseIf) -> Option<&BTreeSet<VlanId>> {
         self.interface(name).map(|iface| &iface.vlans)
     }
+
+    /// Name of the subinterface carrying a tagged VLAN on a base interface:
+    /// the existing bridge-domain member, or `<interface>.<vlan>` for a new one.
+    pub fn tagged_subinterface(&self, name: &BaseIf, vlan: &VlanId) -> String {
+        self.interface(name)
+            .and_then(|iface| iface.tagged.get(vlan))
+            .cloned()
+            .unwrap_or_else(|| format!("{}.{}", name, vlan))
+    }
+
+    /// Get the access VLAN when the base interface is an access port.
+    pub fn access_vlan(&self, name: &BaseIf) -> Option<VlanId> {
+        self.interface(name).and_then(|iface| iface.access_vlan)
+    }
+
+    /// Get the native VLAN and its untagged subinterface on a base interface.
+    pub fn native_vlan(&self, name: &BaseIf) -> Option<(VlanId, &str)> {
+        self.interface(name)
+            .and_then(|iface| iface.native_vlan.as_ref())
+            .map(|(vlan, iface)| (*vlan, iface.as_str()))
+    }
+
+    /// Get the Q-in-Q services on a base interface.
+    pub fn qinq_for(&self, name: &BaseIf) -> Option<&BTreeMap<QinqTags, (String, VlanId)>> {
+        self.interface(name).map(|iface| &iface.qinq)
+    }
+
+    /// Whether the main interface is configured in l2transport mode (access port).
+    pub fn is_access_port(&self, name: &BaseIf) -> bool {
+        self.interface(name).is_some_and(|iface| iface.l2transport)
+    }
+
+    /// Whether a base interface is administratively down in the base config.
+    pub fn is_shutdown(&self, name: &BaseIf) -> bool {
+        self.interface(name).is_some_and(|iface| iface.shutdown)
+    }
+
+    /// Get the statements configured under an existing l2transport subinterface.
+    pub fn subinterface_statements(&self, name: &str) -> Option<&[String]> {
+        self.subinterfaces.get(name).map(Vec::as_slice)
+    }
+}
+
+/// Bridge group that receives bridge-domains for new VLANs by default.
+pub const DEFAULT_BRIDGE_GROUP: &str = "VLAN";
+
+/// Location of a bridge-domain under `l2vpn`.
+#[derive(Clone, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct DomainPath {
+    /// Name of the bridge group (e.g. `VLAN`).
+    pub group: String,
+    /// Name of the bridge-domain (e.g. `VLAN300`).
+    pub name: String,
+}
+
+impl DomainPath {
+    /// Create a path from a bridge group and bridge-domain name.
+    pub fn new(group: impl Into<String>, name: impl Into<String>) -> Self {
+        Self {
+            group: group.into(),
+            name: name.into(),
+        }
+    }
+
+    /// Path of a new `VLAN<id>` bridge-domain in the given bridge group.
+    pub fn for_new_vlan(group: &str, vlan: VlanId) -> Self {
+        Self::new(group, format!("VLAN{}", vlan))
+    }
+
+    /// Path of a new `VLAN<outer>-<inner>` bridge-domain of a Q-in-Q service
+    /// in the given bridge group.
+    pub fn for_new_qinq(group: &str, tags: QinqTags) -> Self {
+        Self::new(group, format!("VLAN{}-{}", tags.outer, tags.inner))
+    }
+
+    /// Bridge group header (e.g. `bridge group VLAN`).
+    pub fn group_header(&self) -> String {
+        format!("bridge group {}", self.group)
+    }
+
+    /// Bridge-domain header (e.g. `bridge-domain VLAN300`).
+    pub fn domain_header(&self) -> String {
+        format!("bridge-domain {}", self.name)
+    }
 }
 
 /// Planned creation of a new subinterface for a VLAN.
@@ -249,10 +649,34 @@ impl BaseContext {
 pub struct InterfaceCreation {
     /// Base interface on which to create the subinterface.
     pub baseif: BaseIf,
-    /// VLAN identifier used for the subinterface tag.
+    /// Subinterface number: the VLAN tag, or the number allocated to a
+    /// Q-in-Q service.
     pub vlan: VlanId,
     /// Description to set on the subinterface.
     pub description: String,
+    /// Encapsulation of the subinterface.
+    pub encapsulation: Encapsulation,
+}
+
+impl InterfaceCreation {
+    /// Name of the subinterface to create (e.g. `FortyGigE0/0/0/1.300`).
+    pub fn iface(&self) -> String {
+        format!("{}.{}", self.baseif, self.vlan)
+    }
+}
+
+/// Planned rewrite of the description of an existing interface.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DescriptionUpdate {
+    /// Interface the update is grouped under (the parent of a subinterface,
+    /// or the BVI itself).
+    pub baseif: BaseIf,
+    /// Header of the interface block (e.g. `interface FortyGigE0/0/0/1.300 l2transport`).
+    pub header: String,
+    /// Description in the base config, if any.
+    pub old: Option<String>,
+    /// Description after the change.
+    pub new: String,
 }
 
 /// Planned removal of a subinterface.
@@ -260,6 +684,8 @@ pub struct InterfaceCreation {
 pub struct InterfaceRemoval {
     /// Base interface from which the subinterface is being removed.
     pub baseif: BaseIf,
+    /// Name of the subinterface being removed (e.g. `FortyGigE0/0/0/1.300`).
+    pub iface: String,
     /// The full removal command (e.g. "no interface ...").
     pub command: String,
 }
@@ -271,6 +697,48 @@ pub struct InterfaceMembership {
     pub baseif: BaseIf,
     /// VLAN identifier for membership.
     pub vlan: VlanId,
+    /// Whether the main interface is the member (access mode) instead of a subinterface.
+    pub access: bool,
+}
+
+impl InterfaceMembership {
+    /// Name of the bridge-domain member interface.
+    pub fn member(&self) -> String {
+        if self.access {
+            self.baseif.to_string()
+        } else {
+            format!("{}.{}", self.baseif, self.vlan)
+        }
+    }
+}
+
+/// Planned conversion of a main interface between trunk and access mode.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ModeConversion {
+    /// Interface being converted.
+    pub baseif: BaseIf,
+    /// Mode after the conversion.
+    pub to: SwitchportMode,
+    /// Statements to configure on the converted interface.
+    pub statements: Vec<String>,
+}
+
+impl ModeConversion {
+    /// Interface header before the conversion.
+    pub fn old_header(&self) -> String {
+        match self.to {
+            SwitchportMode::Access => format!("interface {}", self.baseif),
+            SwitchportMode::Trunk => format!("interface {} l2transport", self.baseif),
+        }
+    }
+
+    /// Interface header after the conversion.
+    pub fn new_header(&self) -> String {
+        match self.to {
+            SwitchportMode::Access => format!("interface {} l2transport", self.baseif),
+            SwitchportMode::Trunk => format!("interface {}", self.baseif),
+        }
+    }
 }
 
 /// Accumulated per-VLAN changes (additions/removals and metadata).
@@ -278,6 +746,8 @@ pub struct InterfaceMembership {
 pub struct VlanChange {
     /// VLAN being modified.
     pub vlan: VlanId,
+    /// Bridge-domain holding the VLAN.
+    pub path: DomainPath,
     /// Optional name/description for the VLAN.
     pub description: Option<Spanned<String>>,
     /// Interfaces to remove from the VLAN.
@@ -286,19 +756,27 @@ pub struct VlanChange {
     pub additions: Vec<InterfaceMembership>,
     /// Whether to create a BVI interface for this VLAN.
     pub add_bvi: bool,
+    /// Whether to detach the BVI (`no routed interface BVI<vlan>`).
+    pub remove_bvi: bool,
+    /// Whether to remove the bridge-domain entirely (`no vlan <id>`).
+    pub remove_domain: bool,
 }
 
 impl VlanChange {
-    /// Initialize a change record for the given VLAN using existing spec metadata.
-    pub fn new(vlan: VlanId, change_spec: &ChangeSpec) -> Self {
+    /// Initialize a change record for the given VLAN and bridge-domain using
+    /// existing spec metadata.
+    pub fn new(vlan: VlanId, path: DomainPath, change_spec: &ChangeSpec) -> Self {
         let description = change_spec.vlan_name(&vlan).cloned();
 
         VlanChange {
             vlan,
+            path,
             description,
             removals: BTreeSet::new(),
             additions: Vec::new(),
             add_bvi: false,
+            remove_bvi: false,
+            remove_domain: false,
         }
     }
 
@@ -307,9 +785,22 @@ impl VlanChange {
         self.removals.insert(iface);
     }
 
-    /// Track addition of an interface to the VLAN.
+    /// Track addition of a subinterface to the VLAN.
     pub fn record_addition(&mut self, baseif: BaseIf, vlan: VlanId) {
-        self.additions.push(InterfaceMembership { baseif, vlan });
+        self.additions.push(InterfaceMembership {
+            baseif,
+            vlan,
+            access: false,
+        });
+    }
+
+    /// Track addition of a main interface to the VLAN as an access port.
+    pub fn record_access_addition(&mut self, baseif: BaseIf) {
+        self.additions.push(InterfaceMembership {
+            baseif,
+            vlan: self.vlan,
+            access: true,
+        });
     }
 }
 
@@ -322,4 +813,46 @@ pub struct ChangePlan {
     pub additions: Vec<InterfaceCreation>,
     /// Per-VLAN change details.
     pub vlan_changes: BTreeMap<VlanId, VlanChange>,
+    /// Changes to bridge-domains of Q-in-Q services that do not share the
+    /// domain of their inner VLAN.
+    pub qinq_changes: BTreeMap<QinqTags, VlanChange>,
+    /// Main interfaces converted between trunk and access mode.
+    pub conversions: Vec<ModeConversion>,
+    /// Changed interfaces that are access ports after the change.
+    pub access_interfaces: BTreeSet<BaseIf>,
+    /// VLANs whose BVI interface is removed.
+    pub bvi_removals: BTreeSet<VlanId>,
+    /// Descriptions of existing subinterfaces and BVIs to rewrite.
+    pub description_updates: Vec<DescriptionUpdate>,
+}
+
+impl ChangePlan {
+    /// Return the mode conversion planned for an interface, if any.
+    pub fn conversion_for(&self, baseif: &BaseIf) -> Option<&ModeConversion> {
+        self.conversions.iter().find(|c| &c.baseif == baseif)
+    }
+
+    /// Changes to every bridge-domain: per-VLAN ones followed by Q-in-Q ones.
+    pub fn domain_changes(&self) -> impl Iterator<Item = &VlanChange> {
+        self.vlan_changes.values().chain(self.qinq_changes.values())
+    }
+
+    /// Bridge-domain changes grouped by bridge group, ordered by group name
+    /// and VLAN (Q-in-Q domains last).
+    pub fn changes_by_group(&self) -> BTreeMap<&str, Vec<&VlanChange>> {
+        let mut groups: BTreeMap<&str, Vec<&VlanChange>> = BTreeMap::new();
+        for change in self.domain_changes() {
+            groups.entry(&change.path.group).or_default().push(change);
+        }
+        groups
+    }
+
+    /// Interface header of a changed main interface after the change.
+    pub fn interface_header(&self, baseif: &BaseIf) -> String {
+        if self.access_interfaces.contains(baseif) {
+            format!("interface {} l2transport", baseif)
+        } else {
+            format!("interface {}", baseif)
+        }
+    }
 }
@@ -3,16 +3,34 @@
 //! This module builds the diff plan between the base configuration context and
 //! the desired change specification.
 
-use crate::ast::Span;
+use crate::ast::{Span, Spanned};
 use crate::change::model::{
-    BaseContext, ChangePlan, ChangeSpec, InterfaceChange, InterfaceCreation, InterfaceRemoval,
-    VlanChange, VlanId,
+    statement_setting, BaseContext, BaseIf, ChangePlan, ChangeSpec, DescriptionUpdate, DomainPath,
+    InterfaceChange, InterfaceCreation, InterfaceRemoval, ModeConversion, QinqTags, SwitchportMode,
+    VlanChange, VlanId, DEFAULT_BRIDGE_GROUP,
 };
 use crate::change::validator::{
-    validate_interface_description, validate_not_bundled_interface, validate_vlan_addition,
-    validate_vlan_removals,
+    validate_bundle_membership, validate_bundle_speeds, validate_bvi_removal,
+    validate_interface_description, validate_not_bundled_interface, validate_switchport_mode,
+    validate_vlan_addition, validate_vlan_removals, validate_vlan_teardown, warn_bvi_address,
+    warn_shutdown_interface,
 };
+use crate::error::{Diagnostic, ErrorKind};
+use crate::semantics::Encapsulation;
 use std::cmp::Ordering;
+use std::collections::BTreeSet;
+
+/// Resolved state of an interface whose subinterfaces are being planned.
+struct InterfaceTarget<'b> {
+    baseif: &'b BaseIf,
+    change: &'b InterfaceChange,
+    /// Switchport mode after the change.
+    mode: SwitchportMode,
+    /// Base interface description used in subinterface descriptions.
+    base_desc: String,
+    /// Tagged VLANs after the change.
+    tagged: BTreeSet<VlanId>,
+}
 
 /// Builds a concrete change plan from desired input and the existing base context.
 pub struct ChangePlanner<'a> {
@@ -20,28 +38,87 @@ pub struct ChangePlanner<'a> {
     change_spec: &'a ChangeSpec,
     /// Context describing the current configuration state.
     base_ctx: &'a BaseContext,
+    /// Bridge group that receives bridge-domains for new VLANs.
+    default_bridge_group: &'a str,
 }
 
 impl<'a> ChangePlanner<'a> {
     /// Create a planner over the given change spec and base context.
+    ///
+    /// New bridge-domains are created in `bridge group VLAN` unless
+    /// [`with_default_bridge_group`](Self::with_default_bridge_group) is used.
     pub fn new(change_spec: &'a ChangeSpec, base_ctx: &'a BaseContext) -> Self {
         Self {
             change_spec,
             base_ctx,
+            default_bridge_group: DEFAULT_BRIDGE_GROUP,
         }
     }
 
+    /// Create bridge-domains for new VLANs in `group` instead.
+    pub fn with_default_bridge_group(mut self, group: &'a str) -> Self {
+        self.default_bridge_group = group;
+        self
+    }
+
+    /// Change record for a VLAN, created on first use.
+    ///
+    /// VLANs with an existing bridge-domain keep its bridge group and name;
+    /// new ones get a `VLAN<id>` domain in the default bridge group.
+    fn vlan_change<'p>(&self, plan: &'p mut ChangePlan, vlan: VlanId) -> &'p mut VlanChange {
+        plan.vlan_changes.entry(vlan).or_insert_with(|| {
+            let path = self
+                .base_ctx
+                .domain_path(&vlan)
+                .cloned()
+                .unwrap_or_else(|| DomainPath::for_new_vlan(self.default_bridge_group, vlan));
+            VlanChange::new(vlan, path, self.change_spec)
+        })
+    }
+
     /// Produce a `ChangePlan`, validating inputs along the way.
-    pub fn plan(&self) -> Result<ChangePlan, crate::error::Diagnostic> {
+    ///
+    /// Validation failures are pushed to `diags`; interfaces that cannot be
+    /// planned are skipped so the remaining ones are still checked.
+    pub fn plan(&self, diags: &mut Vec<Diagnostic>) -> ChangePlan {
         let mut plan = ChangePlan::default();
 
+        validate_bundle_speeds(self.change_spec, self.base_ctx, diags);
+
         for (baseif, change) in &self.change_spec.interface_changes {
             let existing = self.base_ctx.vlans_for(baseif).cloned().unwrap_or_default();
 
-            validate_not_bundled_interface(baseif, change, self.base_ctx, self.change_spec)?;
-            validate_vlan_removals(baseif, change, &existing)?;
+            if !validate_not_bundled_interface(
+                baseif,
+                change,
+                self.base_ctx,
+                self.change_spec,
+                diags,
+            ) {
+                continue;
+            }
+            let Some(mode) =
+                validate_switchport_mode(baseif, change, self.base_ctx, self.change_spec, diags)
+            else {
+                continue;
+            };
+            validate_vlan_removals(baseif, change, &existing, diags);
 
-            let desired = desired_vlans(change, &existing);
+            let desired = match mode {
+                SwitchportMode::Trunk => desired_vlans(change, &existing),
+                SwitchportMode::Access => BTreeSet::new(),
+            };
+            if !validate_bundle_membership(
+                baseif,
+                change,
+                mode,
+                &desired,
+                self.base_ctx,
+                self.change_spec,
+                diags,
+            ) {
+                continue;
+            }
 
             let base_desc = change
                 .description
@@ -54,29 +131,40 @@ impl<'a> ChangePlanner<'a> {
                 });
 
             let has_description = base_desc.is_some();
-            validate_interface_description(baseif, has_description, self.change_spec)?;
+            if !validate_interface_description(baseif, has_description, self.change_spec, diags) {
+                continue;
+            }
             let base_desc = base_desc.unwrap();
 
+            self.plan_switchport_mode(baseif, change, mode, &mut plan, diags);
+
             for vlan in existing.difference(&desired) {
-                let iface = format!("{}.{}", baseif, vlan);
+                let iface = self.base_ctx.tagged_subinterface(baseif, vlan);
                 plan.removal_cmds.push(InterfaceRemoval {
                     baseif: baseif.clone(),
                     command: format!("no interface {} l2transport", iface),
+                    iface: iface.clone(),
                 });
 
-                plan.vlan_changes
-                    .entry(*vlan)
-                    .or_insert_with(|| VlanChange::new(*vlan, self.change_spec))
-                    .record_removal(iface);
+                self.vlan_change(&mut plan, *vlan).record_removal(iface);
             }
 
             for vlan in desired.difference(&existing) {
-                // Validate that the VLAN is defined in vlan database or exists in base config
-                if let Some(span) = change
+                let span = change
                     .addition_span_for(vlan)
-                    .or_else(|| self.change_spec.interface_span(baseif))
-                {
-                    validate_vlan_addition(*vlan, self.change_spec, self.base_ctx, span)?;
+                    .or_else(|| self.change_spec.interface_span(baseif));
+                // Validate that the VLAN is defined in vlan database or exists in base config
+                if let Some(span) = span {
+                    validate_vlan_addition(*vlan, self.change_spec, self.base_ctx, span, diags);
+                }
+
+                let iface = format!("{}.{}", baseif, vlan);
+                if self.subinterface_in_use(baseif, &iface, *vlan, &plan) {
+                    diags.push(Diagnostic::with_span(
+                        ErrorKind::SubinterfaceConflict { interface: iface },
+                        span.unwrap_or_else(|| Span::line_only(1)),
+                    ));
+                    continue;
                 }
 
                 plan.additions.push(InterfaceCreation {
@@ -88,23 +176,571 @@ impl<'a> ChangePlanner<'a> {
                         self.change_spec,
                         self.base_ctx,
                     ),
+                    encapsulation: Encapsulation::Dot1q(vlan.get()),
                 });
 
-                plan.vlan_changes
-                    .entry(*vlan)
-                    .or_insert_with(|| VlanChange::new(*vlan, self.change_spec))
+                self.vlan_change(&mut plan, *vlan)
                     .record_addition(baseif.clone(), *vlan);
             }
+
+            let target = InterfaceTarget {
+                baseif,
+                change,
+                mode,
+                base_desc,
+                tagged: desired,
+            };
+            let native = self.plan_native_vlan(&target, &mut plan, diags);
+            self.plan_qinq(&target, native, &mut plan, diags);
+
+            let added = plan.additions.iter().any(|a| &a.baseif == baseif)
+                || plan
+                    .vlan_changes
+                    .values()
+                    .flat_map(|c| &c.additions)
+                    .any(|m| m.access && &m.baseif == baseif);
+            if added {
+                warn_shutdown_interface(baseif, change, self.base_ctx, self.change_spec, diags);
+            }
         }
 
         for vlan in &self.change_spec.bvi_additions {
-            plan.vlan_changes
-                .entry(*vlan)
-                .or_insert_with(|| VlanChange::new(*vlan, self.change_spec))
-                .add_bvi = true;
+            if self.base_ctx.has_routed_bvi(vlan) {
+                continue;
+            }
+            self.vlan_change(&mut plan, *vlan).add_bvi = true;
+        }
+
+        self.plan_teardown(&mut plan, diags);
+        self.plan_renames(&mut plan);
+        self.plan_description_changes(&mut plan);
+
+        plan
+    }
+
+    /// Rewrite the descriptions of existing subinterfaces of interfaces whose
+    /// description is changed.
+    fn plan_description_changes(&self, plan: &mut ChangePlan) {
+        for (baseif, change) in &self.change_spec.interface_changes {
+            let Some(desc) = &change.description else {
+                continue;
+            };
+            if self.base_ctx.description_for(baseif) == Some(desc.value.as_str()) {
+                continue;
+            }
+            for (iface, vlan) in self.base_ctx.subinterfaces_of(baseif) {
+                self.plan_subinterface_description(baseif, &iface, vlan, plan);
+            }
         }
+    }
+
+    /// Rename the bridge-domains of existing VLANs given a new name by
+    /// `vlan <id> name <name>`, rewriting the descriptions of their
+    /// subinterfaces and BVIs to match.
+    fn plan_renames(&self, plan: &mut ChangePlan) {
+        for (vlan, name) in &self.change_spec.vlans {
+            let Some(name) = name else { continue };
+            let Some(old) = self.base_ctx.domain_descriptions.get(vlan) else {
+                continue;
+            };
+            if old.as_deref() == Some(name.value.as_str()) {
+                continue;
+            }
+            self.vlan_change(plan, *vlan);
 
-        Ok(plan)
+            for (baseif, iface) in self.base_ctx.subinterfaces_in(vlan) {
+                self.plan_subinterface_description(&baseif, &iface, *vlan, plan);
+            }
+
+            let bvi = BaseIf::new(format!("BVI{}", vlan));
+            let explicit = self
+                .change_spec
+                .bvi_statements
+                .get(vlan)
+                .is_some_and(|stmts| stmts.iter().any(|s| s.value.starts_with("description ")));
+            if let Some(iface) = self.base_ctx.interface(&bvi) {
+                if !explicit && !plan.bvi_removals.contains(vlan) {
+                    plan.description_updates.push(DescriptionUpdate {
+                        header: format!("interface {}", bvi),
+                        baseif: bvi,
+                        old: iface.description.clone(),
+                        new: name.value.clone(),
+                    });
+                }
+            }
+        }
+    }
+
+    /// Rewrite the description of an existing subinterface when it no longer
+    /// matches the `<vlan name>,<interface description>` convention.
+    ///
+    /// Subinterfaces removed by the change and interfaces without a
+    /// description are left alone.
+    fn plan_subinterface_description(
+        &self,
+        baseif: &BaseIf,
+        iface: &str,
+        vlan: VlanId,
+        plan: &mut ChangePlan,
+    ) {
+        if plan.removal_cmds.iter().any(|r| r.iface == iface) {
+            return;
+        }
+        let header = format!("interface {} l2transport", iface);
+        if plan.description_updates.iter().any(|u| u.header == header) {
+            return;
+        }
+        let base_desc = self
+            .change_spec
+            .interface_changes
+            .get(baseif)
+            .and_then(|change| change.description.as_ref())
+            .map(|desc| desc.value.as_str())
+            .or_else(|| self.base_ctx.description_for(baseif));
+        let Some(base_desc) = base_desc else { return };
+
+        let new = build_subinterface_description(vlan, base_desc, self.change_spec, self.base_ctx);
+        let old = self
+            .base_ctx
+            .subinterface_statements(iface)
+            .unwrap_or_default()
+            .iter()
+            .find_map(|stmt| stmt.strip_prefix("description "))
+            .map(|desc| desc.trim().to_string());
+        if old.as_deref() != Some(new.as_str()) {
+            plan.description_updates.push(DescriptionUpdate {
+                baseif: baseif.clone(),
+                header,
+                old,
+                new,
+            });
+        }
+    }
+
+    /// Whether an existing subinterface of `baseif` named `iface` carries
+    /// another VLAN than `vlan` and is kept by the plan, so a new
+    /// subinterface for `vlan` cannot take its name.
+    fn subinterface_in_use(
+        &self,
+        baseif: &BaseIf,
+        iface: &str,
+        vlan: VlanId,
+        plan: &ChangePlan,
+    ) -> bool {
+        self.base_ctx
+            .subinterfaces_of(baseif)
+            .get(iface)
+            .is_some_and(|used| *used != vlan)
+            && !plan.removal_cmds.iter().any(|r| r.iface == iface)
+    }
+
+    /// Plan `no vlan` and `no interface BVI` teardowns.
+    ///
+    /// Removing a VLAN removes every subinterface carrying it, its BVI and
+    /// its bridge-domain, along with the own bridge-domains of Q-in-Q services
+    /// with that inner tag; removing only the BVI also detaches it from the
+    /// bridge-domain.
+    fn plan_teardown(&self, plan: &mut ChangePlan, diags: &mut Vec<Diagnostic>) {
+        for (vlan, span) in &self.change_spec.vlan_removals {
+            if !validate_vlan_teardown(*vlan, *span, self.change_spec, self.base_ctx, diags) {
+                continue;
+            }
+
+            for (baseif, iface) in self.base_ctx.subinterfaces_in(vlan) {
+                if plan.removal_cmds.iter().any(|r| r.iface == iface) {
+                    continue;
+                }
+                plan.removal_cmds.push(InterfaceRemoval {
+                    baseif,
+                    command: format!("no interface {} l2transport", iface),
+                    iface,
+                });
+            }
+
+            let bvi = BaseIf::new(format!("BVI{}", vlan));
+            if self.base_ctx.interface(&bvi).is_some() {
+                warn_bvi_address(*vlan, *span, self.base_ctx, diags);
+                plan.bvi_removals.insert(*vlan);
+            }
+
+            self.vlan_change(plan, *vlan).remove_domain = true;
+            for (tags, path) in &self.base_ctx.qinq_domains {
+                if tags.inner == *vlan && self.base_ctx.domain_path(vlan) != Some(path) {
+                    self.qinq_change(plan, *tags).remove_domain = true;
+                }
+            }
+        }
+
+        for (vlan, span) in &self.change_spec.bvi_removals {
+            if self.change_spec.vlan_removals.contains_key(vlan)
+                || !validate_bvi_removal(*vlan, *span, self.change_spec, self.base_ctx, diags)
+            {
+                continue;
+            }
+            plan.bvi_removals.insert(*vlan);
+            if self.base_ctx.has_routed_bvi(vlan) {
+                self.vlan_change(plan, *vlan).remove_bvi = true;
+            }
+        }
+    }
+
+    /// Plan the native VLAN of a trunk, carried on an untagged subinterface.
+    ///
+    /// Returns the native VLAN after the change.
+    fn plan_native_vlan(
+        &self,
+        target: &InterfaceTarget,
+        plan: &mut ChangePlan,
+        diags: &mut Vec<Diagnostic>,
+    ) -> Option<VlanId> {
+        let (baseif, change) = (target.baseif, target.change);
+        let base_native = self.base_ctx.native_vlan(baseif);
+        let native = match (target.mode, &change.native_vlan) {
+            (SwitchportMode::Access, _) => None,
+            (SwitchportMode::Trunk, Some(native)) => native.value,
+            (SwitchportMode::Trunk, None) => base_native.map(|(vlan, _)| vlan),
+        };
+        if let Some(vlan) = native.filter(|vlan| target.tagged.contains(vlan)) {
+            let span = change
+                .native_vlan
+                .as_ref()
+                .map(|v| v.span)
+                .or_else(|| change.addition_span_for(&vlan))
+                .or_else(|| self.change_spec.interface_span(baseif))
+                .unwrap_or_else(|| Span::line_only(1));
+            diags.push(Diagnostic::with_span(
+                ErrorKind::NativeVlanTagged {
+                    vlan: vlan.get(),
+                    interface: baseif.to_string(),
+                },
+                span,
+            ));
+            return None;
+        }
+        if native == base_native.map(|(vlan, _)| vlan) {
+            return native;
+        }
+
+        if let Some((vlan, iface)) = base_native {
+            plan.removal_cmds.push(InterfaceRemoval {
+                baseif: baseif.clone(),
+                command: format!("no interface {} l2transport", iface),
+                iface: iface.to_string(),
+            });
+            self.vlan_change(plan, vlan)
+                .record_removal(iface.to_string());
+        }
+        if let Some(vlan) = native {
+            if let Some(span) = change
+                .native_vlan
+                .as_ref()
+                .map(|v| v.span)
+                .or_else(|| self.change_spec.interface_span(baseif))
+            {
+                validate_vlan_addition(vlan, self.change_spec, self.base_ctx, span, diags);
+            }
+            plan.additions.push(InterfaceCreation {
+                baseif: baseif.clone(),
+                vlan,
+                description: build_subinterface_description(
+                    vlan,
+                    &target.base_desc,
+                    self.change_spec,
+                    self.base_ctx,
+                ),
+                encapsulation: Encapsulation::Untagged,
+            });
+            self.vlan_change(plan, vlan)
+                .record_addition(baseif.clone(), vlan);
+        }
+        native
+    }
+
+    /// Change record for the bridge-domain of a Q-in-Q service, created on first use.
+    ///
+    /// An existing service keeps its bridge-domain. A new one joins the domain
+    /// of its inner VLAN unless another service (existing or added by the
+    /// change) has the same inner tag under a different outer tag; then it
+    /// gets a `VLAN<outer>-<inner>` domain of its own in the default bridge
+    /// group.
+    fn qinq_change<'p>(&self, plan: &'p mut ChangePlan, tags: QinqTags) -> &'p mut VlanChange {
+        let vlan = tags.inner;
+        let vlan_path = self
+            .base_ctx
+            .domain_path(&vlan)
+            .cloned()
+            .unwrap_or_else(|| DomainPath::for_new_vlan(self.default_bridge_group, vlan));
+        let shared = self
+            .base_ctx
+            .qinq_domains
+            .keys()
+            .chain(
+                self.change_spec
+                    .interface_changes
+                    .values()
+                    .flat_map(|change| change.qinq_add.keys()),
+            )
+            .any(|other| other.inner == vlan && other.outer != tags.outer);
+        let path = match self.base_ctx.qinq_domain(&tags) {
+            Some(path) => path.clone(),
+            None if shared => DomainPath::for_new_qinq(self.default_bridge_group, tags),
+            None => vlan_path.clone(),
+        };
+
+        if path == vlan_path {
+            return self.vlan_change(plan, vlan);
+        }
+        plan.qinq_changes
+            .entry(tags)
+            .or_insert_with(|| VlanChange::new(vlan, path, self.change_spec))
+    }
+
+    /// Plan Q-in-Q services on a trunk.
+    ///
+    /// A new service gets the subinterface numbered after its inner tag, or
+    /// after its outer tag when that number is already taken on the interface.
+    fn plan_qinq(
+        &self,
+        target: &InterfaceTarget,
+        native: Option<VlanId>,
+        plan: &mut ChangePlan,
+        diags: &mut Vec<Diagnostic>,
+    ) {
+        let (baseif, change) = (target.baseif, target.change);
+        let existing = self.base_ctx.qinq_for(baseif).cloned().unwrap_or_default();
+
+        for (tags, span) in &change.qinq_remove {
+            if !existing.contains_key(tags) {
+                diags.push(Diagnostic::with_span(
+                    ErrorKind::QinqNotPresent {
+                        outer: tags.outer.get(),
+                        inner: tags.inner.get(),
+                        interface: baseif.to_string(),
+                    },
+                    *span,
+                ));
+            }
+        }
+
+        for (tags, (iface, _)) in &existing {
+            let keep =
+                target.mode == SwitchportMode::Trunk && !change.qinq_remove.contains_key(tags);
+            if keep {
+                continue;
+            }
+            plan.removal_cmds.push(InterfaceRemoval {
+                baseif: baseif.clone(),
+                command: format!("no interface {} l2transport", iface),
+                iface: iface.clone(),
+            });
+            self.qinq_change(plan, *tags).record_removal(iface.clone());
+        }
+
+        // Subinterface names taken on the interface after the change.
+        let mut used: BTreeSet<String> = self
+            .base_ctx
+            .subinterfaces_of(baseif)
+            .into_keys()
+            .filter(|iface| !plan.removal_cmds.iter().any(|r| &r.iface == iface))
+            .collect();
+        used.extend(
+            target
+                .tagged
+                .iter()
+                .map(|vlan| self.base_ctx.tagged_subinterface(baseif, vlan)),
+        );
+        used.extend(native.map(|vlan| format!("{}.{}", baseif, vlan)));
+        for (tags, span) in &change.qinq_add {
+            if existing.contains_key(tags) {
+                continue;
+            }
+            let Some(number) = [tags.inner, tags.outer]
+                .into_iter()
+                .find(|number| !used.contains(&format!("{}.{}", baseif, number)))
+            else {
+                diags.push(Diagnostic::with_span(
+                    ErrorKind::SubinterfaceConflict {
+                        interface: format!("{}.{}", baseif, tags.inner),
+                    },
+                    *span,
+                ));
+                continue;
+            };
+            used.insert(format!("{}.{}", baseif, number));
+            validate_vlan_addition(tags.inner, self.change_spec, self.base_ctx, *span, diags);
+
+            plan.additions.push(InterfaceCreation {
+                baseif: baseif.clone(),
+                vlan: number,
+                description: build_subinterface_description(
+                    tags.inner,
+                    &target.base_desc,
+                    self.change_spec,
+                    self.base_ctx,
+                ),
+                encapsulation: Encapsulation::QinQ {
+                    outer: tags.outer.get(),
+                    inner: tags.inner.get(),
+                },
+            });
+            self.qinq_change(plan, *tags)
+                .record_addition(baseif.clone(), number);
+        }
+    }
+
+    /// Plan the trunk/access conversion and access VLAN membership of a main interface.
+    fn plan_switchport_mode(
+        &self,
+        baseif: &BaseIf,
+        change: &InterfaceChange,
+        mode: SwitchportMode,
+        plan: &mut ChangePlan,
+        diags: &mut Vec<Diagnostic>,
+    ) {
+        let base_access = self.base_ctx.access_vlan(baseif);
+        let is_access = self.base_ctx.is_access_port(baseif);
+
+        if is_access != (mode == SwitchportMode::Access) {
+            let applied = change.statements();
+            let base = self
+                .base_ctx
+                .interface(baseif)
+                .map(|iface| iface.statements.as_slice())
+                .unwrap_or_default();
+            plan.conversions.push(ModeConversion {
+                baseif: baseif.clone(),
+                to: mode,
+                statements: merge_statements(base, &applied),
+            });
+        }
+
+        let new_access = match mode {
+            SwitchportMode::Access => change.access_vlan.as_ref().map(|v| v.value).or(base_access),
+            SwitchportMode::Trunk => None,
+        };
+        if mode == SwitchportMode::Access {
+            plan.access_interfaces.insert(baseif.clone());
+        }
+        if new_access == base_access {
+            return;
+        }
+
+        if let Some(vlan) = base_access {
+            self.vlan_change(plan, vlan)
+                .record_removal(baseif.to_string());
+        }
+        if let Some(vlan) = new_access {
+            if let Some(span) = change
+                .access_vlan
+                .as_ref()
+                .map(|v| v.span)
+                .or_else(|| self.change_spec.interface_span(baseif))
+            {
+                validate_vlan_addition(vlan, self.change_spec, self.base_ctx, span, diags);
+            }
+            self.vlan_change(plan, vlan)
+                .record_access_addition(baseif.clone());
+        }
+    }
+}
+
+/// Apply statements to an interface's base statements, replacing statements
+/// that configure the same setting. `no <stmt>` removes the setting.
+fn merge_statements(base: &[String], applied: &[&str]) -> Vec<String> {
+    let mut merged = base.to_vec();
+    for stmt in applied {
+        let stmt = stmt.trim();
+        let setting = statement_setting(stmt);
+        let existing = merged.iter().position(|s| statement_setting(s) == setting);
+        merged.retain(|s| statement_setting(s) != setting);
+        if !stmt.starts_with("no ") {
+            let at = existing.unwrap_or(merged.len());
+            merged.insert(at, stmt.to_string());
+        }
+    }
+    merged
+}
+
+/// Move the VLANs of ports joining a Bundle-Ether onto that bundle.
+///
+/// A port that only gets `channel-group <id>` (no VLAN operation of its own)
+/// hands its tagged VLANs, native VLAN and Q-in-Q services over to the
+/// bundle, as if the change had cleared them on the port and added them under
+/// `interface Bundle-Ether<id>`. The added operations point at the
+/// `channel-group` line. Access ports, ports with explicit VLAN operations and
+/// bundles that exist neither in the base config nor in the change are left
+/// alone so that validation still reports them.
+pub fn move_bundled_vlans(change_spec: &mut ChangeSpec, base_ctx: &BaseContext) {
+    let mut moves = Vec::new();
+    for (baseif, change) in &change_spec.interface_changes {
+        let Some(Spanned {
+            value: Some(bundle_id),
+            span,
+        }) = change.bundle
+        else {
+            continue;
+        };
+        let bundle_if = BaseIf::new(format!("Bundle-Ether{}", bundle_id));
+        let Some(iface) = base_ctx.interface(baseif) else {
+            continue;
+        };
+        if change.has_trunk_ops()
+            || change.access_vlan.is_some()
+            || change.mode.is_some()
+            || iface.l2transport
+            || base_ctx.bundle_id(baseif) == Some(bundle_id)
+            || (base_ctx.interface(&bundle_if).is_none()
+                && !change_spec.interface_changes.contains_key(&bundle_if))
+        {
+            continue;
+        }
+        if iface.tagged.is_empty() && iface.native_vlan.is_none() && iface.qinq.is_empty() {
+            continue;
+        }
+        moves.push((baseif.clone(), bundle_if, span));
+    }
+
+    for (baseif, bundle_if, span) in moves {
+        let iface = base_ctx.interface(&baseif).expect("checked above");
+        let bundle_native = base_ctx.native_vlan(&bundle_if).map(|(vlan, _)| vlan);
+        let bundle_qinq = base_ctx.qinq_for(&bundle_if);
+        let bundle_vlans = base_ctx.vlans_for(&bundle_if);
+
+        let bundle = change_spec
+            .interface_changes
+            .entry(bundle_if.clone())
+            .or_default();
+        for vlan in iface.tagged.keys() {
+            if !bundle_vlans.is_some_and(|vlans| vlans.contains(vlan)) {
+                bundle.trunk_add.entry(*vlan).or_insert(span);
+            }
+        }
+        for tags in iface.qinq.keys() {
+            if !bundle_qinq.is_some_and(|qinq| qinq.contains_key(tags)) {
+                bundle.qinq_add.entry(*tags).or_insert(span);
+            }
+        }
+        let native_moved = match &iface.native_vlan {
+            Some((vlan, _)) if bundle_native.is_none() && bundle.native_vlan.is_none() => {
+                bundle.native_vlan = Some(Spanned::new(Some(*vlan), span));
+                true
+            }
+            _ => false,
+        };
+
+        let port = change_spec
+            .interface_changes
+            .get_mut(&baseif)
+            .expect("collected from the change spec");
+        if !iface.tagged.is_empty() {
+            port.trunk_clear = Some(span);
+        }
+        for tags in iface.qinq.keys() {
+            port.qinq_remove.insert(*tags, span);
+        }
+        if native_moved {
+            port.native_vlan = Some(Spanned::new(None, span));
+        }
     }
 }
 
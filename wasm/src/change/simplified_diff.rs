@@ -0,0 +1,523 @@
+//! Change input derived from an edited simplified config.
+//!
+//! Engineers can edit the whole simplified config (the output of
+//! `build_simplified_config`) to describe the desired state. This module diffs
+//! the edited copy against the original and emits the equivalent change input
+//! accepted by `parse_change_input`, including trunk/access conversions.
+
+use crate::ast::Span;
+use crate::change::input_parser::parse_change_input;
+use crate::change::model::{statement_setting, ChangeSpec, InterfaceChange, VlanId};
+use crate::error::{Diagnostic, ErrorKind};
+use crate::simplified_config::format_vlan_ranges;
+use std::collections::BTreeSet;
+
+/// Diff two simplified configs and return the change input that turns
+/// `original` into `edited`.
+///
+/// Diagnostics refer to lines of `edited`. VLANs that were already unnamed in
+/// `original` (bridge-domains without a description) are not reported.
+pub fn diff_simplified_configs(original: &str, edited: &str) -> Result<String, Vec<Diagnostic>> {
+    let before = parse_change_input(original, &mut Vec::new());
+
+    let mut diags = Vec::new();
+    let after = parse_change_input(edited, &mut diags);
+    let edited_lines: Vec<&str> = edited.lines().collect();
+    diags.retain(|diag| {
+        if !matches!(diag.kind, ErrorKind::VlanNameRequired { .. }) {
+            return true;
+        }
+        let vlan = diag
+            .span
+            .and_then(|span| edited_lines.get(span.line.get() as usize - 1))
+            .and_then(|line| line.split_whitespace().nth(1))
+            .and_then(|vlan| vlan.parse::<u32>().ok());
+        !matches!(
+            vlan.and_then(|v| before.vlans.get(&VlanId::new(v))),
+            Some(None)
+        )
+    });
+    if !diags.is_empty() {
+        return Err(diags);
+    }
+
+    let mut sections: Vec<Vec<String>> = Vec::new();
+
+    // 1. VLAN database
+    let mut vlan_lines = Vec::new();
+    for (vlan, name) in &after.vlans {
+        let name = name.as_ref().map(|n| n.value.as_str());
+        let old_name = before
+            .vlans
+            .get(vlan)
+            .map(|n| n.as_ref().map(|n| n.value.as_str()));
+        if old_name == Some(name) {
+            continue;
+        }
+        match name {
+            Some(name) => vlan_lines.push(format!("  vlan {} name {}", vlan, name)),
+            None => vlan_lines.push(format!("  vlan {}", vlan)),
+        }
+    }
+    for vlan in before.vlans.keys() {
+        if after.vlans.contains_key(vlan) {
+            continue;
+        }
+        // A VLAN dropped from the database but still listed on a port cannot
+        // be torn down.
+        let used = after
+            .interface_changes
+            .values()
+            .find_map(|change| vlan_use_span(change, *vlan));
+        match used {
+            Some(span) => diags.push(Diagnostic::with_span(
+                ErrorKind::VlanRemovalConflict { vlan: vlan.get() },
+                span,
+            )),
+            None => vlan_lines.push(format!("  no vlan {}", vlan)),
+        }
+    }
+    if !diags.is_empty() {
+        return Err(diags);
+    }
+    if !vlan_lines.is_empty() {
+        vlan_lines.insert(0, "vlan database".to_string());
+        sections.push(vlan_lines);
+    }
+
+    // 2. Trunk interfaces
+    let interfaces: BTreeSet<_> = before
+        .interface_changes
+        .keys()
+        .chain(after.interface_changes.keys())
+        .collect();
+    for ifname in interfaces {
+        let old = before.interface_changes.get(ifname);
+        let new = after.interface_changes.get(ifname);
+
+        let mut lines = Vec::new();
+        if let Some(new) = new {
+            let old_stmts = old.map(statements).unwrap_or_default();
+            let new_stmts = statements(new);
+            for stmt in &new_stmts {
+                if !old_stmts.contains(stmt) {
+                    lines.push(format!("  {}", stmt));
+                }
+            }
+            for stmt in &old_stmts {
+                // A new statement of the same setting replaces it.
+                let setting = statement_setting(stmt);
+                if !new_stmts.iter().any(|s| statement_setting(s) == setting) {
+                    lines.push(format!("  no {}", stmt));
+                }
+            }
+        }
+
+        let old_access = old.and_then(access_vlan);
+        let new_access = new.and_then(access_vlan);
+        match (old_access, new_access) {
+            (None, Some(vlan)) => {
+                lines.push("  switchport mode access".to_string());
+                lines.push(format!("  switchport access vlan {}", vlan));
+            }
+            (Some(old_vlan), Some(vlan)) if old_vlan != vlan => {
+                lines.push(format!("  switchport access vlan {}", vlan));
+            }
+            (Some(_), None) if new.is_some() => {
+                lines.push("  switchport mode trunk".to_string());
+            }
+            _ => {}
+        }
+
+        let old_vlans = old.map(trunk_vlans).unwrap_or_default();
+        let new_vlans = new.map(trunk_vlans).unwrap_or_default();
+        let added: BTreeSet<u32> = new_vlans.difference(&old_vlans).copied().collect();
+        // Converting to an access port drops every subinterface already.
+        let removed: BTreeSet<u32> = match new_access {
+            Some(_) => BTreeSet::new(),
+            None => old_vlans.difference(&new_vlans).copied().collect(),
+        };
+        if !added.is_empty() {
+            lines.push(format!(
+                "  switchport trunk allowed vlan add {}",
+                format_vlan_ranges(&added)
+            ));
+        }
+        if !removed.is_empty() {
+            lines.push(format!(
+                "  switchport trunk allowed vlan remove {}",
+                format_vlan_ranges(&removed)
+            ));
+        }
+
+        let old_native = old.and_then(native_vlan);
+        let new_native = new.and_then(native_vlan);
+        match (old_native, new_native) {
+            (_, Some(vlan)) if old_native != new_native => {
+                lines.push(format!("  switchport trunk native vlan {}", vlan));
+            }
+            (Some(_), None) if new.is_some() && new_access.is_none() => {
+                lines.push("  no switchport trunk native vlan".to_string());
+            }
+            _ => {}
+        }
+
+        let old_qinq = old.map(qinq_services).unwrap_or_default();
+        let new_qinq = new.map(qinq_services).unwrap_or_default();
+        for (outer, inner) in new_qinq.difference(&old_qinq) {
+            lines.push(format!(
+                "  switchport dot1q-tunnel vlan {} inner {}",
+                outer, inner
+            ));
+        }
+        if new.is_some() && new_access.is_none() {
+            for (outer, inner) in old_qinq.difference(&new_qinq) {
+                lines.push(format!(
+                    "  no switchport dot1q-tunnel vlan {} inner {}",
+                    outer, inner
+                ));
+            }
+        }
+
+        if !lines.is_empty() {
+            lines.insert(0, format!("interface {}", ifname));
+            sections.push(lines);
+        }
+    }
+
+    // 3. BVI interfaces
+    for vlan in &after.bvi_additions {
+        let old_stmts = bvi_statements(&before, vlan);
+        let new_stmts = bvi_statements(&after, vlan);
+        let is_new = !before.bvi_additions.contains(vlan);
+        let mut lines = vec![format!("interface BVI{}", vlan)];
+        for stmt in &new_stmts {
+            if !old_stmts.contains(stmt) {
+                lines.push(format!("  {}", stmt));
+            }
+        }
+        for stmt in &old_stmts {
+            let setting = statement_setting(stmt);
+            if !new_stmts.iter().any(|s| statement_setting(s) == setting) {
+                lines.push(format!("  no {}", stmt));
+            }
+        }
+        if is_new || lines.len() > 1 {
+            sections.push(lines);
+        }
+    }
+    // BVIs of removed VLANs go away with the VLAN.
+    for vlan in before.bvi_additions.difference(&after.bvi_additions) {
+        if after.vlans.contains_key(vlan) {
+            sections.push(vec![format!("no interface BVI{}", vlan)]);
+        }
+    }
+
+    if sections.is_empty() {
+        return Ok(String::new());
+    }
+    let mut res = sections
+        .into_iter()
+        .map(|lines| lines.join("\n"))
+        .collect::<Vec<_>>()
+        .join("\n\n");
+    res.push('\n');
+    Ok(res)
+}
+
+/// Statements under an interface other than trunk settings.
+fn statements(change: &InterfaceChange) -> Vec<String> {
+    change
+        .statements()
+        .into_iter()
+        .map(str::to_string)
+        .collect()
+}
+
+/// VLAN in `switchport access vlan <id>` of an access port.
+fn access_vlan(change: &InterfaceChange) -> Option<u32> {
+    change.access_vlan.as_ref().map(|vlan| vlan.value.get())
+}
+
+/// VLAN in `switchport trunk native vlan <id>`.
+fn native_vlan(change: &InterfaceChange) -> Option<u32> {
+    change
+        .native_vlan
+        .as_ref()
+        .and_then(|vlan| vlan.value)
+        .map(VlanId::get)
+}
+
+/// Q-in-Q services listed as `switchport dot1q-tunnel vlan <outer> inner <inner>`.
+fn qinq_services(change: &InterfaceChange) -> BTreeSet<(u32, u32)> {
+    change
+        .qinq_add
+        .keys()
+        .map(|tags| (tags.outer.get(), tags.inner.get()))
+        .collect()
+}
+
+/// Span of the statement that still uses `vlan` on an interface, if any.
+fn vlan_use_span(change: &InterfaceChange, vlan: VlanId) -> Option<Span> {
+    change
+        .trunk_set
+        .as_ref()
+        .filter(|set| set.value.contains(&vlan))
+        .map(|set| set.span)
+        .or_else(|| {
+            change
+                .access_vlan
+                .as_ref()
+                .filter(|access| access.value == vlan)
+                .map(|access| access.span)
+        })
+        .or_else(|| {
+            change
+                .native_vlan
+                .as_ref()
+                .filter(|native| native.value == Some(vlan))
+                .map(|native| native.span)
+        })
+        .or_else(|| {
+            change
+                .qinq_add
+                .iter()
+                .find(|(tags, _)| tags.inner == vlan)
+                .map(|(_, span)| *span)
+        })
+}
+
+/// VLANs listed in `switchport trunk allowed vlan <list>`.
+fn trunk_vlans(change: &InterfaceChange) -> BTreeSet<u32> {
+    change
+        .trunk_set
+        .as_ref()
+        .map(|set| set.value.iter().map(|vlan| vlan.get()).collect())
+        .unwrap_or_default()
+}
+
+/// Statements under a BVI interface.
+fn bvi_statements(spec: &ChangeSpec, vlan: &VlanId) -> Vec<String> {
+    spec.bvi_statements
+        .get(vlan)
+        .into_iter()
+        .flatten()
+        .map(|stmt| stmt.value.clone())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ORIGINAL: &str = r#"interface FortyGigE0/0/0/46
+  description To:server1
+  switchport trunk allowed vlan 300-302
+
+interface FortyGigE0/0/0/47
+  description To:server2
+  switchport trunk allowed vlan 300
+
+vlan database
+  vlan 300 name servers
+  vlan 301
+  vlan 302 name storage"#;
+
+    #[test]
+    fn derives_change_input_from_edited_simplified_config() {
+        let edited = r#"interface FortyGigE0/0/0/46
+  description To:server1-new
+  switchport trunk allowed vlan 300 302-304
+
+interface BVI400
+  description users
+
+vlan database
+  vlan 300 name servers
+  vlan 301
+  vlan 302 name storage
+  vlan 303 name web
+  vlan 304 name db
+  vlan 400 name users"#;
+
+        let change_input = diff_simplified_configs(ORIGINAL, edited).expect("diff succeeds");
+
+        let expected = [
+            "vlan database",
+            "  vlan 303 name web",
+            "  vlan 304 name db",
+            "  vlan 400 name users",
+            "",
+            "interface FortyGigE0/0/0/46",
+            "  description To:server1-new",
+            "  switchport trunk allowed vlan add 303-304",
+            "  switchport trunk allowed vlan remove 301",
+            "",
+            "interface FortyGigE0/0/0/47",
+            "  switchport trunk allowed vlan remove 300",
+            "",
+            "interface BVI400",
+            "  description users",
+            "",
+        ]
+        .join("\n");
+
+        assert_eq!(change_input, expected);
+
+        let mut diags = Vec::new();
+        let spec = parse_change_input(&change_input, &mut diags);
+        assert!(diags.is_empty(), "diagnostics: {:?}", diags);
+        assert_eq!(spec.vlans.len(), 3);
+    }
+
+    #[test]
+    fn negates_statements_of_other_settings() {
+        let original = ORIGINAL.replace(
+            "  description To:server2\n",
+            "  description To:server2\n  ipv6 enable\n  mtu 9000\n",
+        );
+        let edited = ORIGINAL.replace(
+            "  description To:server2\n",
+            "  description To:server2\n  ipv6 address 2001:db8::1/64\n  mtu 9216\n",
+        );
+
+        let change_input = diff_simplified_configs(&original, &edited).expect("diff succeeds");
+
+        assert_eq!(
+            change_input,
+            [
+                "interface FortyGigE0/0/0/47",
+                "  ipv6 address 2001:db8::1/64",
+                "  mtu 9216",
+                "  no ipv6 enable",
+                "",
+            ]
+            .join("\n")
+        );
+    }
+
+    #[test]
+    fn negates_statements_removed_from_bvi() {
+        let bvi = "interface BVI300\n  description servers\n  mtu 9000\n\n";
+        let original = format!("{}{}", bvi, ORIGINAL);
+        let edited = format!("interface BVI300\n  description servers\n\n{}", ORIGINAL);
+
+        let change_input = diff_simplified_configs(&original, &edited).expect("diff succeeds");
+
+        assert_eq!(change_input, "interface BVI300\n  no mtu 9000\n");
+    }
+
+    #[test]
+    fn rejects_removing_a_vlan_still_used_by_a_port() {
+        let edited = ORIGINAL.replace("  vlan 302 name storage", "");
+
+        let diags = diff_simplified_configs(ORIGINAL, &edited).expect_err("VLAN 302 is still used");
+
+        assert_eq!(diags.len(), 1);
+        assert_eq!(diags[0].kind.code(), "vlan-removal-conflict");
+        assert_eq!(diags[0].span.map(|s| s.line.get()), Some(3));
+
+        let edited = edited.replace("vlan 300-302", "vlan 300-301");
+        let change_input = diff_simplified_configs(ORIGINAL, &edited).expect("diff succeeds");
+        assert!(change_input.contains("  no vlan 302\n"), "{}", change_input);
+    }
+
+    #[test]
+    fn converts_trunk_port_to_access_port() {
+        let edited = ORIGINAL.replace(
+            "  switchport trunk allowed vlan 300\n",
+            "  switchport mode access\n  switchport access vlan 302\n",
+        );
+
+        let change_input = diff_simplified_configs(ORIGINAL, &edited).expect("diff succeeds");
+
+        assert_eq!(
+            change_input,
+            [
+                "interface FortyGigE0/0/0/47",
+                "  switchport mode access",
+                "  switchport access vlan 302",
+                "",
+            ]
+            .join("\n")
+        );
+    }
+
+    #[test]
+    fn sets_and_clears_native_vlan() {
+        let edited = ORIGINAL
+            .replace(
+                "  switchport trunk allowed vlan 300-302\n",
+                "  switchport trunk allowed vlan 300-301\n  switchport trunk native vlan 302\n",
+            )
+            .replace(
+                "  switchport trunk allowed vlan 300\n",
+                "  switchport trunk allowed vlan 300\n  switchport trunk native vlan 301\n",
+            );
+        let change_input = diff_simplified_configs(ORIGINAL, &edited).expect("diff succeeds");
+        assert_eq!(
+            change_input,
+            [
+                "interface FortyGigE0/0/0/46",
+                "  switchport trunk allowed vlan remove 302",
+                "  switchport trunk native vlan 302",
+                "",
+                "interface FortyGigE0/0/0/47",
+                "  switchport trunk native vlan 301",
+                "",
+            ]
+            .join("\n")
+        );
+
+        let change_input = diff_simplified_configs(&edited, ORIGINAL).expect("diff succeeds");
+        assert!(change_input.contains("  no switchport trunk native vlan\n"));
+    }
+
+    #[test]
+    fn removes_vlans_and_bvis() {
+        let original = format!(
+            "{}\n  vlan 400 name users\n\ninterface BVI302\n\ninterface BVI400",
+            ORIGINAL
+        );
+        let edited = ORIGINAL
+            .replace("300-302", "300 302")
+            .replace("  vlan 301\n", "");
+
+        let change_input = diff_simplified_configs(&original, &edited).expect("diff succeeds");
+
+        assert_eq!(
+            change_input,
+            [
+                "vlan database",
+                "  no vlan 301",
+                "  no vlan 400",
+                "",
+                "interface FortyGigE0/0/0/46",
+                "  switchport trunk allowed vlan remove 301",
+                "",
+                "no interface BVI302",
+                "",
+            ]
+            .join("\n")
+        );
+    }
+
+    #[test]
+    fn unchanged_config_yields_empty_input() {
+        let change_input = diff_simplified_configs(ORIGINAL, ORIGINAL).expect("diff succeeds");
+        assert_eq!(change_input, "");
+    }
+
+    #[test]
+    fn reports_problems_in_edited_config() {
+        let edited = ORIGINAL.replace("vlan 302 name storage", "vlan 302\n  vlan 305");
+
+        let diags = diff_simplified_configs(ORIGINAL, &edited).expect_err("diff fails");
+        let lines: Vec<u32> = diags
+            .iter()
+            .map(|diag| diag.span.expect("span").line.get())
+            .collect();
+
+        assert_eq!(lines, vec![12, 13]);
+    }
+}
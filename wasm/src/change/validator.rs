@@ -2,21 +2,28 @@
 //!
 //! This module validates change specifications against base configuration,
 //! ensuring that requested changes are valid (e.g., VLANs exist before removal).
+//! Every check pushes its findings to a diagnostics sink and reports whether it
+//! passed, so the planner can keep going and surface all problems at once.
 
-use crate::ast::Span;
-use crate::change::model::{BaseContext, BaseIf, ChangeSpec, InterfaceChange, VlanId};
+use crate::ast::{Span, Spanned};
+use crate::change::model::{
+    BaseContext, BaseIf, ChangeSpec, InterfaceChange, SwitchportMode, VlanId,
+};
 use crate::error::{Diagnostic, ErrorKind};
-use std::collections::BTreeSet;
+use crate::semantics::parse_interface_name;
+use std::collections::{BTreeMap, BTreeSet};
 
 /// Ensure VLAN removals reference VLANs that exist on the base interface.
 pub fn validate_vlan_removals(
     baseif: &BaseIf,
     change: &InterfaceChange,
     existing: &BTreeSet<VlanId>,
-) -> Result<(), Diagnostic> {
+    diags: &mut Vec<Diagnostic>,
+) -> bool {
+    let before = diags.len();
     for (vlan, span) in &change.trunk_remove {
         if !existing.contains(vlan) {
-            return Err(Diagnostic::with_span(
+            diags.push(Diagnostic::with_span(
                 ErrorKind::VlanNotPresent {
                     vlan: vlan.get(),
                     interface: baseif.to_string(),
@@ -25,7 +32,7 @@ pub fn validate_vlan_removals(
             ));
         }
     }
-    Ok(())
+    diags.len() == before
 }
 
 /// Validate that an interface has a description in either base or change input.
@@ -33,7 +40,8 @@ pub fn validate_interface_description(
     baseif: &BaseIf,
     has_description: bool,
     change_spec: &ChangeSpec,
-) -> Result<(), Diagnostic> {
+    diags: &mut Vec<Diagnostic>,
+) -> bool {
     if !has_description {
         let mut diag = Diagnostic::new(ErrorKind::MissingDescription {
             interface: baseif.to_string(),
@@ -41,9 +49,10 @@ pub fn validate_interface_description(
         if let Some(span) = change_spec.interface_span(baseif) {
             diag.span = Some(span);
         }
-        return Err(diag);
+        diags.push(diag);
+        return false;
     }
-    Ok(())
+    true
 }
 
 /// Check that a VLAN being added is defined in the change input or base config.
@@ -52,51 +61,365 @@ pub fn validate_vlan_addition(
     change_spec: &ChangeSpec,
     base_ctx: &BaseContext,
     span: Span,
-) -> Result<(), Diagnostic> {
+    diags: &mut Vec<Diagnostic>,
+) -> bool {
+    if change_spec.vlan_removals.contains_key(&vlan) {
+        diags.push(Diagnostic::with_span(
+            ErrorKind::VlanRemovalConflict { vlan: vlan.get() },
+            span,
+        ));
+        return false;
+    }
+
     let vlan_defined_in_change = change_spec.vlans.contains_key(&vlan);
     let vlan_exists_in_base = base_ctx.domain_descriptions.contains_key(&vlan);
 
     if !vlan_defined_in_change && !vlan_exists_in_base {
-        return Err(Diagnostic::with_span(
+        diags.push(Diagnostic::with_span(
             ErrorKind::VlanNotDefinedInDatabase { vlan: vlan.get() },
             span,
         ));
+        return false;
+    }
+
+    true
+}
+
+/// Check that a VLAN torn down with `no vlan` exists in the base config and
+/// is not defined again or given a BVI by the same change.
+pub fn validate_vlan_teardown(
+    vlan: VlanId,
+    span: Span,
+    change_spec: &ChangeSpec,
+    base_ctx: &BaseContext,
+    diags: &mut Vec<Diagnostic>,
+) -> bool {
+    if !base_ctx.domain_descriptions.contains_key(&vlan) {
+        diags.push(Diagnostic::with_span(
+            ErrorKind::VlanNotFound { vlan: vlan.get() },
+            span,
+        ));
+        return false;
+    }
+
+    if change_spec.vlans.contains_key(&vlan) || change_spec.bvi_additions.contains(&vlan) {
+        diags.push(Diagnostic::with_span(
+            ErrorKind::VlanRemovalConflict { vlan: vlan.get() },
+            span,
+        ));
+        return false;
+    }
+
+    true
+}
+
+/// Check that a BVI removed with `no interface BVI<id>` exists in the base
+/// config and is not configured by the same change.
+///
+/// Removing a BVI that still has an IP address is allowed but warned about.
+pub fn validate_bvi_removal(
+    vlan: VlanId,
+    span: Span,
+    change_spec: &ChangeSpec,
+    base_ctx: &BaseContext,
+    diags: &mut Vec<Diagnostic>,
+) -> bool {
+    let bvi = BaseIf::new(format!("BVI{}", vlan));
+    if base_ctx.interface(&bvi).is_none() {
+        diags.push(Diagnostic::with_span(
+            ErrorKind::BviNotPresent {
+                interface: bvi.to_string(),
+            },
+            span,
+        ));
+        return false;
+    }
+
+    if change_spec.bvi_additions.contains(&vlan) {
+        diags.push(Diagnostic::with_span(
+            ErrorKind::BviRemovalConflict {
+                interface: bvi.to_string(),
+            },
+            span,
+        ));
+        return false;
     }
 
-    Ok(())
+    warn_bvi_address(vlan, span, base_ctx, diags);
+    true
+}
+
+/// Warn when a BVI about to be removed still has an IP address.
+pub fn warn_bvi_address(
+    vlan: VlanId,
+    span: Span,
+    base_ctx: &BaseContext,
+    diags: &mut Vec<Diagnostic>,
+) {
+    if base_ctx.bvi_has_address(&vlan) {
+        diags.push(Diagnostic::with_span(
+            ErrorKind::BviHasAddress {
+                interface: format!("BVI{}", vlan),
+            },
+            span,
+        ));
+    }
+}
+
+/// Warn when VLANs are added to an interface that stays shut down after the
+/// change.
+pub fn warn_shutdown_interface(
+    baseif: &BaseIf,
+    change: &InterfaceChange,
+    base_ctx: &BaseContext,
+    change_spec: &ChangeSpec,
+    diags: &mut Vec<Diagnostic>,
+) {
+    let shutdown = change
+        .shutdown
+        .as_ref()
+        .map_or(base_ctx.is_shutdown(baseif), |s| s.value);
+    if !shutdown {
+        return;
+    }
+    let span = change
+        .trunk_ops_span()
+        .or_else(|| change.access_vlan.as_ref().map(|v| v.span))
+        .or_else(|| change_spec.interface_span(baseif))
+        .unwrap_or_else(|| Span::line_only(1));
+    diags.push(Diagnostic::with_span(
+        ErrorKind::VlansOnShutdownInterface {
+            interface: baseif.to_string(),
+        },
+        span,
+    ));
 }
 
 /// Ensure VLAN changes are not attempted on member interfaces of a bundle.
+///
+/// Interfaces joining or leaving a bundle in the same change are checked by
+/// [`validate_bundle_membership`] instead.
 pub fn validate_not_bundled_interface(
     baseif: &BaseIf,
     change: &InterfaceChange,
     base_ctx: &BaseContext,
     change_spec: &ChangeSpec,
-) -> Result<(), Diagnostic> {
+    diags: &mut Vec<Diagnostic>,
+) -> bool {
+    if change.bundle.is_some() {
+        return true;
+    }
     if let Some(bundle_id) = base_ctx.bundle_id(baseif) {
         // If interface is bundled, it should not have VLAN add/remove operations
-        if change.trunk_clear.is_some()
-            || change.trunk_set.is_some()
-            || !change.trunk_add.is_empty()
-            || !change.trunk_remove.is_empty()
-        {
+        if change.has_trunk_ops() || change.access_vlan.is_some() {
             // Get the span from the first VLAN operation
             let span = change
-                .trunk_clear
-                .or_else(|| change.trunk_set.as_ref().map(|s| s.span))
-                .or_else(|| change.trunk_add.values().copied().next())
-                .or_else(|| change.trunk_remove.values().copied().next())
+                .trunk_ops_span()
+                .or_else(|| change.access_vlan.as_ref().map(|v| v.span))
                 .or_else(|| change_spec.interface_span(baseif))
                 .unwrap_or_else(|| Span::line_only(1));
 
-            return Err(Diagnostic::with_span(
+            diags.push(Diagnostic::with_span(
                 ErrorKind::BundledInterfaceCannotConfigureVlans {
                     interface: baseif.to_string(),
                     bundle_id,
                 },
                 span,
             ));
+            return false;
         }
     }
-    Ok(())
+    true
+}
+
+/// Check a `channel-group` / `no channel-group` request on an interface.
+///
+/// A joining interface must be a physical port, its Bundle-Ether must exist in
+/// the base config or be configured by the change, and it must not carry any
+/// VLAN once the change is applied (`desired` holds its tagged VLANs after the
+/// change; ports that only join have their VLANs moved to the bundle by
+/// [`move_bundled_vlans`](crate::change::planner::move_bundled_vlans) first).
+/// A leaving interface must currently be a bundle member.
+pub fn validate_bundle_membership(
+    baseif: &BaseIf,
+    change: &InterfaceChange,
+    mode: SwitchportMode,
+    desired: &BTreeSet<VlanId>,
+    base_ctx: &BaseContext,
+    change_spec: &ChangeSpec,
+    diags: &mut Vec<Diagnostic>,
+) -> bool {
+    let Some(bundle) = &change.bundle else {
+        return true;
+    };
+    let Some(bundle_id) = bundle.value else {
+        if base_ctx.bundle_id(baseif).is_none() {
+            diags.push(Diagnostic::with_span(
+                ErrorKind::NotBundleMember {
+                    interface: baseif.to_string(),
+                },
+                bundle.span,
+            ));
+            return false;
+        }
+        return true;
+    };
+
+    if parse_interface_name(baseif.as_str()).is_none() {
+        diags.push(Diagnostic::with_span(
+            ErrorKind::InvalidBundleMember {
+                interface: baseif.to_string(),
+            },
+            bundle.span,
+        ));
+        return false;
+    }
+
+    let bundle_if = BaseIf::new(format!("Bundle-Ether{}", bundle_id));
+    if base_ctx.interface(&bundle_if).is_none()
+        && !change_spec.interface_changes.contains_key(&bundle_if)
+    {
+        diags.push(Diagnostic::with_span(
+            ErrorKind::BundleNotFound { bundle_id },
+            bundle.span,
+        ));
+        return false;
+    }
+
+    let native = match &change.native_vlan {
+        Some(native) => native.value,
+        None => base_ctx.native_vlan(baseif).map(|(vlan, _)| vlan),
+    };
+    let qinq = !change.qinq_add.is_empty()
+        || base_ctx
+            .qinq_for(baseif)
+            .is_some_and(|qinq| qinq.keys().any(|t| !change.qinq_remove.contains_key(t)));
+    if mode == SwitchportMode::Access || !desired.is_empty() || native.is_some() || qinq {
+        let span = change
+            .trunk_ops_span()
+            .or_else(|| change.access_vlan.as_ref().map(|v| v.span))
+            .unwrap_or(bundle.span);
+        diags.push(Diagnostic::with_span(
+            ErrorKind::BundledInterfaceCannotConfigureVlans {
+                interface: baseif.to_string(),
+                bundle_id,
+            },
+            span,
+        ));
+        return false;
+    }
+
+    true
+}
+
+/// Ensure every member of a bundle joined in the change has the same speed as
+/// the bundle's other members.
+///
+/// The speed of the first remaining base member (or of the first joining one
+/// when the bundle has none) is the expected speed.
+pub fn validate_bundle_speeds(
+    change_spec: &ChangeSpec,
+    base_ctx: &BaseContext,
+    diags: &mut Vec<Diagnostic>,
+) -> bool {
+    let before = diags.len();
+    let mut joining: BTreeMap<u32, Vec<(&BaseIf, Span)>> = BTreeMap::new();
+    for (baseif, change) in &change_spec.interface_changes {
+        if let Some(Spanned {
+            value: Some(bundle_id),
+            span,
+        }) = &change.bundle
+        {
+            joining.entry(*bundle_id).or_default().push((baseif, *span));
+        }
+    }
+
+    for (bundle_id, members) in joining {
+        let speed_of = |baseif: &BaseIf| parse_interface_name(baseif.as_str()).map(|(s, _)| s);
+        let remaining = base_ctx.bundle_members(bundle_id).into_iter().filter(|m| {
+            change_spec
+                .interface_changes
+                .get(*m)
+                .is_none_or(|change| change.bundle.is_none())
+        });
+        let Some(expected) = remaining
+            .chain(members.iter().map(|(baseif, _)| *baseif))
+            .find_map(speed_of)
+        else {
+            continue;
+        };
+        for (baseif, span) in members {
+            let Some(speed) = speed_of(baseif) else {
+                continue;
+            };
+            if speed != expected {
+                diags.push(Diagnostic::with_span(
+                    ErrorKind::BundleSpeedMismatch {
+                        interface: baseif.to_string(),
+                        bundle_id,
+                        speed,
+                        expected: expected.clone(),
+                    },
+                    span,
+                ));
+            }
+        }
+    }
+    diags.len() == before
+}
+
+/// Resolve the switchport mode requested for an interface.
+///
+/// Returns `None` when access and trunk settings are mixed or an access port
+/// has no VLAN. Without an explicit `switchport mode`, an access VLAN selects
+/// access mode and an interface keeps the mode it has in the base config.
+pub fn validate_switchport_mode(
+    baseif: &BaseIf,
+    change: &InterfaceChange,
+    base_ctx: &BaseContext,
+    change_spec: &ChangeSpec,
+    diags: &mut Vec<Diagnostic>,
+) -> Option<SwitchportMode> {
+    let base_access = base_ctx.access_vlan(baseif).is_some() || base_ctx.is_access_port(baseif);
+    let explicit = change.mode.as_ref().map(|m| m.value);
+
+    let conflict_span = match explicit {
+        Some(SwitchportMode::Trunk) => change.access_vlan.as_ref().map(|v| v.span),
+        Some(SwitchportMode::Access) => change.trunk_ops_span(),
+        None if change.access_vlan.is_some() || base_access => change.trunk_ops_span(),
+        None => None,
+    };
+    if let Some(span) = conflict_span {
+        diags.push(Diagnostic::with_span(
+            ErrorKind::SwitchportModeConflict {
+                interface: baseif.to_string(),
+            },
+            span,
+        ));
+        return None;
+    }
+
+    let mode = match explicit {
+        Some(mode) => mode,
+        None if change.access_vlan.is_some() || base_access => SwitchportMode::Access,
+        None => SwitchportMode::Trunk,
+    };
+
+    if mode == SwitchportMode::Access && change.access_vlan.is_none() && !base_access {
+        let span = change
+            .mode
+            .as_ref()
+            .map(|m| m.span)
+            .or_else(|| change_spec.interface_span(baseif))
+            .unwrap_or_else(|| Span::line_only(1));
+        diags.push(Diagnostic::with_span(
+            ErrorKind::AccessVlanRequired {
+                interface: baseif.to_string(),
+            },
+            span,
+        ));
+        return None;
+    }
+
+    Some(mode)
 }
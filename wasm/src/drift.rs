@@ -0,0 +1,540 @@
+//! Semantic diff between two running-configs.
+//!
+//! Blocks are matched by their header (`interface ...`, `bridge-domain VLAN...`)
+//! rather than by line position, so reordering does not show up as drift.
+//! Statement changes are reported per block with line numbers from both sides,
+//! and the VLAN/trunk level difference is summarized through `BaseContext`.
+
+use crate::ast::SpannedNode;
+use crate::change::model::{statement_setting, BaseContext};
+use crate::parse::parser::tokenize_spanned;
+use crate::semantics::analyze;
+use crate::simplified_config::format_vlan_ranges;
+use std::collections::{BTreeMap, BTreeSet};
+
+/// How a block differs between the two configs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BlockChangeKind {
+    Added,
+    Removed,
+    Modified,
+}
+
+/// A statement that differs inside a block.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum StatementChange {
+    Added {
+        text: String,
+        new_line: u32,
+    },
+    Removed {
+        text: String,
+        old_line: u32,
+    },
+    /// The same setting configured with a different value.
+    Modified {
+        old: String,
+        new: String,
+        old_line: u32,
+        new_line: u32,
+    },
+}
+
+/// Difference found for one block, identified by its header path.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BlockDiff {
+    /// Headers from the top level down to this block (empty for top-level statements).
+    pub path: Vec<String>,
+    /// Whether the block was added, removed or modified.
+    pub kind: BlockChangeKind,
+    /// Line of the block header in the old config.
+    pub old_line: Option<u32>,
+    /// Line of the block header in the new config.
+    pub new_line: Option<u32>,
+    /// Statement changes directly under this block (empty for added/removed blocks).
+    pub statements: Vec<StatementChange>,
+}
+
+/// VLAN whose bridge-domain description changed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VlanRename {
+    /// VLAN ID of the bridge-domain.
+    pub vlan: u32,
+    /// Description in the old config.
+    pub old: Option<String>,
+    /// Description in the new config.
+    pub new: Option<String>,
+}
+
+/// VLANs added to or removed from a trunk interface.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TrunkDiff {
+    /// Base interface carrying the VLANs.
+    pub interface: String,
+    /// VLANs only carried in the new config.
+    pub added: BTreeSet<u32>,
+    /// VLANs only carried in the old config.
+    pub removed: BTreeSet<u32>,
+}
+
+/// Difference at the VLAN and trunk level.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct VlanSummary {
+    /// Bridge-domains only in the new config, with their description.
+    pub added: BTreeMap<u32, Option<String>>,
+    /// Bridge-domains only in the old config, with their description.
+    pub removed: BTreeMap<u32, Option<String>>,
+    /// Bridge-domains whose description changed.
+    pub renamed: Vec<VlanRename>,
+    /// Trunk interfaces whose VLAN membership changed.
+    pub trunks: Vec<TrunkDiff>,
+}
+
+impl VlanSummary {
+    /// Whether no VLAN or trunk changed.
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty()
+            && self.removed.is_empty()
+            && self.renamed.is_empty()
+            && self.trunks.is_empty()
+    }
+}
+
+/// Semantic difference between two configs.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ConfigDiff {
+    /// Changed blocks, depth first: matched and removed blocks in old-config
+    /// order, followed by the blocks added at the same level.
+    pub blocks: Vec<BlockDiff>,
+    /// VLAN and trunk level summary.
+    pub summary: VlanSummary,
+}
+
+impl ConfigDiff {
+    /// Whether the configs are equivalent.
+    pub fn is_empty(&self) -> bool {
+        self.blocks.is_empty() && self.summary.is_empty()
+    }
+
+    /// Render the diff as text, followed by the VLAN summary.
+    pub fn render(&self) -> String {
+        let mut lines: Vec<String> = Vec::new();
+
+        for block in &self.blocks {
+            let header = if block.path.is_empty() {
+                "(top level)".to_string()
+            } else {
+                block.path.join(" > ")
+            };
+            let (mark, lines_ref) = match block.kind {
+                BlockChangeKind::Added => ('+', format!("-> {}", fmt_line(block.new_line))),
+                BlockChangeKind::Removed => ('-', format!("{} ->", fmt_line(block.old_line))),
+                BlockChangeKind::Modified => (
+                    '~',
+                    format!(
+                        "{} -> {}",
+                        fmt_line(block.old_line),
+                        fmt_line(block.new_line)
+                    ),
+                ),
+            };
+            if block.path.is_empty() {
+                lines.push(format!("{} {}", mark, header));
+            } else {
+                lines.push(format!("{} {} ({})", mark, header, lines_ref));
+            }
+
+            for stmt in &block.statements {
+                lines.push(match stmt {
+                    StatementChange::Added { text, new_line } => {
+                        format!("    + {} (-> {})", text, new_line)
+                    }
+                    StatementChange::Removed { text, old_line } => {
+                        format!("    - {} ({} ->)", text, old_line)
+                    }
+                    StatementChange::Modified {
+                        old,
+                        new,
+                        old_line,
+                        new_line,
+                    } => format!("    ~ {} => {} ({} -> {})", old, new, old_line, new_line),
+                });
+            }
+        }
+
+        if !self.summary.is_empty() {
+            if !lines.is_empty() {
+                lines.push(String::new());
+            }
+            lines.push("VLAN summary:".to_string());
+            for (vlan, desc) in &self.summary.added {
+                lines.push(format!("  + VLAN{}{}", vlan, fmt_desc(desc)));
+            }
+            for (vlan, desc) in &self.summary.removed {
+                lines.push(format!("  - VLAN{}{}", vlan, fmt_desc(desc)));
+            }
+            for rename in &self.summary.renamed {
+                lines.push(format!(
+                    "  ~ VLAN{} {} => {}",
+                    rename.vlan,
+                    rename.old.as_deref().unwrap_or("(none)"),
+                    rename.new.as_deref().unwrap_or("(none)")
+                ));
+            }
+            for trunk in &self.summary.trunks {
+                let mut ops = Vec::new();
+                if !trunk.added.is_empty() {
+                    ops.push(format!("add {}", format_vlan_ranges(&trunk.added)));
+                }
+                if !trunk.removed.is_empty() {
+                    ops.push(format!("remove {}", format_vlan_ranges(&trunk.removed)));
+                }
+                lines.push(format!("  {} trunk: {}", trunk.interface, ops.join(", ")));
+            }
+        }
+
+        if lines.is_empty() {
+            return String::new();
+        }
+        let mut res = lines.join("\n");
+        res.push('\n');
+        res
+    }
+}
+
+fn fmt_line(line: Option<u32>) -> String {
+    line.map_or_else(|| "?".to_string(), |l| l.to_string())
+}
+
+fn fmt_desc(desc: &Option<String>) -> String {
+    desc.as_ref()
+        .map_or_else(String::new, |d| format!(" {}", d))
+}
+
+/// Compare two running-configs.
+pub fn diff_configs(old_config: &str, new_config: &str) -> ConfigDiff {
+    let old_nodes = tokenize_spanned(old_config);
+    let new_nodes = tokenize_spanned(new_config);
+
+    let mut blocks = Vec::new();
+    diff_children(
+        &old_nodes,
+        &new_nodes,
+        &mut Vec::new(),
+        (None, None),
+        &mut blocks,
+    );
+
+    ConfigDiff {
+        blocks,
+        summary: summarize(&old_nodes, &new_nodes),
+    }
+}
+
+/// Compare the children of two matched blocks (or the top levels).
+fn diff_children(
+    old: &[SpannedNode],
+    new: &[SpannedNode],
+    path: &mut Vec<String>,
+    lines: (Option<u32>, Option<u32>),
+    out: &mut Vec<BlockDiff>,
+) {
+    // Statements: multiset difference, then pair single-valued settings.
+    let old_stmts: Vec<_> = old.iter().filter_map(|n| n.as_stmt()).collect();
+    let new_stmts: Vec<_> = new.iter().filter_map(|n| n.as_stmt()).collect();
+    let mut used = vec![false; new_stmts.len()];
+    let mut removed = Vec::new();
+    for stmt in &old_stmts {
+        match (0..new_stmts.len()).find(|&i| !used[i] && new_stmts[i].stmt == stmt.stmt) {
+            Some(i) => used[i] = true,
+            None => removed.push(*stmt),
+        }
+    }
+    let added: Vec<_> = new_stmts
+        .iter()
+        .zip(&used)
+        .filter(|(_, used)| !**used)
+        .map(|(stmt, _)| *stmt)
+        .collect();
+
+    let count = |stmts: &[&crate::ast::SpannedNodeStmt], key: &str| {
+        stmts
+            .iter()
+            .filter(|s| statement_setting(&s.stmt) == key)
+            .count()
+    };
+    let mut statements = Vec::new();
+    let mut paired = BTreeSet::new();
+    for stmt in &removed {
+        let key = statement_setting(&stmt.stmt);
+        if count(&removed, key) == 1 && count(&added, key) == 1 {
+            let new = added
+                .iter()
+                .find(|s| statement_setting(&s.stmt) == key)
+                .expect("counted above");
+            paired.insert(key);
+            statements.push(StatementChange::Modified {
+                old: stmt.stmt.clone(),
+                new: new.stmt.clone(),
+                old_line: stmt.span.line.get(),
+                new_line: new.span.line.get(),
+            });
+        } else {
+            statements.push(StatementChange::Removed {
+                text: stmt.stmt.clone(),
+                old_line: stmt.span.line.get(),
+            });
+        }
+    }
+    for stmt in &added {
+        if !paired.contains(statement_setting(&stmt.stmt)) {
+            statements.push(StatementChange::Added {
+                text: stmt.stmt.clone(),
+                new_line: stmt.span.line.get(),
+            });
+        }
+    }
+
+    if !statements.is_empty() {
+        out.push(BlockDiff {
+            path: path.clone(),
+            kind: BlockChangeKind::Modified,
+            old_line: lines.0,
+            new_line: lines.1,
+            statements,
+        });
+    }
+
+    // Blocks: match the n-th occurrence of each header.
+    let old_blocks: Vec<_> = old.iter().filter_map(|n| n.as_block()).collect();
+    let new_blocks: Vec<_> = new.iter().filter_map(|n| n.as_block()).collect();
+    let mut used = vec![false; new_blocks.len()];
+    for block in &old_blocks {
+        path.push(block.name.clone());
+        match (0..new_blocks.len()).find(|&i| !used[i] && new_blocks[i].name == block.name) {
+            Some(i) => {
+                used[i] = true;
+                let lines = (
+                    Some(block.span.line.get()),
+                    Some(new_blocks[i].span.line.get()),
+                );
+                diff_children(&block.stmts, &new_blocks[i].stmts, path, lines, out);
+            }
+            None => out.push(BlockDiff {
+                path: path.clone(),
+                kind: BlockChangeKind::Removed,
+                old_line: Some(block.span.line.get()),
+                new_line: None,
+                statements: Vec::new(),
+            }),
+        }
+        path.pop();
+    }
+    for (block, _) in new_blocks.iter().zip(&used).filter(|(_, used)| !**used) {
+        path.push(block.name.clone());
+        out.push(BlockDiff {
+            path: path.clone(),
+            kind: BlockChangeKind::Added,
+            old_line: None,
+            new_line: Some(block.span.line.get()),
+            statements: Vec::new(),
+        });
+        path.pop();
+    }
+}
+
+/// Summarize bridge-domain and trunk membership differences.
+fn summarize(old_nodes: &[SpannedNode], new_nodes: &[SpannedNode]) -> VlanSummary {
+    let old = BaseContext::from_analysis(&analyze(old_nodes).domains, old_nodes);
+    let new = BaseContext::from_analysis(&analyze(new_nodes).domains, new_nodes);
+
+    let mut summary = VlanSummary::default();
+
+    let vlans: BTreeSet<_> = old
+        .domain_descriptions
+        .keys()
+        .chain(new.domain_descriptions.keys())
+        .collect();
+    for vlan in vlans {
+        match (
+            old.domain_descriptions.get(vlan),
+            new.domain_descriptions.get(vlan),
+        ) {
+            (None, Some(desc)) => {
+                summary.added.insert(vlan.get(), desc.clone());
+            }
+            (Some(desc), None) => {
+                summary.removed.insert(vlan.get(), desc.clone());
+            }
+            (Some(old_desc), Some(new_desc)) if old_desc != new_desc => {
+                summary.renamed.push(VlanRename {
+                    vlan: vlan.get(),
+                    old: old_desc.clone(),
+                    new: new_desc.clone(),
+                });
+            }
+            _ => {}
+        }
+    }
+
+    let interfaces: BTreeSet<_> = old.interfaces.keys().chain(new.interfaces.keys()).collect();
+    for interface in interfaces {
+        let vlans = |ctx: &BaseContext| -> BTreeSet<u32> {
+            ctx.vlans_for(interface)
+                .map(|vlans| vlans.iter().map(|v| v.get()).collect())
+                .unwrap_or_default()
+        };
+        let (old_vlans, new_vlans) = (vlans(&old), vlans(&new));
+        let added: BTreeSet<u32> = new_vlans.difference(&old_vlans).copied().collect();
+        let removed: BTreeSet<u32> = old_vlans.difference(&new_vlans).copied().collect();
+        if !added.is_empty() || !removed.is_empty() {
+            summary.trunks.push(TrunkDiff {
+                interface: interface.to_string(),
+                added,
+                removed,
+            });
+        }
+    }
+
+    summary
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const OLD: &str = r#"hostname leaf1
+interface FortyGigE0/0/0/46
+ description To:server1
+ mtu 9000
+!
+interface FortyGigE0/0/0/46.300 l2transport
+ description servers,To:server1
+ encapsulation dot1q 300
+ rewrite ingress tag pop 1 symmetric
+!
+l2vpn
+ bridge group VLAN
+  bridge-domain VLAN300
+   description servers
+   interface FortyGigE0/0/0/46.300
+  !
+ !
+!
+"#;
+
+    const NEW: &str = r#"hostname leaf1
+interface FortyGigE0/0/0/46.400 l2transport
+ description web,To:server1
+ encapsulation dot1q 400
+ rewrite ingress tag pop 1 symmetric
+!
+interface FortyGigE0/0/0/46
+ description To:server1-new
+ mtu 9000
+ load-interval 30
+!
+l2vpn
+ bridge group VLAN
+  bridge-domain VLAN300
+   description servers
+  !
+  bridge-domain VLAN400
+   description web
+   interface FortyGigE0/0/0/46.400
+  !
+ !
+!
+"#;
+
+    #[test]
+    fn matches_blocks_by_header() {
+        let diff = diff_configs(OLD, NEW);
+
+        assert_eq!(
+            diff.blocks[0],
+            BlockDiff {
+                path: vec!["interface FortyGigE0/0/0/46".to_string()],
+                kind: BlockChangeKind::Modified,
+                old_line: Some(2),
+                new_line: Some(7),
+                statements: vec![
+                    StatementChange::Modified {
+                        old: "description To:server1".to_string(),
+                        new: "description To:server1-new".to_string(),
+                        old_line: 3,
+                        new_line: 8,
+                    },
+                    StatementChange::Added {
+                        text: "load-interval 30".to_string(),
+                        new_line: 10,
+                    },
+                ],
+            }
+        );
+
+        let rendered = diff.render();
+        let expected = [
+            "~ interface FortyGigE0/0/0/46 (2 -> 7)",
+            "    ~ description To:server1 => description To:server1-new (3 -> 8)",
+            "    + load-interval 30 (-> 10)",
+            "- interface FortyGigE0/0/0/46.300 l2transport (6 ->)",
+            "~ l2vpn > bridge group VLAN > bridge-domain VLAN300 (13 -> 14)",
+            "    - interface FortyGigE0/0/0/46.300 (15 ->)",
+            "+ l2vpn > bridge group VLAN > bridge-domain VLAN400 (-> 17)",
+            "+ interface FortyGigE0/0/0/46.400 l2transport (-> 2)",
+            "",
+            "VLAN summary:",
+            "  + VLAN400 web",
+            "  FortyGigE0/0/0/46 trunk: add 400, remove 300",
+            "",
+        ]
+        .join("\n");
+        assert_eq!(rendered, expected);
+    }
+
+    #[test]
+    fn identical_configs_have_no_diff() {
+        let reordered = r#"l2vpn
+ bridge group VLAN
+  bridge-domain VLAN300
+   interface FortyGigE0/0/0/46.300
+   description servers
+  !
+ !
+!
+interface FortyGigE0/0/0/46.300 l2transport
+ description servers,To:server1
+ encapsulation dot1q 300
+ rewrite ingress tag pop 1 symmetric
+!
+interface FortyGigE0/0/0/46
+ mtu 9000
+ description To:server1
+!
+hostname leaf1
+"#;
+        let diff = diff_configs(OLD, reordered);
+        assert!(diff.is_empty(), "{}", diff.render());
+        assert_eq!(diff.render(), "");
+    }
+
+    #[test]
+    fn pairs_only_statements_of_the_same_setting() {
+        let old = "interface FortyGigE0/0/0/46\n mtu 9000\n ipv6 enable\n!\n";
+        let new = "interface FortyGigE0/0/0/46\n mtu 9216\n ipv6 address 2001:db8::1/64\n!\n";
+
+        let rendered = diff_configs(old, new).render();
+        assert_eq!(
+            rendered,
+            [
+                "~ interface FortyGigE0/0/0/46 (1 -> 1)",
+                "    ~ mtu 9000 => mtu 9216 (2 -> 2)",
+                "    - ipv6 enable (3 ->)",
+                "    + ipv6 address 2001:db8::1/64 (-> 3)",
+                "",
+            ]
+            .join("\n")
+        );
+    }
+}
@@ -0,0 +1,593 @@
+//! Message catalogs used to render diagnostics in a given language.
+
+use crate::error::ErrorKind;
+
+/// Language used to render diagnostic messages.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Locale {
+    #[default]
+    Ja,
+    En,
+}
+
+impl Locale {
+    /// Parse a language tag such as `ja`, `en` or `en-US` (case-insensitive).
+    pub fn from_tag(tag: &str) -> Option<Self> {
+        let lang = tag.split(['-', '_']).next()?.to_ascii_lowercase();
+        match lang.as_str() {
+            "ja" => Some(Locale::Ja),
+            "en" => Some(Locale::En),
+            _ => None,
+        }
+    }
+
+    /// Resolve an optional language tag, falling back to the default locale.
+    pub fn from_tag_or_default(tag: Option<&str>) -> Self {
+        tag.and_then(Locale::from_tag).unwrap_or_default()
+    }
+
+    /// Built-in catalog for this locale.
+    pub fn catalog(self) -> &'static dyn MessageCatalog {
+        match self {
+            Locale::Ja => &Japanese,
+            Locale::En => &English,
+        }
+    }
+}
+
+/// Source of user-facing text for diagnostics.
+///
+/// Implement this to render diagnostics in a language that is not built in.
+pub trait MessageCatalog {
+    /// Render the message for an error kind without span information.
+    fn message(&self, kind: &ErrorKind) -> String;
+
+    /// Render an optional suggestion on how to fix the problem.
+    fn hint(&self, kind: &ErrorKind) -> Option<String>;
+
+    /// Append a source line reference to a rendered message.
+    fn at_line(&self, message: &str, line: u32) -> String;
+}
+
+/// Japanese messages (the default).
+pub struct Japanese;
+
+impl MessageCatalog for Japanese {
+    fn message(&self, kind: &ErrorKind) -> String {
+        match kind {
+            ErrorKind::VlanNotPresent { vlan, interface } => {
+                format!(
+                    "ベース設定に存在しないため、インターフェイス{}からVLAN {}を削除できません",
+                    interface, vlan
+                )
+            }
+            ErrorKind::VlanNotDefinedInDatabase { vlan } => {
+                format!(
+                    "VLAN {} がvlan databaseまたはベース設定で定義されていません",
+                    vlan
+                )
+            }
+            ErrorKind::VlanNameRequired { vlan } => {
+                if let Some(v) = vlan {
+                    format!("VLAN {} の名前は必須です", v)
+                } else {
+                    "VLAN名は必須です".to_string()
+                }
+            }
+            ErrorKind::InvalidVlanId { text } => {
+                format!("無効なVLAN IDです: {}", text)
+            }
+            ErrorKind::InvalidVlanNumber { text } => {
+                format!("無効なVLAN番号です: {}", text)
+            }
+            ErrorKind::InvalidVlanRange { text } => {
+                format!(
+                    "無効なVLAN範囲です（開始値は終了値以下である必要があります）: {}",
+                    text
+                )
+            }
+            ErrorKind::VlanListEmpty => "VLANリストが空です".to_string(),
+            ErrorKind::VlanNotFound { vlan } => {
+                format!("VLAN {} はベース設定に存在しないため削除できません", vlan)
+            }
+            ErrorKind::VlanRemovalConflict { vlan } => {
+                format!("削除するVLAN {} を同じ変更で使用しています", vlan)
+            }
+            ErrorKind::MissingDescription { interface } => {
+                format!("インターフェイス{}にはdescriptionが必要です", interface)
+            }
+            ErrorKind::InvalidBviNumber { text } => {
+                format!("無効なBVI番号です: {}", text)
+            }
+            ErrorKind::BviNotPresent { interface } => {
+                format!("{} はベース設定に存在しないため削除できません", interface)
+            }
+            ErrorKind::BviRemovalConflict { interface } => {
+                format!("削除する{}を同じ変更で設定しています", interface)
+            }
+            ErrorKind::BviHasAddress { interface } => {
+                format!("削除する{}にIPアドレスが設定されています", interface)
+            }
+            ErrorKind::VlansOnShutdownInterface { interface } => {
+                format!(
+                    "shutdown されているインターフェイス{}にVLANを追加しています",
+                    interface
+                )
+            }
+            ErrorKind::InvalidInterfaceRange { text } => {
+                format!("無効なインターフェイス範囲です: {}", text)
+            }
+            ErrorKind::BundledInterfaceCannotConfigureVlans {
+                interface,
+                bundle_id,
+            } => {
+                format!(
+                    "インターフェイス{}はBundle {}のメンバーのため直接VLANを設定できません。代わりにBundle-Ether{}で設定してください",
+                    interface, bundle_id, bundle_id
+                )
+            }
+            ErrorKind::InvalidBundleId { text } => {
+                format!("無効なBundle番号です: {}", text)
+            }
+            ErrorKind::UnsupportedBundleMode { mode } => {
+                format!("channel-group の mode {} はサポートされていません", mode)
+            }
+            ErrorKind::BundleNotFound { bundle_id } => {
+                format!(
+                    "Bundle-Ether{} がベース設定にも変更にも存在しません",
+                    bundle_id
+                )
+            }
+            ErrorKind::InvalidBundleMember { interface } => {
+                format!(
+                    "{} は物理インターフェイスではないためBundleのメンバーにできません",
+                    interface
+                )
+            }
+            ErrorKind::NotBundleMember { interface } => {
+                format!(
+                    "インターフェイス{}はBundleのメンバーではないため外せません",
+                    interface
+                )
+            }
+            ErrorKind::BundleSpeedMismatch {
+                interface,
+                bundle_id,
+                speed,
+                expected,
+            } => {
+                format!(
+                    "インターフェイス{}（{}）はBundle-Ether{}の他のメンバー（{}）と速度が異なります",
+                    interface, speed, bundle_id, expected
+                )
+            }
+            ErrorKind::UnsupportedSwitchportMode { mode } => {
+                format!("switchport mode {} はサポートされていません", mode)
+            }
+            ErrorKind::SwitchportModeConflict { interface } => {
+                format!(
+                    "インターフェイス{}でaccessとtrunkの設定が混在しています",
+                    interface
+                )
+            }
+            ErrorKind::AccessVlanRequired { interface } => {
+                format!(
+                    "インターフェイス{}をaccessモードにするにはaccess VLANが必要です",
+                    interface
+                )
+            }
+            ErrorKind::NativeVlanTagged { vlan, interface } => {
+                format!(
+                    "VLAN {} はインターフェイス{}のnative VLANのため、tagged VLANとして許可できません",
+                    vlan, interface
+                )
+            }
+            ErrorKind::QinqNotPresent {
+                outer,
+                inner,
+                interface,
+            } => {
+                format!(
+                    "ベース設定に存在しないため、インターフェイス{}からQ-in-Q (outer {} / inner {})を削除できません",
+                    interface, outer, inner
+                )
+            }
+            ErrorKind::SubinterfaceConflict { interface } => {
+                format!(
+                    "サブインターフェイス{}が別のVLANまたはQ-in-Qと重複しています",
+                    interface
+                )
+            }
+            ErrorKind::InvalidTrunkAction { action } => {
+                format!("無効なtrunkアクションです: {}", action)
+            }
+            ErrorKind::EncapsulationMismatch { interface } => {
+                format!(
+                    "sub-interface number と encapsulation tag が一致していない: {}",
+                    interface
+                )
+            }
+            ErrorKind::MissingRewrite { interface, pop } => {
+                format!(
+                    "rewrite ingress tag pop {} symmetric が存在しない: {}",
+                    pop, interface
+                )
+            }
+            ErrorKind::BviNumberMismatch { interface } => {
+                format!("BVI number がブリッジ名と異なる: {}", interface)
+            }
+            ErrorKind::BviNotConfigured { interface } => {
+                format!("routed interface の BVI が定義されていない: {}", interface)
+            }
+            ErrorKind::SubinterfaceNumberMismatch { interface } => {
+                format!("sub-interface number がブリッジ名と異なる: {}", interface)
+            }
+            ErrorKind::InconsistentIndentation { found, expected } => format!(
+                "インデント（{}桁）が同じブロックの行（{}桁）と揃っていません",
+                found, expected
+            ),
+            ErrorKind::MixedIndentation => "インデントにタブとスペースが混在しています".to_string(),
+            ErrorKind::Generic { message } => message.clone(),
+        }
+    }
+
+    fn hint(&self, kind: &ErrorKind) -> Option<String> {
+        match kind {
+            ErrorKind::VlanNotDefinedInDatabase { vlan } => Some(format!(
+                "vlan database に vlan {} name <名前> を追加してください",
+                vlan
+            )),
+            ErrorKind::VlanNameRequired { .. } => {
+                Some("vlan <番号> name <名前> の形式で指定してください".to_string())
+            }
+            ErrorKind::MissingDescription { .. } => {
+                Some("description <説明> を追加してください".to_string())
+            }
+            ErrorKind::UnsupportedSwitchportMode { .. } => Some(
+                "switchport mode trunk または switchport mode access を使用してください"
+                    .to_string(),
+            ),
+            ErrorKind::SwitchportModeConflict { .. } => Some(
+                "accessからtrunkへ変更する場合は switchport mode trunk を明示してください"
+                    .to_string(),
+            ),
+            ErrorKind::AccessVlanRequired { .. } => {
+                Some("switchport access vlan <番号> を追加してください".to_string())
+            }
+            ErrorKind::NativeVlanTagged { .. } => {
+                Some("switchport trunk allowed vlan から native VLAN を除いてください".to_string())
+            }
+            ErrorKind::InvalidTrunkAction { .. } => {
+                Some("add または remove を指定してください".to_string())
+            }
+            ErrorKind::EncapsulationMismatch { .. } => Some(
+                "encapsulation dot1q の tag を sub-interface number と揃えてください".to_string(),
+            ),
+            ErrorKind::MissingRewrite { pop, .. } => Some(format!(
+                "rewrite ingress tag pop {} symmetric を追加してください",
+                pop
+            )),
+            ErrorKind::BviNotConfigured { interface } => {
+                Some(format!("interface {} を追加してください", interface))
+            }
+            ErrorKind::VlanRemovalConflict { vlan } => Some(format!(
+                "no vlan {} か、VLAN {} を使う設定のどちらかを削除してください",
+                vlan, vlan
+            )),
+            ErrorKind::BviHasAddress { .. } => {
+                Some("L3 の設定が不要になったことを確認してから適用してください".to_string())
+            }
+            ErrorKind::VlansOnShutdownInterface { .. } => {
+                Some("通信させる場合は同じ変更で no shutdown を追加してください".to_string())
+            }
+            ErrorKind::InvalidInterfaceRange { .. } => Some(
+                "FortyGigE0/0/0/10 - 20, FortyGigE0/0/0/30 のように指定してください".to_string(),
+            ),
+            ErrorKind::UnsupportedBundleMode { .. } => {
+                Some("mode active、mode passive または mode on を使用してください".to_string())
+            }
+            ErrorKind::BundleNotFound { bundle_id } => Some(format!(
+                "interface Bundle-Ether{} を追加してください",
+                bundle_id
+            )),
+            ErrorKind::InconsistentIndentation { .. } => {
+                Some("同じ階層の行は同じ幅でインデントしてください".to_string())
+            }
+            ErrorKind::MixedIndentation => {
+                Some("タブは8桁ごとのタブストップとして解釈します".to_string())
+            }
+            _ => None,
+        }
+    }
+
+    fn at_line(&self, message: &str, line: u32) -> String {
+        format!("{}（{}行目）", message, line)
+    }
+}
+
+/// English messages.
+pub struct English;
+
+impl MessageCatalog for English {
+    fn message(&self, kind: &ErrorKind) -> String {
+        match kind {
+            ErrorKind::VlanNotPresent { vlan, interface } => {
+                format!(
+                    "Cannot remove VLAN {} from interface {} because it is not in the base config",
+                    vlan, interface
+                )
+            }
+            ErrorKind::VlanNotDefinedInDatabase { vlan } => {
+                format!(
+                    "VLAN {} is not defined in the vlan database or the base config",
+                    vlan
+                )
+            }
+            ErrorKind::VlanNameRequired { vlan } => {
+                if let Some(v) = vlan {
+                    format!("VLAN {} requires a name", v)
+                } else {
+                    "VLAN name is required".to_string()
+                }
+            }
+            ErrorKind::InvalidVlanId { text } => format!("Invalid VLAN ID: {}", text),
+            ErrorKind::InvalidVlanNumber { text } => format!("Invalid VLAN number: {}", text),
+            ErrorKind::InvalidVlanRange { text } => {
+                format!(
+                    "Invalid VLAN range (start must not be greater than end): {}",
+                    text
+                )
+            }
+            ErrorKind::VlanListEmpty => "VLAN list is empty".to_string(),
+            ErrorKind::VlanNotFound { vlan } => {
+                format!(
+                    "Cannot remove VLAN {} because it is not in the base config",
+                    vlan
+                )
+            }
+            ErrorKind::VlanRemovalConflict { vlan } => {
+                format!("VLAN {} is removed but also used by this change", vlan)
+            }
+            ErrorKind::MissingDescription { interface } => {
+                format!("Interface {} requires a description", interface)
+            }
+            ErrorKind::InvalidBviNumber { text } => format!("Invalid BVI number: {}", text),
+            ErrorKind::BviNotPresent { interface } => {
+                format!(
+                    "Cannot remove {} because it is not in the base config",
+                    interface
+                )
+            }
+            ErrorKind::BviRemovalConflict { interface } => {
+                format!(
+                    "{} is removed but also configured by this change",
+                    interface
+                )
+            }
+            ErrorKind::BviHasAddress { interface } => {
+                format!("{} still has an IP address and will be removed", interface)
+            }
+            ErrorKind::VlansOnShutdownInterface { interface } => {
+                format!(
+                    "Adding VLANs to interface {}, which is shut down",
+                    interface
+                )
+            }
+            ErrorKind::InvalidInterfaceRange { text } => {
+                format!("Invalid interface range: {}", text)
+            }
+            ErrorKind::BundledInterfaceCannotConfigureVlans {
+                interface,
+                bundle_id,
+            } => {
+                format!(
+                    "Interface {} is a member of Bundle {} and cannot carry VLANs directly. Configure them on Bundle-Ether{} instead",
+                    interface, bundle_id, bundle_id
+                )
+            }
+            ErrorKind::InvalidBundleId { text } => {
+                format!("Invalid bundle number: {}", text)
+            }
+            ErrorKind::UnsupportedBundleMode { mode } => {
+                format!("channel-group mode {} is not supported", mode)
+            }
+            ErrorKind::BundleNotFound { bundle_id } => {
+                format!(
+                    "Bundle-Ether{} is neither in the base config nor in the change",
+                    bundle_id
+                )
+            }
+            ErrorKind::InvalidBundleMember { interface } => {
+                format!(
+                    "{} is not a physical interface and cannot join a bundle",
+                    interface
+                )
+            }
+            ErrorKind::NotBundleMember { interface } => {
+                format!(
+                    "Interface {} is not a bundle member and cannot leave one",
+                    interface
+                )
+            }
+            ErrorKind::BundleSpeedMismatch {
+                interface,
+                bundle_id,
+                speed,
+                expected,
+            } => {
+                format!(
+                    "Interface {} ({}) does not match the speed of the other members of Bundle-Ether{} ({})",
+                    interface, speed, bundle_id, expected
+                )
+            }
+            ErrorKind::UnsupportedSwitchportMode { mode } => {
+                format!("switchport mode {} is not supported", mode)
+            }
+            ErrorKind::SwitchportModeConflict { interface } => {
+                format!("Interface {} mixes access and trunk settings", interface)
+            }
+            ErrorKind::AccessVlanRequired { interface } => {
+                format!(
+                    "Interface {} needs an access VLAN to be in access mode",
+                    interface
+                )
+            }
+            ErrorKind::NativeVlanTagged { vlan, interface } => {
+                format!(
+                    "VLAN {} is the native VLAN of interface {} and cannot also be allowed tagged",
+                    vlan, interface
+                )
+            }
+            ErrorKind::QinqNotPresent {
+                outer,
+                inner,
+                interface,
+            } => {
+                format!(
+                    "Cannot remove Q-in-Q (outer {} / inner {}) from interface {} because it is not in the base config",
+                    outer, inner, interface
+                )
+            }
+            ErrorKind::SubinterfaceConflict { interface } => {
+                format!(
+                    "Subinterface {} is already used by another VLAN or Q-in-Q service",
+                    interface
+                )
+            }
+            ErrorKind::InvalidTrunkAction { action } => {
+                format!("Invalid trunk action: {}", action)
+            }
+            ErrorKind::EncapsulationMismatch { interface } => {
+                format!(
+                    "sub-interface number does not match the encapsulation tag: {}",
+                    interface
+                )
+            }
+            ErrorKind::MissingRewrite { interface, pop } => {
+                format!(
+                    "rewrite ingress tag pop {} symmetric is missing: {}",
+                    pop, interface
+                )
+            }
+            ErrorKind::BviNumberMismatch { interface } => {
+                format!(
+                    "BVI number does not match the bridge-domain name: {}",
+                    interface
+                )
+            }
+            ErrorKind::BviNotConfigured { interface } => {
+                format!("routed interface BVI is not configured: {}", interface)
+            }
+            ErrorKind::SubinterfaceNumberMismatch { interface } => {
+                format!(
+                    "sub-interface number does not match the bridge-domain name: {}",
+                    interface
+                )
+            }
+            ErrorKind::InconsistentIndentation { found, expected } => format!(
+                "Indentation of {} columns does not match the other lines of the block ({} columns)",
+                found, expected
+            ),
+            ErrorKind::MixedIndentation => "Indentation mixes tabs and spaces".to_string(),
+            ErrorKind::Generic { message } => message.clone(),
+        }
+    }
+
+    fn hint(&self, kind: &ErrorKind) -> Option<String> {
+        match kind {
+            ErrorKind::VlanNotDefinedInDatabase { vlan } => Some(format!(
+                "Add vlan {} name <name> to the vlan database",
+                vlan
+            )),
+            ErrorKind::VlanNameRequired { .. } => {
+                Some("Use the form vlan <id> name <name>".to_string())
+            }
+            ErrorKind::MissingDescription { .. } => Some("Add description <text>".to_string()),
+            ErrorKind::UnsupportedSwitchportMode { .. } => {
+                Some("Use switchport mode trunk or switchport mode access".to_string())
+            }
+            ErrorKind::SwitchportModeConflict { .. } => Some(
+                "Give switchport mode trunk explicitly to convert an access port to trunk"
+                    .to_string(),
+            ),
+            ErrorKind::AccessVlanRequired { .. } => {
+                Some("Add switchport access vlan <id>".to_string())
+            }
+            ErrorKind::NativeVlanTagged { .. } => {
+                Some("Remove the native VLAN from switchport trunk allowed vlan".to_string())
+            }
+            ErrorKind::InvalidTrunkAction { .. } => Some("Use add or remove".to_string()),
+            ErrorKind::EncapsulationMismatch { .. } => {
+                Some("Make the encapsulation dot1q tag match the sub-interface number".to_string())
+            }
+            ErrorKind::MissingRewrite { pop, .. } => {
+                Some(format!("Add rewrite ingress tag pop {} symmetric", pop))
+            }
+            ErrorKind::BviNotConfigured { interface } => {
+                Some(format!("Add interface {}", interface))
+            }
+            ErrorKind::VlanRemovalConflict { vlan } => Some(format!(
+                "Drop either no vlan {} or the settings that use VLAN {}",
+                vlan, vlan
+            )),
+            ErrorKind::BviHasAddress { .. } => {
+                Some("Make sure the L3 configuration is no longer needed".to_string())
+            }
+            ErrorKind::VlansOnShutdownInterface { .. } => {
+                Some("Add no shutdown in the same change to bring the port up".to_string())
+            }
+            ErrorKind::InvalidInterfaceRange { .. } => {
+                Some("Use the form FortyGigE0/0/0/10 - 20, FortyGigE0/0/0/30".to_string())
+            }
+            ErrorKind::UnsupportedBundleMode { .. } => {
+                Some("Use mode active, mode passive or mode on".to_string())
+            }
+            ErrorKind::BundleNotFound { bundle_id } => {
+                Some(format!("Add interface Bundle-Ether{}", bundle_id))
+            }
+            ErrorKind::InconsistentIndentation { .. } => {
+                Some("Indent lines of the same level by the same width".to_string())
+            }
+            ErrorKind::MixedIndentation => {
+                Some("Tabs are read as advancing to the next multiple of 8 columns".to_string())
+            }
+            _ => None,
+        }
+    }
+
+    fn at_line(&self, message: &str, line: u32) -> String {
+        format!("{} (line {})", message, line)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_language_tags() {
+        assert_eq!(Locale::from_tag("en"), Some(Locale::En));
+        assert_eq!(Locale::from_tag("en-US"), Some(Locale::En));
+        assert_eq!(Locale::from_tag("JA_jp"), Some(Locale::Ja));
+        assert_eq!(Locale::from_tag("fr"), None);
+        assert_eq!(Locale::from_tag_or_default(Some("fr")), Locale::Ja);
+        assert_eq!(Locale::from_tag_or_default(None), Locale::Ja);
+    }
+
+    #[test]
+    fn renders_messages_per_locale() {
+        let kind = ErrorKind::VlanNotDefinedInDatabase { vlan: 300 };
+        assert_eq!(
+            Locale::Ja
+                .catalog()
+                .at_line(&Locale::Ja.catalog().message(&kind), 4),
+            "VLAN 300 がvlan databaseまたはベース設定で定義されていません（4行目）"
+        );
+        assert_eq!(
+            Locale::En
+                .catalog()
+                .at_line(&Locale::En.catalog().message(&kind), 4),
+            "VLAN 300 is not defined in the vlan database or the base config (line 4)"
+        );
+    }
+}
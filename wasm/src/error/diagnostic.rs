@@ -1,96 +1,239 @@
 use crate::ast::Span;
+use crate::error::catalog::{Japanese, MessageCatalog};
+
+/// How serious a diagnostic is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Severity {
+    Error,
+    Warning,
+    Info,
+}
+
+impl Severity {
+    /// Lowercase name used when exposing the severity to callers.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Severity::Error => "error",
+            Severity::Warning => "warning",
+            Severity::Info => "info",
+        }
+    }
+}
 
 /// Represents different kinds of errors that can occur during parsing and validation
 #[derive(Debug, Clone)]
 pub enum ErrorKind {
     // VLAN-related errors
-    VlanNotPresent { vlan: u32, interface: String },
-    VlanNotDefinedInDatabase { vlan: u32 },
-    VlanNameRequired { vlan: Option<u32> },
-    InvalidVlanId { text: String },
-    InvalidVlanNumber { text: String },
-    InvalidVlanRange { text: String },
+    VlanNotPresent {
+        vlan: u32,
+        interface: String,
+    },
+    VlanNotDefinedInDatabase {
+        vlan: u32,
+    },
+    VlanNameRequired {
+        vlan: Option<u32>,
+    },
+    InvalidVlanId {
+        text: String,
+    },
+    InvalidVlanNumber {
+        text: String,
+    },
+    InvalidVlanRange {
+        text: String,
+    },
     VlanListEmpty,
+    VlanNotFound {
+        vlan: u32,
+    },
+    VlanRemovalConflict {
+        vlan: u32,
+    },
 
     // Interface-related errors
-    MissingDescription { interface: String },
-    InvalidBviNumber { text: String },
-    BundledInterfaceCannotConfigureVlans { interface: String, bundle_id: u32 },
+    MissingDescription {
+        interface: String,
+    },
+    InvalidBviNumber {
+        text: String,
+    },
+    BviNotPresent {
+        interface: String,
+    },
+    BviRemovalConflict {
+        interface: String,
+    },
+    BviHasAddress {
+        interface: String,
+    },
+    VlansOnShutdownInterface {
+        interface: String,
+    },
+    InvalidInterfaceRange {
+        text: String,
+    },
+    BundledInterfaceCannotConfigureVlans {
+        interface: String,
+        bundle_id: u32,
+    },
+    InvalidBundleId {
+        text: String,
+    },
+    UnsupportedBundleMode {
+        mode: String,
+    },
+    BundleNotFound {
+        bundle_id: u32,
+    },
+    InvalidBundleMember {
+        interface: String,
+    },
+    NotBundleMember {
+        interface: String,
+    },
+    BundleSpeedMismatch {
+        interface: String,
+        bundle_id: u32,
+        speed: String,
+        expected: String,
+    },
 
     // Switchport mode errors
-    UnsupportedSwitchportMode { mode: String },
-    AccessModeNotSupported,
+    UnsupportedSwitchportMode {
+        mode: String,
+    },
+    SwitchportModeConflict {
+        interface: String,
+    },
+    AccessVlanRequired {
+        interface: String,
+    },
+    NativeVlanTagged {
+        vlan: u32,
+        interface: String,
+    },
+    QinqNotPresent {
+        outer: u32,
+        inner: u32,
+        interface: String,
+    },
+    SubinterfaceConflict {
+        interface: String,
+    },
 
     // Trunk action errors
-    InvalidTrunkAction { action: String },
+    InvalidTrunkAction {
+        action: String,
+    },
+
+    // Base configuration lint findings
+    EncapsulationMismatch {
+        interface: String,
+    },
+    MissingRewrite {
+        interface: String,
+        pop: u32,
+    },
+    BviNumberMismatch {
+        interface: String,
+    },
+    BviNotConfigured {
+        interface: String,
+    },
+    SubinterfaceNumberMismatch {
+        interface: String,
+    },
+
+    // Indentation problems recovered by the parser
+    InconsistentIndentation {
+        found: usize,
+        expected: usize,
+    },
+    MixedIndentation,
 
     // Generic errors
-    Generic { message: String },
+    Generic {
+        message: String,
+    },
 }
 
 impl ErrorKind {
-    /// Format the error message without span information
-    pub fn message(&self) -> String {
+    /// Stable identifier for the error kind, independent of the message text.
+    pub fn code(&self) -> &'static str {
         match self {
-            ErrorKind::VlanNotPresent { vlan, interface } => {
-                format!(
-                    "ベース設定に存在しないため、インターフェイス{}からVLAN {}を削除できません",
-                    interface, vlan
-                )
-            }
-            ErrorKind::VlanNotDefinedInDatabase { vlan } => {
-                format!(
-                    "VLAN {} がvlan databaseまたはベース設定で定義されていません",
-                    vlan
-                )
-            }
-            ErrorKind::VlanNameRequired { vlan } => {
-                if let Some(v) = vlan {
-                    format!("VLAN {} の名前は必須です", v)
-                } else {
-                    "VLAN名は必須です".to_string()
-                }
-            }
-            ErrorKind::InvalidVlanId { text } => {
-                format!("無効なVLAN IDです: {}", text)
-            }
-            ErrorKind::InvalidVlanNumber { text } => {
-                format!("無効なVLAN番号です: {}", text)
-            }
-            ErrorKind::InvalidVlanRange { text } => {
-                format!(
-                    "無効なVLAN範囲です（開始値は終了値以下である必要があります）: {}",
-                    text
-                )
-            }
-            ErrorKind::VlanListEmpty => "VLANリストが空です".to_string(),
-            ErrorKind::MissingDescription { interface } => {
-                format!("インターフェイス{}にはdescriptionが必要です", interface)
-            }
-            ErrorKind::InvalidBviNumber { text } => {
-                format!("無効なBVI番号です: {}", text)
-            }
-            ErrorKind::BundledInterfaceCannotConfigureVlans {
-                interface,
-                bundle_id,
-            } => {
-                format!(
-                    "インターフェイス{}はBundle {}のメンバーのため直接VLANを設定できません。代わりにBundle-Ether{}で設定してください",
-                    interface, bundle_id, bundle_id
-                )
-            }
-            ErrorKind::UnsupportedSwitchportMode { mode } => {
-                format!("switchport mode {} はサポートされていません", mode)
-            }
-            ErrorKind::AccessModeNotSupported => {
-                "switchport access はサポートされていません".to_string()
-            }
-            ErrorKind::InvalidTrunkAction { action } => {
-                format!("無効なtrunkアクションです: {}", action)
-            }
-            ErrorKind::Generic { message } => message.clone(),
+            ErrorKind::VlanNotPresent { .. } => "vlan-not-present",
+            ErrorKind::VlanNotDefinedInDatabase { .. } => "vlan-not-defined",
+            ErrorKind::VlanNameRequired { .. } => "vlan-name-required",
+            ErrorKind::InvalidVlanId { .. } => "invalid-vlan-id",
+            ErrorKind::InvalidVlanNumber { .. } => "invalid-vlan-number",
+            ErrorKind::InvalidVlanRange { .. } => "invalid-vlan-range",
+            ErrorKind::VlanListEmpty => "vlan-list-empty",
+            ErrorKind::VlanNotFound { .. } => "vlan-not-found",
+            ErrorKind::VlanRemovalConflict { .. } => "vlan-removal-conflict",
+            ErrorKind::MissingDescription { .. } => "missing-description",
+            ErrorKind::InvalidBviNumber { .. } => "invalid-bvi-number",
+            ErrorKind::BviNotPresent { .. } => "bvi-not-present",
+            ErrorKind::BviRemovalConflict { .. } => "bvi-removal-conflict",
+            ErrorKind::BviHasAddress { .. } => "bvi-has-address",
+            ErrorKind::VlansOnShutdownInterface { .. } => "vlans-on-shutdown-interface",
+            ErrorKind::InvalidInterfaceRange { .. } => "invalid-interface-range",
+            ErrorKind::BundledInterfaceCannotConfigureVlans { .. } => "bundled-interface-vlans",
+            ErrorKind::InvalidBundleId { .. } => "invalid-bundle-id",
+            ErrorKind::UnsupportedBundleMode { .. } => "unsupported-bundle-mode",
+            ErrorKind::BundleNotFound { .. } => "bundle-not-found",
+            ErrorKind::InvalidBundleMember { .. } => "invalid-bundle-member",
+            ErrorKind::NotBundleMember { .. } => "not-bundle-member",
+            ErrorKind::BundleSpeedMismatch { .. } => "bundle-speed-mismatch",
+            ErrorKind::UnsupportedSwitchportMode { .. } => "unsupported-switchport-mode",
+            ErrorKind::SwitchportModeConflict { .. } => "switchport-mode-conflict",
+            ErrorKind::AccessVlanRequired { .. } => "access-vlan-required",
+            ErrorKind::NativeVlanTagged { .. } => "native-vlan-tagged",
+            ErrorKind::QinqNotPresent { .. } => "qinq-not-present",
+            ErrorKind::SubinterfaceConflict { .. } => "subinterface-conflict",
+            ErrorKind::InvalidTrunkAction { .. } => "invalid-trunk-action",
+            ErrorKind::EncapsulationMismatch { .. } => "encapsulation-mismatch",
+            ErrorKind::MissingRewrite { .. } => "missing-rewrite",
+            ErrorKind::BviNumberMismatch { .. } => "bvi-number-mismatch",
+            ErrorKind::BviNotConfigured { .. } => "bvi-not-configured",
+            ErrorKind::SubinterfaceNumberMismatch { .. } => "subinterface-number-mismatch",
+            ErrorKind::InconsistentIndentation { .. } => "inconsistent-indentation",
+            ErrorKind::MixedIndentation => "mixed-indentation",
+            ErrorKind::Generic { .. } => "generic",
         }
     }
+
+    /// Default severity for the error kind.
+    ///
+    /// Problems in change input are errors; findings in the base
+    /// configuration are warnings since they describe existing state, as are
+    /// valid changes that deserve a second look (such as removing a BVI that
+    /// still has an address or adding VLANs to a shut down port) and
+    /// indentation problems the parser recovers from.
+    pub fn severity(&self) -> Severity {
+        match self {
+            ErrorKind::BviHasAddress { .. }
+            | ErrorKind::VlansOnShutdownInterface { .. }
+            | ErrorKind::EncapsulationMismatch { .. }
+            | ErrorKind::MissingRewrite { .. }
+            | ErrorKind::BviNumberMismatch { .. }
+            | ErrorKind::BviNotConfigured { .. }
+            | ErrorKind::SubinterfaceNumberMismatch { .. }
+            | ErrorKind::InconsistentIndentation { .. }
+            | ErrorKind::MixedIndentation => Severity::Warning,
+            _ => Severity::Error,
+        }
+    }
+
+    /// Optional suggestion on how to fix the problem, in the default locale.
+    pub fn hint(&self) -> Option<String> {
+        Japanese.hint(self)
+    }
+
+    /// Format the error message without span information, in the default locale.
+    pub fn message(&self) -> String {
+        Japanese.message(self)
+    }
 }
 
 /// A diagnostic message with optional span information
@@ -116,12 +259,22 @@ impl Diagnostic {
         }
     }
 
+    /// Severity of the diagnostic, derived from its kind.
+    pub fn severity(&self) -> Severity {
+        self.kind.severity()
+    }
+
     /// Format the diagnostic as a string with optional line number.
     /// When span is present, appends "（N行目）" to the message.
     pub fn format(&self) -> String {
-        let message = self.kind.message();
+        self.format_with(&Japanese)
+    }
+
+    /// Format the diagnostic using the given message catalog.
+    pub fn format_with(&self, catalog: &dyn MessageCatalog) -> String {
+        let message = catalog.message(&self.kind);
         match self.span {
-            Some(span) => format!("{}（{}行目）", message, span.line.get()),
+            Some(span) => catalog.at_line(&message, span.line.get()),
             None => message,
         }
     }
@@ -1,5 +1,7 @@
 //! Error types and diagnostic utilities for structured error reporting.
 
+pub mod catalog;
 pub mod diagnostic;
 
-pub use diagnostic::{Diagnostic, ErrorKind};
+pub use catalog::{English, Japanese, Locale, MessageCatalog};
+pub use diagnostic::{Diagnostic, ErrorKind, Severity};
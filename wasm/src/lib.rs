@@ -2,6 +2,7 @@ use wasm_bindgen::prelude::*;
 
 mod ast;
 mod change;
+mod drift;
 mod error;
 mod parse;
 mod regex;
@@ -9,10 +10,23 @@ mod semantics;
 mod simplified_config;
 
 use crate::parse::Node as ParsedNode;
-use change::ChangeEngine;
 
-pub use parse::tokenize;
-pub use semantics::{analyze, Config};
+pub use ast::{
+    print_cst, print_edited, print_formal, print_nodes, to_spanned, CstBlock, CstLine, CstNode,
+    CstStmt, Span, SpannedNode, SpannedNodeBlock, SpannedNodeStmt,
+};
+pub use change::{
+    diff_simplified_configs, AppliedConfig, ChangeEngine, ChangeOptions, ChangeOutput,
+};
+pub use drift::{
+    diff_configs, BlockChangeKind, BlockDiff, ConfigDiff, StatementChange, TrunkDiff, VlanRename,
+    VlanSummary,
+};
+pub use error::{Diagnostic, English, ErrorKind, Japanese, Locale, MessageCatalog, Severity};
+pub use parse::header::ConfigHeader;
+pub use parse::parser::{lower, parse_cst};
+pub use parse::{tokenize, tokenize_spanned};
+pub use semantics::{analyze, analyze_text, analyze_with_catalog, BridgeDomain, Config};
 
 /// Statement node exposed to JS/WASM callers.
 #[wasm_bindgen(getter_with_clone)]
@@ -114,6 +128,40 @@ fn convert_node_to_wasm(node: &ParsedNode) -> Node {
     }
 }
 
+/// Structured diagnostic exposed to JS/WASM callers.
+#[wasm_bindgen(getter_with_clone)]
+#[derive(Clone)]
+pub struct DiagnosticInfo {
+    /// Stable identifier of the diagnostic kind (e.g. `vlan-not-present`).
+    pub code: String,
+    /// Severity of the diagnostic: `"error"`, `"warning"` or `"info"`.
+    pub severity: String,
+    /// 1-based line number, when the diagnostic points at a source line.
+    pub line: Option<u32>,
+    /// 0-based column where the offending text starts.
+    #[wasm_bindgen(js_name = colStart)]
+    pub col_start: Option<u32>,
+    /// 0-based column where the offending text ends (exclusive).
+    #[wasm_bindgen(js_name = colEnd)]
+    pub col_end: Option<u32>,
+    /// Human-readable description of the problem.
+    pub message: String,
+    /// Optional suggestion on how to fix the problem.
+    pub hint: Option<String>,
+}
+
+fn convert_diagnostic_to_wasm(diag: &Diagnostic, catalog: &dyn MessageCatalog) -> DiagnosticInfo {
+    DiagnosticInfo {
+        code: diag.kind.code().to_string(),
+        severity: diag.severity().as_str().to_string(),
+        line: diag.span.map(|span| span.line.get()),
+        col_start: diag.span.map(|span| span.col_start),
+        col_end: diag.span.map(|span| span.col_end),
+        message: catalog.message(&diag.kind),
+        hint: catalog.hint(&diag.kind),
+    }
+}
+
 /// Return the version string for the compiled WASM bundle.
 #[wasm_bindgen]
 pub fn wasm_version() -> String {
@@ -121,20 +169,34 @@ pub fn wasm_version() -> String {
 }
 
 /// Parse and analyze a base IOS XR configuration.
+///
+/// `locale` is a language tag such as `"en"`; Japanese is used when omitted.
 #[wasm_bindgen]
-pub fn analyze_config(config_text: String) -> Result<Config, String> {
-    let nodes = tokenize(&config_text);
-    Ok(analyze(&nodes))
+pub fn analyze_config(config_text: String, locale: Option<String>) -> Result<Config, String> {
+    let catalog = Locale::from_tag_or_default(locale.as_deref()).catalog();
+    Ok(analyze_text(&config_text, catalog))
 }
 
 /// Lint a configuration and return formatted warnings or errors.
 #[wasm_bindgen]
-pub fn lint_config(config_text: String) -> Result<String, String> {
-    let nodes = tokenize(&config_text);
-    let config = analyze(&nodes);
+pub fn lint_config(config_text: String, locale: Option<String>) -> Result<String, String> {
+    let catalog = Locale::from_tag_or_default(locale.as_deref()).catalog();
+    let config = analyze_text(&config_text, catalog);
     Ok(config.lint())
 }
 
+/// Lint a configuration and return the findings as structured diagnostics.
+#[wasm_bindgen]
+pub fn lint_config_diagnostics(config_text: String, locale: Option<String>) -> Vec<DiagnosticInfo> {
+    let catalog = Locale::from_tag_or_default(locale.as_deref()).catalog();
+    let config = analyze_text(&config_text, catalog);
+    config
+        .diagnostics
+        .iter()
+        .map(|diag| convert_diagnostic_to_wasm(diag, catalog))
+        .collect()
+}
+
 /// Parse configuration text into a list of AST nodes.
 #[wasm_bindgen]
 pub fn parse_config(config_text: String) -> Result<Vec<Node>, String> {
@@ -142,22 +204,138 @@ pub fn parse_config(config_text: String) -> Result<Vec<Node>, String> {
     Ok(nodes.iter().map(convert_node_to_wasm).collect())
 }
 
+/// Render a configuration in `show running-config formal` style.
+///
+/// Both the indented and the formal format are accepted as input.
+#[wasm_bindgen]
+pub fn format_config_formal(config_text: String) -> String {
+    print_formal(&tokenize_spanned(&config_text))
+}
+
 /// Generated IOS XR CLI commands representing a requested change.
 #[wasm_bindgen(getter_with_clone)]
 #[derive(Clone)]
 pub struct GeneratedChange {
-    /// CLI change commands ready to be applied.
+    /// CLI change commands ready to be applied (empty when generation failed).
     #[wasm_bindgen(js_name = changeOutput)]
     pub change_output: String,
+    /// CLI commands that undo `changeOutput` (empty when generation failed).
+    #[wasm_bindgen(js_name = rollbackOutput)]
+    pub rollback_output: String,
+    /// Full configuration after applying the change (empty when generation failed).
+    #[wasm_bindgen(js_name = appliedConfig)]
+    pub applied_config: String,
+    /// Simplified configuration after applying the change (empty when generation failed).
+    #[wasm_bindgen(js_name = appliedSimplifiedConfig)]
+    pub applied_simplified_config: String,
+    /// Problems found while parsing, validating and planning the change
+    /// (only warnings when generation succeeded).
+    pub diagnostics: Vec<DiagnosticInfo>,
+}
+
+/// Change options for an optional default bridge group name.
+///
+/// Fails when the name cannot be used in an `l2vpn bridge group` command.
+fn change_options(bridge_group: Option<String>) -> Result<ChangeOptions, String> {
+    match bridge_group {
+        Some(group) => ChangeOptions::with_default_bridge_group(&group),
+        None => Ok(ChangeOptions::default()),
+    }
+}
+
+/// Convert a successful engine output, including its warnings.
+fn generated_change(output: ChangeOutput, catalog: &dyn MessageCatalog) -> GeneratedChange {
+    GeneratedChange {
+        change_output: output.commands,
+        rollback_output: output.rollback,
+        applied_config: output.applied.text,
+        applied_simplified_config: output.applied.simplified_config,
+        diagnostics: output
+            .warnings
+            .iter()
+            .map(|diag| convert_diagnostic_to_wasm(diag, catalog))
+            .collect(),
+    }
+}
+
+/// Build change commands and report every diagnostic instead of throwing.
+///
+/// `bridge_group` names the bridge group for bridge-domains of new VLANs
+/// (`VLAN` when omitted); only an invalid name throws.
+#[wasm_bindgen]
+pub fn generate_change_report(
+    base_config: String,
+    change_input: String,
+    locale: Option<String>,
+    bridge_group: Option<String>,
+) -> Result<GeneratedChange, String> {
+    let catalog = Locale::from_tag_or_default(locale.as_deref()).catalog();
+    let options = change_options(bridge_group)?;
+    Ok(
+        match ChangeEngine::generate_with_options(&base_config, &change_input, &options) {
+            Ok(output) => generated_change(output, catalog),
+            Err(diags) => GeneratedChange {
+                change_output: String::new(),
+                rollback_output: String::new(),
+                applied_config: String::new(),
+                applied_simplified_config: String::new(),
+                diagnostics: diags
+                    .iter()
+                    .map(|diag| convert_diagnostic_to_wasm(diag, catalog))
+                    .collect(),
+            },
+        },
+    )
 }
 
 /// Build change commands from the base config and a simplified desired diff.
+///
+/// On failure, every diagnostic is reported on its own line; on success,
+/// warnings are returned in `diagnostics`. `bridge_group` names the bridge
+/// group for bridge-domains of new VLANs (`VLAN` when omitted).
 #[wasm_bindgen]
 pub fn generate_change_config(
     base_config: String,
     change_input: String,
+    locale: Option<String>,
+    bridge_group: Option<String>,
 ) -> Result<GeneratedChange, String> {
-    let change_output =
-        ChangeEngine::generate(&base_config, &change_input).map_err(|diag| diag.format())?;
-    Ok(GeneratedChange { change_output })
+    let catalog = Locale::from_tag_or_default(locale.as_deref()).catalog();
+    let options = change_options(bridge_group)?;
+    let output = ChangeEngine::generate_with_options(&base_config, &change_input, &options)
+        .map_err(|diags| {
+            diags
+                .iter()
+                .map(|diag| diag.format_with(catalog))
+                .collect::<Vec<_>>()
+                .join("\n")
+        })?;
+    Ok(generated_change(output, catalog))
+}
+
+/// Derive change input from the original and an edited simplified config.
+///
+/// On failure, every diagnostic is reported on its own line.
+#[wasm_bindgen]
+pub fn generate_change_input(
+    original_simplified: String,
+    edited_simplified: String,
+    locale: Option<String>,
+) -> Result<String, String> {
+    let catalog = Locale::from_tag_or_default(locale.as_deref()).catalog();
+    diff_simplified_configs(&original_simplified, &edited_simplified).map_err(|diags| {
+        diags
+            .iter()
+            .map(|diag| diag.format_with(catalog))
+            .collect::<Vec<_>>()
+            .join("\n")
+    })
+}
+
+/// Semantic diff between two running-configs, rendered as text.
+///
+/// Returns an empty string when the configs are equivalent.
+#[wasm_bindgen]
+pub fn diff_running_configs(old_config: String, new_config: String) -> String {
+    diff_configs(&old_config, &new_config).render()
 }
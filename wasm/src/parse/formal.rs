@@ -0,0 +1,313 @@
+//! Reader for `show running-config formal` output.
+//!
+//! The formal format prints every statement on one line, prefixed with the
+//! headers of all enclosing blocks:
+//!
+//! ```text
+//! interface FortyGigE0/0/0/46.300 l2transport encapsulation dot1q 300
+//! l2vpn bridge group VLAN bridge-domain VLAN300 interface FortyGigE0/0/0/46.300
+//! ```
+//!
+//! Block headers are recognized from the known IOS XR sub-modes, so the
+//! reader rebuilds the same tree as the indented format.
+
+use crate::ast::{
+    to_spanned, CstLine, CstNode, Span, SpannedNode, SpannedNodeBlock, SpannedNodeStmt,
+};
+use crate::parse::opaque::Opaque;
+
+/// A whitespace-separated word of a line.
+struct Token<'a> {
+    text: &'a str,
+    /// Byte offset of the word in the line.
+    offset: usize,
+    /// Character column of the word in the line.
+    col: u32,
+}
+
+fn split_tokens(line: &str) -> Vec<Token<'_>> {
+    let mut tokens = Vec::new();
+    let mut start = None;
+    for (col, (offset, c)) in line.char_indices().enumerate() {
+        match (c.is_whitespace(), start) {
+            (false, None) => start = Some((offset, col as u32)),
+            (true, Some((begin, begin_col))) => {
+                tokens.push(Token {
+                    text: &line[begin..offset],
+                    offset: begin,
+                    col: begin_col,
+                });
+                start = None;
+            }
+            _ => {}
+        }
+    }
+    if let Some((begin, begin_col)) = start {
+        tokens.push(Token {
+            text: &line[begin..],
+            offset: begin,
+            col: begin_col,
+        });
+    }
+    tokens
+}
+
+/// Sub-mode a block header enters, used to look up the headers valid inside it.
+fn mode(header: &str) -> &str {
+    let mut words = header.split_whitespace();
+    match (words.next(), words.next()) {
+        (Some("bridge"), Some("group")) => "bridge group",
+        (Some("router"), Some("static")) => "router static",
+        (Some("router"), Some("bgp")) => "router bgp",
+        (Some("router"), Some("ospf")) => "router ospf",
+        (Some(first), _) => first,
+        (None, _) => "",
+    }
+}
+
+/// Number of words of `words` forming a block header inside `parent`.
+fn header_len(parent: Option<&str>, words: &[&str]) -> Option<usize> {
+    let len = match (parent.map_or("", mode), *words.first()?) {
+        ("", "interface") => match (words.get(1), words.get(2), words.get(3)) {
+            (Some(&"preconfigure"), _, Some(&"l2transport")) => 4,
+            (Some(&"preconfigure"), _, _) => 3,
+            (_, Some(&"l2transport"), _) => 3,
+            _ => 2,
+        },
+        ("", "l2vpn") => 1,
+        ("", "router") => match words.get(1) {
+            Some(&"static") => 2,
+            _ => 3,
+        },
+        ("", "vrf") => 2,
+        ("", "ipv4" | "ipv6") if words.get(1) == Some(&"access-list") => 3,
+        ("l2vpn", "bridge" | "xconnect") if words.get(1) == Some(&"group") => 3,
+        ("bridge group", "bridge-domain") => 2,
+        ("bridge-domain", "interface") => 2,
+        ("router static" | "router bgp" | "vrf" | "neighbor", "address-family") => 3,
+        ("router static" | "router bgp", "vrf") => 2,
+        ("router bgp", "neighbor") => 2,
+        ("router ospf", "area") => 2,
+        ("area", "interface") => 2,
+        _ => return None,
+    };
+    (words.len() >= len).then_some(len)
+}
+
+/// Whether a statement is written with the headers of its enclosing blocks.
+fn is_qualified(stmt: &str) -> bool {
+    let words: Vec<&str> = stmt.split_whitespace().collect();
+    header_len(None, &words).is_some_and(|len| len < words.len())
+}
+
+/// Whether a parsed configuration is `show running-config formal` output.
+///
+/// The formal format has no indented blocks apart from multi-line
+/// constructs such as `route-policy`, and writes some statements with the
+/// headers of their enclosing blocks.
+pub fn is_formal(nodes: &[CstNode]) -> bool {
+    let indented = nodes.iter().any(|node| match node {
+        CstNode::Block(block) => Opaque::detect(block.header.text()).is_none(),
+        _ => false,
+    });
+    !indented
+        && nodes.iter().any(|node| match node {
+            CstNode::Stmt(stmt) => is_qualified(stmt.line.text()),
+            _ => false,
+        })
+}
+
+/// Rebuild the block structure of `show running-config formal` output.
+///
+/// Spans of blocks and statements cover their words on the line that first
+/// mentions them. Multi-line blocks (such as `route-policy`) are kept as
+/// parsed.
+pub fn from_formal(nodes: &[CstNode]) -> Vec<SpannedNode> {
+    let mut res = Vec::new();
+    for node in nodes {
+        match node {
+            CstNode::Trivia(_) => {}
+            CstNode::Stmt(stmt) => insert_line(&mut res, None, &stmt.line),
+            CstNode::Block(_) => res.extend(to_spanned(std::slice::from_ref(node))),
+        }
+    }
+    collapse_empty_blocks(&mut res);
+    res
+}
+
+fn insert_line(level: &mut Vec<SpannedNode>, parent: Option<&str>, line: &CstLine) {
+    let content = line.content();
+    let tokens = split_tokens(content);
+    insert_tokens(level, parent, line.line, content, &tokens);
+}
+
+fn insert_tokens(
+    level: &mut Vec<SpannedNode>,
+    parent: Option<&str>,
+    line_no: u32,
+    content: &str,
+    tokens: &[Token],
+) {
+    let Some(first) = tokens.first() else {
+        return;
+    };
+    let words: Vec<&str> = tokens.iter().map(|t| t.text).collect();
+    let Some(len) = header_len(parent, &words) else {
+        let text = content[first.offset..].trim_end();
+        level.push(SpannedNode::Stmt(SpannedNodeStmt {
+            stmt: text.to_string(),
+            span: Span::for_line(line_no, text).shifted(first.col),
+        }));
+        return;
+    };
+
+    let last = &tokens[len - 1];
+    let header = &content[first.offset..last.offset + last.text.len()];
+    let at = match level.iter().position(|node| node_name(node) == header) {
+        Some(at) => at,
+        None => {
+            level.push(SpannedNode::Block(SpannedNodeBlock {
+                name: header.to_string(),
+                span: Span::for_line(line_no, header).shifted(first.col),
+                stmts: Vec::new(),
+            }));
+            level.len() - 1
+        }
+    };
+    if let SpannedNode::Stmt(stmt) = &level[at] {
+        level[at] = SpannedNode::Block(SpannedNodeBlock {
+            name: stmt.stmt.clone(),
+            span: stmt.span,
+            stmts: Vec::new(),
+        });
+    }
+    if let SpannedNode::Block(block) = &mut level[at] {
+        insert_tokens(
+            &mut block.stmts,
+            Some(header),
+            line_no,
+            content,
+            &tokens[len..],
+        );
+    }
+}
+
+fn node_name(node: &SpannedNode) -> &str {
+    match node {
+        SpannedNode::Stmt(stmt) => &stmt.stmt,
+        SpannedNode::Block(block) => &block.name,
+    }
+}
+
+/// Headers without statements are printed as statements in the indented
+/// format, so they are read as statements too.
+fn collapse_empty_blocks(level: &mut [SpannedNode]) {
+    for node in level.iter_mut() {
+        let SpannedNode::Block(block) = node else {
+            continue;
+        };
+        if block.stmts.is_empty() {
+            *node = SpannedNode::Stmt(SpannedNodeStmt {
+                stmt: block.name.clone(),
+                span: block.span,
+            });
+        } else {
+            collapse_empty_blocks(&mut block.stmts);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ast::print_formal;
+    use crate::parse::parser::{parse_cst, tokenize_spanned};
+
+    const FORMAL: &str = "RP/0/RP0/CPU0:sw1#show running-config formal
+hostname sw1
+interface FortyGigE0/0/0/46 description To:server1
+interface FortyGigE0/0/0/46.300 l2transport
+interface FortyGigE0/0/0/46.300 l2transport description servers,To:server1
+interface FortyGigE0/0/0/46.300 l2transport encapsulation dot1q 300
+interface FortyGigE0/0/0/46.300 l2transport rewrite ingress tag pop 1 symmetric
+l2vpn bridge group VLAN bridge-domain VLAN300 description servers
+l2vpn bridge group VLAN bridge-domain VLAN300 interface FortyGigE0/0/0/46.300
+l2vpn bridge group VLAN bridge-domain VLAN300 routed interface BVI300
+end
+";
+
+    const INDENTED: &str = "hostname sw1
+interface FortyGigE0/0/0/46
+ description To:server1
+!
+interface FortyGigE0/0/0/46.300 l2transport
+ description servers,To:server1
+ encapsulation dot1q 300
+ rewrite ingress tag pop 1 symmetric
+!
+l2vpn
+ bridge group VLAN
+  bridge-domain VLAN300
+   description servers
+   interface FortyGigE0/0/0/46.300
+   !
+   routed interface BVI300
+  !
+ !
+!
+";
+
+    #[test]
+    fn reads_the_same_tree_as_the_indented_format() {
+        let cst = parse_cst(FORMAL);
+        assert!(is_formal(&cst));
+        assert!(!is_formal(&parse_cst(INDENTED)));
+        assert_eq!(
+            print_formal(&from_formal(&cst)),
+            print_formal(&tokenize_spanned(INDENTED))
+        );
+    }
+
+    #[test]
+    fn indented_config_with_a_qualified_looking_line_is_not_formal() {
+        let indented = format!("interface preconfigure FortyGigE0/0/0/1\n{}", INDENTED);
+        assert!(!is_formal(&parse_cst(&indented)));
+        assert!(!is_formal(&parse_cst(
+            "interface FortyGigE0/0/0/1 shutdown\ninterface Foo\n description test\n"
+        )));
+        assert!(!is_formal(&parse_cst(
+            "hostname sw1\ninterface preconfigure FortyGigE0/0/0/1\n"
+        )));
+        assert!(is_formal(&parse_cst(
+            "route-policy PASS\n  pass\nend-policy\ninterface Foo description test\n"
+        )));
+    }
+
+    #[test]
+    fn keeps_original_spans() {
+        let nodes = tokenize_spanned(FORMAL);
+        let l2vpn = nodes[3].as_block().expect("l2vpn block");
+        assert_eq!(l2vpn.span, Span::new(8, 0, 5));
+        let group = l2vpn.stmts[0].as_block().expect("bridge group block");
+        assert_eq!(group.span, Span::new(8, 6, 23));
+        let domain = group.stmts[0].as_block().expect("bridge-domain block");
+        let member = domain.stmts[1].as_stmt().expect("member statement");
+        assert_eq!(member.stmt, "interface FortyGigE0/0/0/46.300");
+        assert_eq!(member.span, Span::new(9, 46, 77));
+    }
+
+    #[test]
+    fn writes_one_line_per_statement() {
+        let printed = print_formal(&tokenize_spanned(INDENTED));
+        assert_eq!(
+            printed,
+            FORMAL
+                .lines()
+                .filter(|line| !line.starts_with("RP/")
+                    && *line != "end"
+                    && *line != "interface FortyGigE0/0/0/46.300 l2transport")
+                .map(|line| format!("{}\n", line))
+                .collect::<String>()
+        );
+    }
+}
@@ -0,0 +1,122 @@
+//! Lines added around the configuration by `show running-config`.
+//!
+//! Output pasted from a device starts with the CLI prompt and command, the
+//! command timestamp, `Building configuration...` and `!!` comments, and ends
+//! with `end`. These lines are kept as trivia in the concrete syntax tree and
+//! summarized in a [`ConfigHeader`].
+
+use wasm_bindgen::prelude::*;
+
+use crate::ast::{CstLine, CstNode};
+use crate::regex;
+
+/// Metadata found in the `show running-config` output around a configuration.
+#[wasm_bindgen(getter_with_clone)]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ConfigHeader {
+    /// Hostname from the CLI prompt (e.g. `sw1` in `RP/0/RP0/CPU0:sw1#`).
+    pub hostname: Option<String>,
+    /// IOS XR version from `!! IOS XR Configuration <version>`.
+    pub version: Option<String>,
+    /// Timestamp from `!! Last configuration change at <timestamp>`.
+    #[wasm_bindgen(js_name = lastChange)]
+    pub last_change: Option<String>,
+    /// User from `!! Last configuration change at ... by <user>`.
+    #[wasm_bindgen(js_name = lastChangeBy)]
+    pub last_change_by: Option<String>,
+}
+
+impl ConfigHeader {
+    /// Collect metadata from the top-level trivia of a parsed configuration.
+    pub fn from_cst(nodes: &[CstNode]) -> Self {
+        let mut header = ConfigHeader::default();
+        for node in nodes {
+            let CstNode::Trivia(line) = node else {
+                continue;
+            };
+            let text = line.text();
+            if let Some(caps) = regex!(r"^[\w/]+:([\w.-]+)#").captures(text) {
+                header.hostname = header
+                    .hostname
+                    .or_else(|| caps.get(1).map(|m| m.as_str().to_string()));
+            } else if let Some(caps) = regex!(r"^!!\s*IOS XR Configuration\s+(\S+)").captures(text)
+            {
+                header.version = caps.get(1).map(|m| m.as_str().to_string());
+            } else if let Some(caps) =
+                regex!(r"^!!\s*Last configuration change at\s+(.+?)(?:\s+by\s+(\S+))?$")
+                    .captures(text)
+            {
+                header.last_change = caps.get(1).map(|m| m.as_str().to_string());
+                header.last_change_by = caps.get(2).map(|m| m.as_str().to_string());
+            }
+        }
+        header
+    }
+}
+
+/// Whether a line is `show running-config` output rather than configuration.
+///
+/// `end` only counts at the top level, where it terminates the output.
+pub fn is_artifact(line: &CstLine) -> bool {
+    let text = line.text();
+    text == "Building configuration..."
+        || (text == "end" && line.indent() == 0)
+        // CLI prompt, with or without the command typed after it.
+        || regex!(r"^[\w/]+:[\w.-]+#").captures(text).is_some()
+        // Timestamp printed before the command output.
+        || regex!(r"^(?:Mon|Tue|Wed|Thu|Fri|Sat|Sun)\s+[A-Z][a-z]{2}\s+\d+\s+\d+:\d{2}:\d{2}(?:\.\d+)?\s+\S+$")
+            .captures(text)
+            .is_some()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse::parser::{parse_cst, tokenize_spanned};
+
+    const SHOW_RUN: &str = "RP/0/RP0/CPU0:sw1#show running-config
+Thu Oct 17 10:00:00.123 JST
+Building configuration...
+!! IOS XR Configuration 7.3.2
+!! Last configuration change at Wed Oct 16 09:12:45 2024 by admin
+!
+hostname sw1
+interface FortyGigE0/0/0/46
+ description To:server1
+!
+end
+
+RP/0/RP0/CPU0:sw1#
+";
+
+    #[test]
+    fn extracts_metadata() {
+        let header = ConfigHeader::from_cst(&parse_cst(SHOW_RUN));
+        assert_eq!(
+            header,
+            ConfigHeader {
+                hostname: Some("sw1".to_string()),
+                version: Some("7.3.2".to_string()),
+                last_change: Some("Wed Oct 16 09:12:45 2024".to_string()),
+                last_change_by: Some("admin".to_string()),
+            }
+        );
+        assert_eq!(
+            ConfigHeader::from_cst(&parse_cst("hostname sw1\n")),
+            ConfigHeader::default()
+        );
+    }
+
+    #[test]
+    fn strips_artifacts_from_nodes() {
+        let nodes = tokenize_spanned(SHOW_RUN);
+        let names: Vec<&str> = nodes
+            .iter()
+            .map(|node| match node {
+                crate::ast::SpannedNode::Stmt(stmt) => stmt.stmt.as_str(),
+                crate::ast::SpannedNode::Block(block) => block.name.as_str(),
+            })
+            .collect();
+        assert_eq!(names, ["hostname sw1", "interface FortyGigE0/0/0/46"]);
+    }
+}
@@ -2,8 +2,13 @@
 
 use serde::{Deserialize, Serialize};
 
+pub mod formal;
+pub mod header;
+pub mod opaque;
 pub mod parser;
 
+pub use parser::tokenize_spanned;
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct NodeBlock {
     /// Header text of the block (e.g., `interface TenGigE0/0/0/0`).
@@ -70,6 +75,9 @@ fn convert_spanned(node: &crate::ast::SpannedNode) -> Node {
 }
 
 /// Tokenize input using the spanned parser and drop span information.
+///
+/// [`analyze`](crate::semantics::analyze) reports findings with their
+/// location, so it takes the nodes of [`tokenize_spanned`] instead.
 pub fn tokenize(input: &str) -> Vec<Node> {
     parser::tokenize_spanned(input)
         .iter()
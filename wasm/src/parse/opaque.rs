@@ -0,0 +1,103 @@
+//! Multi-line constructs whose body is not indented configuration.
+//!
+//! Routing policy language blocks and banners run from a header line to a
+//! terminator line, and their body may be indented arbitrarily (or not at
+//! all). The parser keeps each of them as a single block of opaque lines
+//! instead of following the indentation.
+
+use crate::ast::{SpannedNode, SpannedNodeBlock};
+
+/// A construct that runs from its header line to a terminator line.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Opaque {
+    /// `route-policy <name>` ... `end-policy`.
+    RoutePolicy,
+    /// `prefix-set <name>`, `community-set <name>`, ... `end-set`.
+    Set,
+    /// `banner <kind> <delimiter>` ... `<delimiter>`.
+    Banner { delimiter: String },
+}
+
+impl Opaque {
+    /// Recognize the header of a top-level multi-line construct.
+    ///
+    /// Banners that open and close on the same line are plain statements.
+    pub fn detect(header: &str) -> Option<Self> {
+        let mut words = header.split_whitespace();
+        match words.next()? {
+            "route-policy" if words.next().is_some() => Some(Opaque::RoutePolicy),
+            "end-set" => None,
+            word if word.ends_with("-set") && words.next().is_some() => Some(Opaque::Set),
+            "banner" => {
+                let kind = words.next()?;
+                let text = header
+                    .trim_start()
+                    .strip_prefix("banner")?
+                    .trim_start()
+                    .strip_prefix(kind)?
+                    .trim_start();
+                let delimiter = if text.starts_with("^C") {
+                    "^C".to_string()
+                } else {
+                    text.chars().next()?.to_string()
+                };
+                if text[delimiter.len()..].contains(&delimiter) {
+                    return None;
+                }
+                Some(Opaque::Banner { delimiter })
+            }
+            _ => None,
+        }
+    }
+
+    /// Whether `line` closes the construct.
+    pub fn ends_at(&self, line: &str) -> bool {
+        match self {
+            Opaque::RoutePolicy => line.trim() == "end-policy",
+            Opaque::Set => line.trim() == "end-set",
+            Opaque::Banner { delimiter } => line.contains(delimiter.as_str()),
+        }
+    }
+
+    /// Render a parsed construct: the header, then the body and the
+    /// terminator as written, including their leading whitespace.
+    pub fn render(&self, block: &SpannedNodeBlock) -> Vec<String> {
+        let mut lines = vec![block.name.clone()];
+        for node in &block.stmts {
+            lines.push(match node {
+                SpannedNode::Stmt(stmt) => stmt.stmt.clone(),
+                SpannedNode::Block(block) => block.name.clone(),
+            });
+        }
+        lines
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn detects_headers() {
+        assert_eq!(
+            Opaque::detect("route-policy PASS($tag)"),
+            Some(Opaque::RoutePolicy)
+        );
+        assert_eq!(Opaque::detect("extcommunity-set rt RT"), Some(Opaque::Set));
+        assert_eq!(Opaque::detect("end-set"), None);
+        assert_eq!(
+            Opaque::detect("banner motd ^C"),
+            Some(Opaque::Banner {
+                delimiter: "^C".to_string()
+            })
+        );
+        assert_eq!(
+            Opaque::detect("banner login # Authorized"),
+            Some(Opaque::Banner {
+                delimiter: "#".to_string()
+            })
+        );
+        assert_eq!(Opaque::detect("banner exec ;single line;"), None);
+        assert_eq!(Opaque::detect("hostname sw1"), None);
+    }
+}
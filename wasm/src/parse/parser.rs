@@ -1,7 +1,25 @@
-use crate::ast::{Span, SpannedNode, SpannedNodeBlock, SpannedNodeStmt};
+use crate::ast::cst::{to_spanned, CstBlock, CstLine, CstNode, CstStmt};
+use crate::ast::SpannedNode;
+use crate::error::{Diagnostic, ErrorKind};
+use crate::parse::formal::{from_formal, is_formal};
+use crate::parse::header::is_artifact;
+use crate::parse::opaque::Opaque;
 
-fn get_indent(line: &str) -> usize {
-    line.chars().take_while(|&c| c == ' ').count()
+/// Columns between tab stops.
+pub const TAB_WIDTH: usize = 8;
+
+/// Width of the leading whitespace of a line, with tabs advancing to the next
+/// multiple of [`TAB_WIDTH`].
+pub fn indent_width(line: &str) -> usize {
+    let mut width = 0;
+    for c in line.chars() {
+        match c {
+            ' ' => width += 1,
+            '\t' => width += TAB_WIDTH - width % TAB_WIDTH,
+            _ => break,
+        }
+    }
+    width
 }
 
 struct LinesWithLineNumbers<'a> {
@@ -22,6 +40,32 @@ impl<'a> LinesWithLineNumbers<'a> {
     fn peek(&self) -> Option<&'a str> {
         self.lines.get(self.index).copied()
     }
+
+    /// Indentation of the next line that is not blank.
+    fn next_indent(&self) -> Option<usize> {
+        self.lines[self.index..]
+            .iter()
+            .find(|line| !line.trim().is_empty())
+            .map(|line| indent_width(line))
+    }
+
+    /// Smallest indentation of the remaining configuration lines.
+    fn min_indent(&self) -> Option<usize> {
+        self.lines[self.index..]
+            .iter()
+            .map(|raw| CstLine {
+                line: 0,
+                raw: raw.to_string(),
+            })
+            .filter(|line| !line.is_trivia() && !is_artifact(line))
+            .map(|line| line.indent())
+            .min()
+    }
+
+    /// Number of lines before the next one matching `pred`.
+    fn offset_of(&self, pred: impl Fn(&str) -> bool) -> Option<usize> {
+        self.lines[self.index..].iter().position(|line| pred(line))
+    }
 }
 
 impl<'a> Iterator for LinesWithLineNumbers<'a> {
@@ -40,61 +84,189 @@ impl<'a> Iterator for LinesWithLineNumbers<'a> {
 }
 
 /// Tokenizes the input string into a tree of SpannedNodes with span information
+///
+/// Both the indented and the `show running-config formal` format are accepted.
 pub fn tokenize_spanned(s: &str) -> Vec<SpannedNode> {
-    let lines_vec: Vec<&str> = s.lines().collect();
+    lower(&parse_cst(s))
+}
+
+/// Convert a concrete syntax tree to SpannedNodes, rebuilding the block
+/// structure of `show running-config formal` output.
+pub fn lower(nodes: &[CstNode]) -> Vec<SpannedNode> {
+    if is_formal(nodes) {
+        from_formal(nodes)
+    } else {
+        to_spanned(nodes)
+    }
+}
+
+/// Parses the input string into a lossless concrete syntax tree.
+///
+/// See [`parse_cst_with_diagnostics`]; indentation problems are recovered
+/// from silently.
+pub fn parse_cst(s: &str) -> Vec<CstNode> {
+    parse_cst_with_diagnostics(s, &mut Vec::new())
+}
+
+/// Parses the input string into a lossless concrete syntax tree, reporting
+/// indentation problems to `diags`.
+///
+/// A line opens a block when the next non-blank line is indented deeper, and
+/// the block ends at the first line indented no deeper than its header. Tabs
+/// advance to the next multiple of [`TAB_WIDTH`] columns. A line whose
+/// indentation matches no open block level is read as part of the innermost
+/// block it is deeper than, with an
+/// [`InconsistentIndentation`](ErrorKind::InconsistentIndentation) warning.
+///
+/// `!` lines, comments, blank lines and `show running-config` artifacts (see
+/// [`is_artifact`]) are kept as trivia or closers, so
+/// [`print_cst`](crate::ast::print_cst) reproduces the input. Top-level route
+/// policies, sets and banners (see [`Opaque`]) become one block holding every
+/// line up to their terminator, whatever the indentation of their body.
+pub fn parse_cst_with_diagnostics(s: &str, diags: &mut Vec<Diagnostic>) -> Vec<CstNode> {
+    let lines_vec: Vec<&str> = s.split_inclusive('\n').collect();
     let mut lines = LinesWithLineNumbers::new(&lines_vec);
     let mut elements = Vec::new();
-    tokenize_impl(&mut lines, &mut elements);
+    parse_impl(&mut lines, &mut elements, None, diags);
     elements
 }
 
-fn tokenize_impl(lines: &mut LinesWithLineNumbers, res: &mut Vec<SpannedNode>) {
-    while let Some((l, line_no)) = lines.next() {
-        let this_indent = get_indent(l);
-        let line = l.trim();
+/// Parse the lines of the block whose header is indented by `parent`
+/// columns (`None` for the top level).
+fn parse_impl(
+    lines: &mut LinesWithLineNumbers,
+    res: &mut Vec<CstNode>,
+    parent: Option<usize>,
+    diags: &mut Vec<Diagnostic>,
+) {
+    // Indentation of the first statement of the block. Top-level statements
+    // are expected at the smallest indentation, so a stray indent on the
+    // first line is reported on that line only.
+    let mut level = match parent {
+        Some(_) => None,
+        None => lines.min_indent(),
+    };
 
-        let peek = lines.peek().unwrap_or("");
-        let next_indent = get_indent(peek);
-        let peek_trimmed = peek.trim();
+    while let Some(peek) = lines.peek() {
+        // Blank lines go with the next line that is not blank.
+        let indent = if peek.trim().is_empty() {
+            lines.next_indent()
+        } else {
+            Some(indent_width(peek))
+        };
+        if let (Some(parent), Some(indent)) = (parent, indent) {
+            if indent <= parent {
+                return;
+            }
+        }
 
-        // this is beginning of block
-        if next_indent > this_indent {
-            let mut buf = Vec::new();
-            tokenize_impl(lines, &mut buf);
-            res.push(SpannedNode::Block(SpannedNodeBlock {
-                name: line.to_string(),
-                span: Span::line_only(line_no),
-                stmts: buf,
-            }));
+        let Some((raw, line_no)) = lines.next() else {
+            return;
+        };
+        let line = CstLine {
+            line: line_no,
+            raw: raw.to_string(),
+        };
+        if line.is_trivia() || is_artifact(&line) {
+            push_trivia(res, line);
             continue;
         }
 
-        if !line.starts_with('!') && !line.is_empty() {
-            res.push(SpannedNode::Stmt(SpannedNodeStmt {
-                stmt: line.to_string(),
-                span: Span::line_only(line_no),
+        let this_indent = line.indent();
+        check_indent(&line, *level.get_or_insert(this_indent), diags);
+
+        if parent.is_none() {
+            if let Some(block) = parse_opaque(lines, &line) {
+                res.push(CstNode::Block(block));
+                continue;
+            }
+        }
+
+        // this is beginning of block
+        if lines.next_indent().is_some_and(|next| next > this_indent) {
+            let mut children = Vec::new();
+            parse_impl(lines, &mut children, Some(this_indent), diags);
+            res.push(CstNode::Block(CstBlock {
+                header: line,
+                children,
+                closer: None,
             }));
+        } else {
+            res.push(CstNode::Stmt(CstStmt { line, closer: None }));
         }
+    }
+}
 
-        // next line is other block
-        if next_indent < this_indent {
-            // wtf
-            if peek_trimmed == "end-set" || peek_trimmed == "end-policy" {
-                if let Some((next_line, next_line_no)) = lines.next() {
-                    res.push(SpannedNode::Stmt(SpannedNodeStmt {
-                        stmt: next_line.trim().to_string(),
-                        span: Span::line_only(next_line_no),
-                    }));
-                }
+/// Report indentation that mixes tabs and spaces or differs from the other
+/// statements of the block.
+fn check_indent(line: &CstLine, expected: usize, diags: &mut Vec<Diagnostic>) {
+    let content = line.content();
+    let leading = &content[..content.len() - content.trim_start().len()];
+    if leading.contains('\t') && leading.contains(' ') {
+        diags.push(Diagnostic::with_span(
+            ErrorKind::MixedIndentation,
+            line.span(),
+        ));
+    }
+    let found = line.indent();
+    if found != expected {
+        diags.push(Diagnostic::with_span(
+            ErrorKind::InconsistentIndentation { found, expected },
+            line.span(),
+        ));
+    }
+}
+
+/// Consume a multi-line construct starting at `header` up to its terminator.
+///
+/// Returns `None`, consuming nothing, when `header` does not open such a
+/// construct or its terminator is missing.
+fn parse_opaque(lines: &mut LinesWithLineNumbers, header: &CstLine) -> Option<CstBlock> {
+    let opaque = Opaque::detect(header.text())?;
+    let len = lines.offset_of(|line| opaque.ends_at(line))?;
+    let children = lines
+        .take(len + 1)
+        .map(|(raw, line_no)| {
+            let line = CstLine {
+                line: line_no,
+                raw: raw.to_string(),
+            };
+            if line.text().is_empty() {
+                CstNode::Trivia(line)
+            } else {
+                CstNode::Stmt(CstStmt { line, closer: None })
+            }
+        })
+        .collect();
+    Some(CstBlock {
+        header: header.clone(),
+        children,
+        closer: None,
+    })
+}
+
+/// Attach a `!` line to the node it closes, or keep it as trivia.
+fn push_trivia(res: &mut Vec<CstNode>, line: CstLine) {
+    if line.is_closer() {
+        let closer = match res.last_mut() {
+            Some(CstNode::Stmt(stmt)) if stmt.line.indent() == line.indent() => &mut stmt.closer,
+            Some(CstNode::Block(block)) if block.header.indent() == line.indent() => {
+                &mut block.closer
             }
+            _ => return res.push(CstNode::Trivia(line)),
+        };
+        if closer.is_none() {
+            *closer = Some(line);
             return;
         }
     }
+    res.push(CstNode::Trivia(line));
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::ast::{print_cst, Span};
 
     #[test]
     fn test_tokenize_spanned_basic() {
@@ -112,7 +284,7 @@ mod tests {
 
             if let Some(stmt) = block.stmts[0].as_stmt() {
                 assert_eq!(stmt.stmt, "description test");
-                assert_eq!(stmt.span.line.get(), 2);
+                assert_eq!(stmt.span, Span::new(2, 2, 18));
             }
 
             if let Some(stmt) = block.stmts[1].as_stmt() {
@@ -145,4 +317,184 @@ interface Foo
             assert_eq!(block.span.line.get(), 4);
         }
     }
+
+    #[test]
+    fn test_parse_cst_round_trip() {
+        let inputs = [
+            "",
+            "hostname demo",
+            "interface Foo\n  description test  \n\n! comment\ninterface Bar\n\tmtu 9000\n",
+            "interface Foo\r\n description test\r\n!\r\nend\r\n",
+            "  indented\nfirst\n   deeper\n  shallower\nlast",
+            "route-policy PASS\n  pass\nend-policy\n!\n",
+        ];
+        for input in inputs {
+            assert_eq!(print_cst(&parse_cst(input)), input);
+        }
+    }
+
+    #[test]
+    fn test_parse_cst_attaches_closers() {
+        let input = "interface Foo\n description test\n!\n!! comment\nhostname demo\n";
+        let nodes = parse_cst(input);
+        assert_eq!(nodes.len(), 3);
+
+        if let CstNode::Block(block) = &nodes[0] {
+            assert_eq!(block.header.text(), "interface Foo");
+            assert_eq!(block.closer.as_ref().map(|l| l.line), Some(3));
+        } else {
+            panic!("Expected block");
+        }
+        assert!(matches!(&nodes[1], CstNode::Trivia(line) if line.text() == "!! comment"));
+        // Trivia and closers are dropped when lowering.
+        let spanned = to_spanned(&nodes);
+        assert_eq!(spanned.len(), 2);
+        assert_eq!(spanned[1].as_stmt().map(|s| s.span.line.get()), Some(5));
+    }
+
+    #[test]
+    fn test_tokenize_spanned_opaque_constructs() {
+        let input = r#"banner motd ^C
+  Authorized access only
+!
+      Disconnect now
+^C
+prefix-set SERVERS
+  192.0.2.0/24,
+    198.51.100.0/24
+end-set
+!
+route-policy PASS
+  if destination in SERVERS then
+    pass
+  endif
+end-policy
+!
+interface Foo
+  description test
+"#;
+        let nodes = tokenize_spanned(input);
+        assert_eq!(nodes.len(), 4);
+
+        let banner = nodes[0].as_block().expect("banner block");
+        assert_eq!(banner.name, "banner motd ^C");
+        let lines: Vec<&str> = banner
+            .stmts
+            .iter()
+            .filter_map(|n| n.as_stmt().map(|s| s.stmt.as_str()))
+            .collect();
+        assert_eq!(
+            lines,
+            [
+                "  Authorized access only",
+                "!",
+                "      Disconnect now",
+                "^C"
+            ]
+        );
+        assert_eq!(banner.stmts[2].as_stmt().unwrap().span, Span::new(4, 6, 20));
+
+        let set = nodes[1].as_block().expect("prefix-set block");
+        assert_eq!(set.stmts.len(), 3);
+        assert!(set.stmts.iter().all(|n| n.as_stmt().is_some()));
+
+        let policy = nodes[2].as_block().expect("route-policy block");
+        assert_eq!(policy.stmts.len(), 4);
+        assert_eq!(policy.stmts[3].as_stmt().unwrap().stmt, "end-policy");
+
+        let interface = nodes[3].as_block().expect("interface block");
+        assert_eq!(interface.name, "interface Foo");
+        assert_eq!(interface.span.line.get(), 17);
+        assert_eq!(print_cst(&parse_cst(input)), input);
+    }
+
+    #[test]
+    fn test_tokenize_spanned_unterminated_opaque_construct() {
+        let input = "route-policy BROKEN\n  pass\ninterface Foo\n  description test\n";
+        let nodes = tokenize_spanned(input);
+        assert_eq!(nodes.len(), 2);
+        assert_eq!(
+            nodes[1].as_block().map(|b| b.name.as_str()),
+            Some("interface Foo")
+        );
+    }
+
+    /// Outline of a tree such as `a { b; c }; d`.
+    fn outline(nodes: &[SpannedNode]) -> String {
+        nodes
+            .iter()
+            .map(|node| match node {
+                SpannedNode::Stmt(stmt) => stmt.stmt.clone(),
+                SpannedNode::Block(block) => {
+                    format!("{} {{ {} }}", block.name, outline(&block.stmts))
+                }
+            })
+            .collect::<Vec<_>>()
+            .join("; ")
+    }
+
+    #[test]
+    fn test_parse_cst_closes_every_block_on_dedent() {
+        let input = "l2vpn\n  bridge group VLAN\n    bridge-domain VLAN300\n\n      interface Foo.300\ninterface Foo\n  mtu 9000\n";
+        let mut diags = Vec::new();
+        let nodes = lower(&parse_cst_with_diagnostics(input, &mut diags));
+        assert!(diags.is_empty(), "{:?}", diags);
+        assert_eq!(
+            outline(&nodes),
+            "l2vpn { bridge group VLAN { bridge-domain VLAN300 { interface Foo.300 } } }; interface Foo { mtu 9000 }"
+        );
+    }
+
+    #[test]
+    fn test_parse_cst_expands_tabs() {
+        let spaces = "interface Foo\n        description test\n        mtu 9000\n";
+        let tabs = "interface Foo\n\tdescription test\n    \tmtu 9000\n";
+        let mut diags = Vec::new();
+        let nodes = lower(&parse_cst_with_diagnostics(tabs, &mut diags));
+        assert_eq!(outline(&nodes), outline(&tokenize_spanned(spaces)));
+        assert_eq!(diags.len(), 1);
+        assert!(matches!(diags[0].kind, ErrorKind::MixedIndentation));
+        assert_eq!(diags[0].span, Some(Span::new(3, 5, 13)));
+        assert_eq!(print_cst(&parse_cst(tabs)), tabs);
+    }
+
+    #[test]
+    fn test_parse_cst_recovers_from_inconsistent_dedent() {
+        let input = "interface Foo\n    description test\n  mtu 9000\n!\ninterface Bar\n";
+        let mut diags = Vec::new();
+        let nodes = lower(&parse_cst_with_diagnostics(input, &mut diags));
+        assert_eq!(
+            outline(&nodes),
+            "interface Foo { description test; mtu 9000 }; interface Bar"
+        );
+        assert_eq!(diags.len(), 1);
+        assert!(matches!(
+            diags[0].kind,
+            ErrorKind::InconsistentIndentation {
+                found: 2,
+                expected: 4
+            }
+        ));
+        assert_eq!(diags[0].span, Some(Span::new(3, 2, 10)));
+    }
+
+    #[test]
+    fn test_parse_cst_reports_stray_top_level_indent_once() {
+        let input = " hostname sw1\ninterface Foo\n description test\n!\ninterface Bar\n";
+        let mut diags = Vec::new();
+        let nodes = lower(&parse_cst_with_diagnostics(input, &mut diags));
+        assert_eq!(
+            outline(&nodes),
+            "hostname sw1; interface Foo { description test }; interface Bar"
+        );
+        assert_eq!(diags.len(), 1);
+        assert!(matches!(
+            diags[0].kind,
+            ErrorKind::InconsistentIndentation {
+                found: 1,
+                expected: 0
+            }
+        ));
+        assert_eq!(diags[0].span.unwrap().line.get(), 1);
+    }
 }
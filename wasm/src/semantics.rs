@@ -1,7 +1,10 @@
-use crate::parse::{Node, NodeBlock, NodeStmt};
+use crate::ast::{Span, SpannedNode, SpannedNodeBlock, SpannedNodeStmt};
+use crate::error::{Diagnostic, ErrorKind, Japanese, MessageCatalog};
+use crate::parse::header::ConfigHeader;
+use crate::parse::parser::{lower, parse_cst_with_diagnostics};
 use crate::regex;
 use crate::simplified_config::{build_simplified_config, SimplifiedConfigData};
-use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
 use wasm_bindgen::prelude::*;
 
 /// Split an interface name into base and optional subinterface number (e.g., "Gig0/0/0/0.100").
@@ -24,6 +27,83 @@ pub(crate) fn parse_interface_name(name: &str) -> Option<(String, String)> {
     Some((speed_type, port_number))
 }
 
+/// Encapsulation of an l2transport subinterface.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Encapsulation {
+    /// Single tag (`encapsulation dot1q <tag>`).
+    Dot1q(u32),
+    /// Q-in-Q double tag (`encapsulation dot1q <outer> second-dot1q <inner>`).
+    QinQ { outer: u32, inner: u32 },
+    /// Untagged frames (`encapsulation untagged`), i.e. the native VLAN.
+    Untagged,
+}
+
+impl Encapsulation {
+    /// Parse an `encapsulation ...` statement.
+    pub fn parse(stmt: &str) -> Option<Self> {
+        let stmt = stmt.trim();
+        if stmt == "encapsulation untagged" {
+            return Some(Encapsulation::Untagged);
+        }
+        let caps = regex!(r"^encapsulation dot1q (\d+)(?: second-dot1q (\d+))?$").captures(stmt)?;
+        let outer = caps.get(1)?.as_str().parse::<u32>().ok()?;
+        match caps.get(2) {
+            Some(inner) => Some(Encapsulation::QinQ {
+                outer,
+                inner: inner.as_str().parse::<u32>().ok()?,
+            }),
+            None => Some(Encapsulation::Dot1q(outer)),
+        }
+    }
+
+    /// Find the encapsulation configured in a list of subinterface statements.
+    pub fn find<'a>(stmts: impl IntoIterator<Item = &'a str>) -> Option<Self> {
+        stmts.into_iter().find_map(Self::parse)
+    }
+
+    /// Tag of the VLAN carried by the subinterface (the inner tag for Q-in-Q).
+    pub fn service_tag(self) -> Option<u32> {
+        match self {
+            Encapsulation::Dot1q(tag) => Some(tag),
+            Encapsulation::QinQ { inner, .. } => Some(inner),
+            Encapsulation::Untagged => None,
+        }
+    }
+
+    /// Number of tags popped by the symmetric ingress rewrite.
+    pub fn pop_count(self) -> u32 {
+        match self {
+            Encapsulation::Dot1q(_) => 1,
+            Encapsulation::QinQ { .. } => 2,
+            Encapsulation::Untagged => 0,
+        }
+    }
+
+    /// Statements configuring the encapsulation and its rewrite.
+    pub fn statements(self) -> Vec<String> {
+        let mut stmts = vec![format!("encapsulation {}", self)];
+        if self.pop_count() > 0 {
+            stmts.push(format!(
+                "rewrite ingress tag pop {} symmetric",
+                self.pop_count()
+            ));
+        }
+        stmts
+    }
+}
+
+impl std::fmt::Display for Encapsulation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Encapsulation::Dot1q(tag) => write!(f, "dot1q {}", tag),
+            Encapsulation::QinQ { outer, inner } => {
+                write!(f, "dot1q {} second-dot1q {}", outer, inner)
+            }
+            Encapsulation::Untagged => f.write_str("untagged"),
+        }
+    }
+}
+
 fn find_bundle_id(stmts: &[String]) -> Option<u32> {
     stmts.iter().find_map(|stmt| {
         regex!(r"^bundle id (\d+)")
@@ -40,15 +120,20 @@ pub struct L2TransportConfig {
     pub baseif: String,
     /// Subinterface number parsed from the name.
     pub sub_if_num: u32,
-    /// Encapsulation dot1q tag when present.
-    pub encap: Option<u32>,
-    /// Whether `rewrite ingress tag pop 1 symmetric` is configured.
+    /// Encapsulation when present.
+    pub encap: Option<Encapsulation>,
+    /// Whether the rewrite matching the encapsulation (`rewrite ingress tag pop <n> symmetric`)
+    /// is configured.
     pub has_rewrite: bool,
+    /// Source span of the `interface ... l2transport` header.
+    pub span: Span,
+    /// Source span of the `encapsulation` statement when present.
+    pub encap_span: Option<Span>,
 }
 
 impl L2TransportConfig {
     /// Attempt to parse an `interface <base>.<sub> l2transport` block into a config struct.
-    pub fn try_new(node: &Node) -> Option<Self> {
+    pub fn try_new(node: &SpannedNode) -> Option<Self> {
         let node_block = node.as_block()?;
 
         let caps = regex!(r"^interface ([^.]+)\.(\d+) l2transport$").captures(&node_block.name)?;
@@ -56,38 +141,68 @@ impl L2TransportConfig {
         let baseif = caps.get(1)?.as_str().to_string();
         let sub_if_num = caps.get(2)?.as_str().parse::<u32>().ok()?;
 
+        let encap = Self::find_encap(node_block);
+        let rewrite = format!(
+            "rewrite ingress tag pop {} symmetric",
+            encap.map_or(1, |(encap, _)| encap.pop_count())
+        );
         let has_rewrite = node_block
             .stmts()
             .filter_map(|x| x.as_stmt())
-            .any(|stmt: &NodeStmt| stmt.stmt() == "rewrite ingress tag pop 1 symmetric");
+            .any(|stmt: &SpannedNodeStmt| stmt.stmt() == rewrite);
 
         Some(L2TransportConfig {
             baseif,
             sub_if_num,
-            encap: Self::find_encap(node_block),
+            encap: encap.map(|(encap, _)| encap),
             has_rewrite,
+            span: node_block.span,
+            encap_span: encap.map(|(_, span)| span),
         })
     }
 
-    /// Find the encapsulation VLAN tag within a subinterface block.
-    fn find_encap(n: &NodeBlock) -> Option<u32> {
-        n.stmts().filter_map(|x| x.as_stmt()).find_map(|stmt| {
-            let caps = regex!(r"^encapsulation dot1q (\d+)$").captures(stmt.stmt())?;
-            let m = caps.get(1)?;
-            m.as_str().parse::<u32>().ok()
-        })
+    /// Find the encapsulation and its span within a subinterface block.
+    fn find_encap(n: &SpannedNodeBlock) -> Option<(Encapsulation, Span)> {
+        n.stmts()
+            .filter_map(|x| x.as_stmt())
+            .find_map(|stmt| Some((Encapsulation::parse(stmt.stmt())?, stmt.span)))
+    }
+
+    /// Full subinterface name (e.g., `FortyGigE0/0/0/1.300`).
+    pub fn name(&self) -> String {
+        format!("{}.{}", self.baseif, self.sub_if_num)
     }
 
     /// Validate encapsulation and rewrite statements for the subinterface.
-    pub fn lint(&self) -> Vec<String> {
+    ///
+    /// Untagged subinterfaces have no tag to match or pop and are not checked.
+    /// A Q-in-Q service is identified by its outer and inner tag, so its
+    /// subinterface number is not checked, but both tags must be popped.
+    pub fn lint(&self) -> Vec<Diagnostic> {
         let mut ret = Vec::new();
+        if self.encap == Some(Encapsulation::Untagged) {
+            return ret;
+        }
 
-        if Some(self.sub_if_num) != self.encap {
-            ret.push("sub-interface number と encapsulation tag が一致していない".to_string());
+        let numbered = matches!(self.encap, Some(Encapsulation::QinQ { .. }))
+            || Some(self.sub_if_num) == self.encap.and_then(Encapsulation::service_tag);
+        if !numbered {
+            ret.push(Diagnostic::with_span(
+                ErrorKind::EncapsulationMismatch {
+                    interface: self.name(),
+                },
+                self.encap_span.unwrap_or(self.span),
+            ));
         }
 
         if !self.has_rewrite {
-            ret.push("rewrite ingress tag pop 1 symmetric が存在しない".to_string());
+            ret.push(Diagnostic::with_span(
+                ErrorKind::MissingRewrite {
+                    interface: self.name(),
+                    pop: self.encap.map_or(1, Encapsulation::pop_count),
+                },
+                self.span,
+            ));
         }
 
         ret
@@ -98,100 +213,243 @@ impl L2TransportConfig {
 #[wasm_bindgen(getter_with_clone)]
 #[derive(Debug, Clone)]
 pub struct BridgeDomain {
-    /// VLAN tag extracted from the bridge-domain name.
+    /// VLAN tag extracted from the bridge-domain name, or from the member
+    /// encapsulations when the name does not encode it.
     #[wasm_bindgen(js_name = vlanTag)]
     pub vlan_tag: u32,
+    /// Name of the enclosing bridge group (e.g. `VLAN` for `bridge group VLAN`).
+    #[wasm_bindgen(js_name = bridgeGroup)]
+    pub group: String,
+    /// Bridge-domain name (e.g. `VLAN300`).
+    pub name: String,
     /// Interfaces assigned to this bridge-domain (including BVIs and subinterfaces).
     pub interfaces: Vec<String>,
+    /// BVI attached with `routed interface BVI<n>`, if any.
+    #[wasm_bindgen(js_name = routedInterface)]
+    pub routed_interface: Option<String>,
     /// Optional description configured on the bridge-domain.
     description: Option<String>,
+    /// Source span of the bridge-domain header.
+    span: Span,
+    /// Source spans of the member statements, parallel to `interfaces`.
+    interface_spans: Vec<Span>,
+    /// Source span of the `routed interface` statement.
+    routed_span: Option<Span>,
+    /// Member subinterfaces carrying a Q-in-Q service.
+    qinq_members: BTreeSet<String>,
 }
 
 impl BridgeDomain {
-    /// Attempt to parse a `bridge-domain VLAN<id>` block into a `BridgeDomain`.
-    pub fn try_new(block: &Node) -> Option<Self> {
+    /// Attempt to parse a `bridge-domain <name>` block of bridge group `group`
+    /// into a `BridgeDomain`.
+    ///
+    /// The VLAN is taken from a `VLAN<id>` name; other domains are mapped
+    /// through the encapsulation (or number) of their first tagged member
+    /// subinterface, then the number of their BVI. Domains that cannot be
+    /// mapped to a VLAN are skipped.
+    pub fn try_new(
+        group: &str,
+        block: &SpannedNode,
+        l2transport: &HashMap<String, Vec<L2TransportConfig>>,
+    ) -> Option<Self> {
         let node_block = block.as_block()?;
+        let name = node_block.name.strip_prefix("bridge-domain ")?.trim();
 
-        let vlan_tag = regex!(r"^bridge-domain VLAN(\d+)$")
-            .captures(&node_block.name)?
-            .get(1)?
-            .as_str()
-            .parse::<u32>()
-            .ok()?;
-
-        let interfaces = node_block
+        let (interfaces, interface_spans): (Vec<String>, Vec<Span>) = node_block
             .stmts()
             .filter_map(|x| x.as_stmt())
-            .filter_map(|stmt: &NodeStmt| {
+            .filter_map(|stmt: &SpannedNodeStmt| {
                 let caps = regex!(r"^interface (\S+)$").captures(stmt.stmt())?;
-                Some(caps.get(1)?.as_str().to_string())
+                Some((caps.get(1)?.as_str().to_string(), stmt.span))
             })
-            .collect();
+            .unzip();
         let description = Self::find_description(node_block);
+        let routed =
+            node_block
+                .stmts()
+                .filter_map(|x| x.as_stmt())
+                .find_map(|stmt: &SpannedNodeStmt| {
+                    let caps = regex!(r"^routed interface (\S+)$").captures(stmt.stmt())?;
+                    Some((caps.get(1)?.as_str().to_string(), stmt.span))
+                });
+        let routed_interface = routed.as_ref().map(|(name, _)| name.clone());
+
+        let vlan_tag = regex!(r"^VLAN(\d+)$")
+            .captures(name)
+            .and_then(|caps| caps.get(1)?.as_str().parse::<u32>().ok())
+            .or_else(|| {
+                interfaces
+                    .iter()
+                    .find_map(|iface| member_service_tag(iface, l2transport))
+            })
+            .or_else(|| {
+                interfaces
+                    .iter()
+                    .chain(&routed_interface)
+                    .find_map(|iface| iface.strip_prefix("BVI")?.parse::<u32>().ok())
+            })?;
+
+        let qinq_members = interfaces
+            .iter()
+            .filter(|iface| {
+                matches!(
+                    member_encapsulation(iface, l2transport),
+                    Some(Encapsulation::QinQ { .. })
+                )
+            })
+            .cloned()
+            .collect();
 
         Some(BridgeDomain {
             vlan_tag,
+            group: group.to_string(),
+            name: name.to_string(),
             interfaces,
             description,
+            span: node_block.span,
+            interface_spans,
+            routed_interface,
+            routed_span: routed.map(|(_, span)| span),
+            qinq_members,
         })
     }
 
     /// Extract the bridge-domain description if present.
-    fn find_description(node_block: &NodeBlock) -> Option<String> {
+    fn find_description(node_block: &SpannedNodeBlock) -> Option<String> {
         node_block
             .stmts()
             .filter_map(|x| x.as_stmt())
-            .find_map(|stmt: &NodeStmt| stmt.stmt().strip_prefix("description "))
+            .find_map(|stmt: &SpannedNodeStmt| stmt.stmt().strip_prefix("description "))
             .map(|desc| desc.trim().to_string())
     }
 
+    /// Source span of the bridge-domain header.
+    pub fn span(&self) -> Span {
+        self.span
+    }
+
     /// Borrow the description text, if configured.
     pub fn description(&self) -> Option<&str> {
         self.description.as_deref()
     }
 
+    /// VLAN number of the routed BVI, if one is attached and correctly named.
+    pub fn routed_bvi(&self) -> Option<u32> {
+        self.routed_interface
+            .as_deref()?
+            .strip_prefix("BVI")?
+            .parse::<u32>()
+            .ok()
+    }
+
     /// Validate BVI and subinterface numbering relative to the bridge-domain VLAN ID.
-    pub fn lint(&self) -> Vec<String> {
+    ///
+    /// `interfaces` holds the names of the interfaces configured in the
+    /// config; the routed BVI must be one of them. Q-in-Q members may be
+    /// numbered freely, and so may the members of a domain whose name does
+    /// not encode its VLAN (their encapsulation is checked instead).
+    pub fn lint(&self, interfaces: &HashSet<String>) -> Vec<Diagnostic> {
+        let routed = self
+            .routed_interface
+            .iter()
+            .zip(&self.routed_span)
+            .filter(|(bvi, _)| !interfaces.contains(*bvi))
+            .map(|(bvi, span)| {
+                Diagnostic::with_span(
+                    ErrorKind::BviNotConfigured {
+                        interface: bvi.clone(),
+                    },
+                    *span,
+                )
+            });
+
+        let named_after_vlan = regex!(r"^VLAN(\d+)$").captures(&self.name).is_some();
         self.interfaces
             .iter()
-            .filter_map(|int| {
+            .zip(&self.interface_spans)
+            .chain(self.routed_interface.iter().zip(&self.routed_span))
+            .filter_map(|(int, span)| {
                 if let Some(bvi_suffix) = int.strip_prefix("BVI") {
                     let Ok(bvi_num) = bvi_suffix.parse::<u32>() else {
                         return None;
                     };
 
                     if self.vlan_tag != bvi_num {
-                        return Some(format!("BVI number がブリッジ名と異なる: {}", int));
+                        return Some(Diagnostic::with_span(
+                            ErrorKind::BviNumberMismatch {
+                                interface: int.clone(),
+                            },
+                            *span,
+                        ));
                     }
                 } else if let Ok((_, sub)) = split_subinterface_id(int) {
-                    if Some(self.vlan_tag) != sub {
-                        return Some(format!(
-                            "sub-interface number がブリッジ名と異なる: {}",
-                            int
+                    if named_after_vlan
+                        && Some(self.vlan_tag) != sub
+                        && !self.qinq_members.contains(int)
+                    {
+                        return Some(Diagnostic::with_span(
+                            ErrorKind::SubinterfaceNumberMismatch {
+                                interface: int.clone(),
+                            },
+                            *span,
                         ));
                     }
                 }
 
                 None
             })
+            .chain(routed)
             .collect()
     }
 }
 
-fn get_bridge_domains(config: &[Node]) -> Option<Vec<BridgeDomain>> {
+/// Tag carried by a tagged member subinterface: its encapsulation's service
+/// tag, or its subinterface number when no encapsulation is configured.
+fn member_service_tag(
+    iface: &str,
+    l2transport: &HashMap<String, Vec<L2TransportConfig>>,
+) -> Option<u32> {
+    let (_, sub) = split_subinterface_id(iface).ok()?;
+    match member_encapsulation(iface, l2transport) {
+        Some(encap) => encap.service_tag(),
+        None => sub,
+    }
+}
+
+/// Encapsulation configured on a member subinterface, if any.
+fn member_encapsulation(
+    iface: &str,
+    l2transport: &HashMap<String, Vec<L2TransportConfig>>,
+) -> Option<Encapsulation> {
+    let (baseif, sub) = split_subinterface_id(iface).ok()?;
+    let sub = sub?;
+    l2transport
+        .get(&baseif)
+        .and_then(|subifs| subifs.iter().find(|t| t.sub_if_num == sub))
+        .and_then(|t| t.encap)
+}
+
+fn get_bridge_domains(
+    config: &[SpannedNode],
+    l2transport: &HashMap<String, Vec<L2TransportConfig>>,
+) -> Option<Vec<BridgeDomain>> {
     let res = config
         .iter()
         .find_map(|x| x.as_block().filter(|x| x.name == "l2vpn"))?
         .stmts()
-        .find_map(|x| x.as_block().filter(|x| x.name == "bridge group VLAN"))?
-        .stmts()
-        .filter_map(BridgeDomain::try_new)
+        .filter_map(|x| x.as_block())
+        .filter_map(|group| Some((group.name.strip_prefix("bridge group ")?.trim(), group)))
+        .flat_map(|(name, group)| {
+            group
+                .stmts()
+                .filter_map(move |domain| BridgeDomain::try_new(name, domain, l2transport))
+        })
         .collect();
 
     Some(res)
 }
 
-fn get_l2_transports(config: &[Node]) -> HashMap<String, Vec<L2TransportConfig>> {
+fn get_l2_transports(config: &[SpannedNode]) -> HashMap<String, Vec<L2TransportConfig>> {
     let mut grouped: HashMap<String, Vec<L2TransportConfig>> = HashMap::new();
 
     for interface in config.iter().filter_map(L2TransportConfig::try_new) {
@@ -204,17 +462,38 @@ fn get_l2_transports(config: &[Node]) -> HashMap<String, Vec<L2TransportConfig>>
     grouped
 }
 
-fn collect_simplified_data(config: &[Node], domains: Vec<BridgeDomain>) -> SimplifiedConfigData {
+fn collect_simplified_data(
+    config: &[SpannedNode],
+    domains: Vec<BridgeDomain>,
+) -> SimplifiedConfigData {
     let mut base_interfaces: BTreeMap<String, Vec<String>> = BTreeMap::new();
     let mut bvi_interfaces: BTreeMap<String, Option<String>> = BTreeMap::new();
     let mut bundle_members: BTreeMap<String, BTreeSet<String>> = BTreeMap::new();
+    let mut encapsulations: BTreeMap<String, Encapsulation> = BTreeMap::new();
+    let mut shutdown_subinterfaces: BTreeSet<String> = BTreeSet::new();
 
     for node_block in config.iter().filter_map(|node| node.as_block()) {
         let Some(interface_name) = node_block.name.strip_prefix("interface ") else {
             continue;
         };
 
-        if interface_name.contains('.') || node_block.name.ends_with(" l2transport") {
+        // Main interfaces in l2transport mode are access ports and stay in the
+        // simplified config; subinterfaces are folded into the VLAN lists.
+        let interface_name = interface_name
+            .strip_suffix(" l2transport")
+            .unwrap_or(interface_name);
+        if interface_name.contains('.') {
+            let stmts: Vec<&str> = node_block
+                .stmts()
+                .filter_map(|x| x.as_stmt())
+                .map(|s| s.stmt())
+                .collect();
+            if let Some(encap) = Encapsulation::find(stmts.iter().copied()) {
+                encapsulations.insert(interface_name.to_string(), encap);
+            }
+            if stmts.iter().any(|stmt| stmt.trim() == "shutdown") {
+                shutdown_subinterfaces.insert(interface_name.to_string());
+            }
             continue;
         }
 
@@ -222,7 +501,7 @@ fn collect_simplified_data(config: &[Node], domains: Vec<BridgeDomain>) -> Simpl
             let description = node_block
                 .stmts()
                 .filter_map(|node| node.as_stmt())
-                .find_map(|stmt: &NodeStmt| stmt.stmt().strip_prefix("description "))
+                .find_map(|stmt: &SpannedNodeStmt| stmt.stmt().strip_prefix("description "))
                 .map(|desc| desc.trim().to_string());
             bvi_interfaces
                 .entry(interface_name.to_string())
@@ -233,7 +512,7 @@ fn collect_simplified_data(config: &[Node], domains: Vec<BridgeDomain>) -> Simpl
         let stmts = node_block
             .stmts()
             .filter_map(|node| node.as_stmt())
-            .map(|stmt: &NodeStmt| stmt.stmt().to_string())
+            .map(|stmt: &SpannedNodeStmt| stmt.stmt().to_string())
             .collect::<Vec<String>>();
 
         if !stmts.is_empty() {
@@ -250,32 +529,76 @@ fn collect_simplified_data(config: &[Node], domains: Vec<BridgeDomain>) -> Simpl
         }
     }
 
-    SimplifiedConfigData::new(domains, base_interfaces, bvi_interfaces, bundle_members)
+    SimplifiedConfigData::new(
+        domains,
+        base_interfaces,
+        bvi_interfaces,
+        bundle_members,
+        encapsulations,
+        shutdown_subinterfaces,
+    )
 }
 
-fn build_lint_output(
+/// Lint findings grouped under the header of the block they belong to,
+/// ordered by position in the source.
+fn collect_lint_findings(
+    config: &[SpannedNode],
     l2transport: &HashMap<String, Vec<L2TransportConfig>>,
     domains: &[BridgeDomain],
-) -> String {
-    let mut msg = String::new();
+) -> Vec<(String, Vec<Diagnostic>)> {
+    let mut findings = Vec::new();
+    let interfaces = configured_interfaces(config);
 
     for trans in l2transport.values().flat_map(|v| v.iter()) {
         let res = trans.lint();
         if !res.is_empty() {
-            msg.push_str(&format!(
-                "--- interface {}.{} l2transport ---\n",
-                trans.baseif, trans.sub_if_num
+            findings.push((
+                trans.span,
+                format!("interface {} l2transport", trans.name()),
+                res,
             ));
-            msg.push_str(&res.join("\n"));
-            msg.push('\n');
         }
     }
 
     for domain in domains {
-        let res = domain.lint();
+        let res = domain.lint(&interfaces);
         if !res.is_empty() {
-            msg.push_str(&format!("--- bridge-domain VLAN{} ---\n", domain.vlan_tag));
-            msg.push_str(&res.join("\n"));
+            findings.push((domain.span(), format!("bridge-domain {}", domain.name), res));
+        }
+    }
+
+    findings.sort_by_key(|(span, _, _)| span.line);
+    findings
+        .into_iter()
+        .map(|(_, header, diags)| (header, diags))
+        .collect()
+}
+
+/// Names of the interfaces configured at the top level, with or without a body.
+fn configured_interfaces(config: &[SpannedNode]) -> HashSet<String> {
+    config
+        .iter()
+        .filter_map(|node| {
+            let header = match node {
+                SpannedNode::Block(block) => block.name.as_str(),
+                SpannedNode::Stmt(stmt) => stmt.stmt(),
+            };
+            let name = header.strip_prefix("interface ")?;
+            Some(name.split_whitespace().next()?.to_string())
+        })
+        .collect()
+}
+
+fn build_lint_output(
+    findings: &[(String, Vec<Diagnostic>)],
+    catalog: &dyn MessageCatalog,
+) -> String {
+    let mut msg = String::new();
+
+    for (header, diags) in findings {
+        msg.push_str(&format!("--- {} ---\n", header));
+        for diag in diags {
+            msg.push_str(&diag.format_with(catalog));
             msg.push('\n');
         }
     }
@@ -292,9 +615,14 @@ pub struct Config {
     /// Formatted lint warnings and errors.
     #[wasm_bindgen(js_name = lintOutput)]
     pub lint_output: String,
+    /// Lint findings as structured diagnostics.
+    #[wasm_bindgen(skip)]
+    pub diagnostics: Vec<Diagnostic>,
     /// Simplified Cisco-like configuration text derived from the base config.
     #[wasm_bindgen(js_name = simplifiedConfig)]
     pub simplified_config: String,
+    /// Metadata from the `show running-config` output around the configuration.
+    pub header: ConfigHeader,
 }
 
 impl Config {
@@ -305,16 +633,345 @@ impl Config {
 }
 
 /// Analyze parsed nodes to produce lint output, bridge-domains, and simplified config.
-pub fn analyze(config: &[Node]) -> Config {
+pub fn analyze(config: &[SpannedNode]) -> Config {
+    analyze_with_catalog(config, &Japanese)
+}
+
+/// Like [`analyze`], rendering lint output with the given message catalog.
+pub fn analyze_with_catalog(config: &[SpannedNode], catalog: &dyn MessageCatalog) -> Config {
     let l2transport = get_l2_transports(config);
-    let domains = get_bridge_domains(config).unwrap_or_default();
-    let lint_output = build_lint_output(&l2transport, &domains);
+    let domains = get_bridge_domains(config, &l2transport).unwrap_or_default();
+    let findings = collect_lint_findings(config, &l2transport, &domains);
+    let lint_output = build_lint_output(&findings, catalog);
+    let diagnostics = findings.into_iter().flat_map(|(_, diags)| diags).collect();
     let simplified_data = collect_simplified_data(config, domains.clone());
     let simplified_config = build_simplified_config(&simplified_data);
 
     Config {
         domains,
         lint_output,
+        diagnostics,
         simplified_config,
+        header: ConfigHeader::default(),
+    }
+}
+
+/// Parse and analyze configuration text, keeping the metadata of pasted
+/// `show running-config` output in [`Config::header`].
+///
+/// Indentation problems found while parsing are reported before the lint
+/// findings.
+pub fn analyze_text(text: &str, catalog: &dyn MessageCatalog) -> Config {
+    let mut diags = Vec::new();
+    let cst = parse_cst_with_diagnostics(text, &mut diags);
+    let mut config = analyze_with_catalog(&lower(&cst), catalog);
+    config.header = ConfigHeader::from_cst(&cst);
+    if !diags.is_empty() {
+        let findings = [("indentation".to_string(), diags.clone())];
+        config.lint_output = build_lint_output(&findings, catalog) + &config.lint_output;
+        diags.append(&mut config.diagnostics);
+        config.diagnostics = diags;
+    }
+    config
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse::tokenize_spanned;
+
+    #[test]
+    fn lint_findings_point_at_offending_lines() {
+        let config = r#"interface FortyGigE0/0/0/46.300 l2transport
+  description servers,To:server1
+  encapsulation dot1q 301
+interface FortyGigE0/0/0/46.400 l2transport
+  encapsulation dot1q 400
+  rewrite ingress tag pop 1 symmetric
+l2vpn
+  bridge group VLAN
+    bridge-domain VLAN400
+      interface FortyGigE0/0/0/46.400
+      interface FortyGigE0/0/0/47.401
+"#;
+
+        let analysis = analyze(&tokenize_spanned(config));
+        let findings: Vec<(&str, Option<Span>)> = analysis
+            .diagnostics
+            .iter()
+            .map(|diag| (diag.kind.code(), diag.span))
+            .collect();
+
+        assert_eq!(
+            findings,
+            vec![
+                ("encapsulation-mismatch", Some(Span::new(3, 2, 25))),
+                ("missing-rewrite", Some(Span::new(1, 0, 43))),
+                ("subinterface-number-mismatch", Some(Span::new(11, 6, 37))),
+            ]
+        );
+        assert!(analysis
+            .lint_output
+            .contains("sub-interface number と encapsulation tag が一致していない: FortyGigE0/0/0/46.300（3行目）"));
+    }
+
+    #[test]
+    fn lints_routed_bvi() {
+        let config = r#"interface BVI300
+  description servers
+l2vpn
+  bridge group VLAN
+    bridge-domain VLAN300
+      routed interface BVI300
+    bridge-domain VLAN301
+      routed interface BVI310
+"#;
+
+        let analysis = analyze(&tokenize_spanned(config));
+        let findings: Vec<(&str, Option<Span>)> = analysis
+            .diagnostics
+            .iter()
+            .map(|diag| (diag.kind.code(), diag.span))
+            .collect();
+
+        assert_eq!(
+            findings,
+            vec![
+                ("bvi-number-mismatch", Some(Span::new(8, 6, 29))),
+                ("bvi-not-configured", Some(Span::new(8, 6, 29))),
+            ]
+        );
+        assert_eq!(analysis.domains[0].routed_bvi(), Some(300));
+    }
+
+    #[test]
+    fn qinq_subinterface_requires_pop_2() {
+        let config = r#"interface FortyGigE0/0/0/46
+  description To:provider
+interface FortyGigE0/0/0/46.200 l2transport
+  encapsulation dot1q 100 second-dot1q 200
+  rewrite ingress tag pop 2 symmetric
+interface FortyGigE0/0/0/46.201 l2transport
+  encapsulation dot1q 100 second-dot1q 201
+  rewrite ingress tag pop 1 symmetric
+l2vpn
+  bridge group VLAN
+    bridge-domain VLAN200
+      interface FortyGigE0/0/0/46.200
+    bridge-domain VLAN201
+      interface FortyGigE0/0/0/46.201
+"#;
+
+        let analysis = analyze(&tokenize_spanned(config));
+        let findings: Vec<String> = analysis
+            .diagnostics
+            .iter()
+            .map(|diag| diag.kind.message())
+            .collect();
+
+        assert_eq!(
+            findings,
+            vec!["rewrite ingress tag pop 2 symmetric が存在しない: FortyGigE0/0/0/46.201"]
+        );
+        assert!(analysis.simplified_config.contains(
+            "  switchport dot1q-tunnel vlan 100 inner 200\n  switchport dot1q-tunnel vlan 100 inner 201\n"
+        ));
+    }
+
+    #[test]
+    fn qinq_services_sharing_an_inner_tag_may_be_numbered_freely() {
+        let config = r#"interface FortyGigE0/0/0/46
+  description To:provider
+interface FortyGigE0/0/0/46.200 l2transport
+  encapsulation dot1q 100 second-dot1q 200
+  rewrite ingress tag pop 2 symmetric
+interface FortyGigE0/0/0/46.101 l2transport
+  encapsulation dot1q 101 second-dot1q 200
+  rewrite ingress tag pop 2 symmetric
+l2vpn
+  bridge group VLAN
+    bridge-domain VLAN200
+      interface FortyGigE0/0/0/46.200
+      interface FortyGigE0/0/0/46.101
+"#;
+
+        let analysis = analyze(&tokenize_spanned(config));
+
+        assert!(
+            analysis.diagnostics.is_empty(),
+            "{:?}",
+            analysis.diagnostics
+        );
+        assert!(analysis.simplified_config.contains(
+            "  switchport dot1q-tunnel vlan 100 inner 200\n  switchport dot1q-tunnel vlan 101 inner 200\n"
+        ));
+    }
+
+    #[test]
+    fn untagged_subinterface_is_native_vlan() {
+        let config = r#"interface FortyGigE0/0/0/46
+  description To:server1
+interface FortyGigE0/0/0/46.300 l2transport
+  encapsulation untagged
+interface FortyGigE0/0/0/46.301 l2transport
+  encapsulation dot1q 301
+  rewrite ingress tag pop 1 symmetric
+l2vpn
+  bridge group VLAN
+    bridge-domain VLAN300
+      interface FortyGigE0/0/0/46.300
+    bridge-domain VLAN301
+      interface FortyGigE0/0/0/46.301
+"#;
+
+        let analysis = analyze(&tokenize_spanned(config));
+
+        assert!(
+            analysis.diagnostics.is_empty(),
+            "{:?}",
+            analysis.diagnostics
+        );
+        assert!(analysis.simplified_config.starts_with(
+            "interface FortyGigE0/0/0/46\n  description To:server1\n  switchport trunk allowed vlan 301\n  switchport trunk native vlan 300\n"
+        ));
+    }
+
+    #[test]
+    fn discovers_domains_in_every_bridge_group() {
+        let config = r#"interface FortyGigE0/0/0/46.300 l2transport
+  encapsulation dot1q 300
+  rewrite ingress tag pop 1 symmetric
+interface FortyGigE0/0/0/47.500 l2transport
+  encapsulation dot1q 500
+  rewrite ingress tag pop 1 symmetric
+interface FortyGigE0/0/0/47.610 l2transport
+  encapsulation dot1q 600 second-dot1q 610
+  rewrite ingress tag pop 2 symmetric
+l2vpn
+ bridge group VLAN
+  bridge-domain VLAN300
+   interface FortyGigE0/0/0/46.300
+  !
+ !
+ bridge group CUSTOMER
+  bridge-domain web-servers
+   interface FortyGigE0/0/0/47.500
+  !
+  bridge-domain cust-b
+   interface FortyGigE0/0/0/47.610
+  !
+  bridge-domain empty
+  !
+ !
+!
+"#;
+
+        let analysis = analyze(&tokenize_spanned(config));
+        let domains: Vec<(&str, &str, u32)> = analysis
+            .domains
+            .iter()
+            .map(|d| (d.group.as_str(), d.name.as_str(), d.vlan_tag))
+            .collect();
+
+        assert_eq!(
+            domains,
+            vec![
+                ("VLAN", "VLAN300", 300),
+                ("CUSTOMER", "web-servers", 500),
+                ("CUSTOMER", "cust-b", 610),
+            ]
+        );
+        assert!(analysis.diagnostics.is_empty());
+        assert!(analysis
+            .simplified_config
+            .contains("switchport trunk allowed vlan 500"));
+    }
+
+    #[test]
+    fn named_domain_members_are_not_checked_against_the_name() {
+        let config = r#"interface FortyGigE0/0/0/46
+ description To:server1
+!
+interface FortyGigE0/0/0/46.500 l2transport
+ encapsulation dot1q 500
+ rewrite ingress tag pop 1 symmetric
+!
+interface FortyGigE0/0/0/46.501 l2transport
+ encapsulation dot1q 502
+ rewrite ingress tag pop 1 symmetric
+!
+l2vpn
+ bridge group CUSTOMER
+  bridge-domain web-servers
+   interface FortyGigE0/0/0/46.500
+   interface FortyGigE0/0/0/46.501
+  !
+ !
+!
+"#;
+
+        let analysis = analyze(&tokenize_spanned(config));
+        let codes: Vec<&str> = analysis
+            .diagnostics
+            .iter()
+            .map(|diag| diag.kind.code())
+            .collect();
+
+        assert_eq!(codes, vec!["encapsulation-mismatch"]);
+    }
+
+    #[test]
+    fn shows_shutdown_state_in_simplified_config() {
+        let config = r#"interface FortyGigE0/0/0/46
+ description To:server1
+ shutdown
+!
+interface FortyGigE0/0/0/46.300 l2transport
+ encapsulation dot1q 300
+ rewrite ingress tag pop 1 symmetric
+ shutdown
+!
+interface FortyGigE0/0/0/46.301 l2transport
+ encapsulation dot1q 301
+ rewrite ingress tag pop 1 symmetric
+!
+l2vpn
+ bridge group VLAN
+  bridge-domain VLAN300
+   interface FortyGigE0/0/0/46.300
+   !
+  !
+  bridge-domain VLAN301
+   interface FortyGigE0/0/0/46.301
+   !
+  !
+ !
+!
+"#;
+
+        let analysis = analyze(&tokenize_spanned(config));
+        assert!(analysis.simplified_config.starts_with(
+            "interface FortyGigE0/0/0/46\n  description To:server1\n  shutdown\n  switchport trunk allowed vlan 300-301\n  ! -- shutdown vlan 300 --\n"
+        ));
+    }
+
+    #[test]
+    fn reports_indentation_problems_before_lint_findings() {
+        let config = "interface FortyGigE0/0/0/46.300 l2transport\n    description servers\n  encapsulation dot1q 301\n  rewrite ingress tag pop 1 symmetric\n";
+        let analysis = analyze_text(config, &Japanese);
+
+        let codes: Vec<&str> = analysis.diagnostics.iter().map(|d| d.kind.code()).collect();
+        assert_eq!(
+            codes,
+            [
+                "inconsistent-indentation",
+                "inconsistent-indentation",
+                "encapsulation-mismatch"
+            ]
+        );
+        assert!(analysis.lint_output.starts_with("--- indentation ---\n"));
+        assert!(analysis
+            .lint_output
+            .contains("--- interface FortyGigE0/0/0/46.300 l2transport ---\n"));
     }
 }
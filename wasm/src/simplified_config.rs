@@ -1,8 +1,8 @@
-use crate::semantics::{split_subinterface_id, BridgeDomain};
+use crate::semantics::{split_subinterface_id, BridgeDomain, Encapsulation};
 use std::collections::{BTreeMap, BTreeSet};
 
 /// Format a sorted set of VLAN tags into IOS-style ranges (e.g., `300-305 310`).
-fn format_vlan_ranges(tags: &BTreeSet<u32>) -> String {
+pub(crate) fn format_vlan_ranges(tags: &BTreeSet<u32>) -> String {
     let mut iter = tags.iter();
     let Some(&first) = iter.next() else {
         return "-".to_string();
@@ -43,6 +43,10 @@ pub struct SimplifiedConfigData {
     pub bvi_interfaces: BTreeMap<String, Option<String>>,
     /// Bundle-Ether members keyed by bundle interface name.
     pub bundle_members: BTreeMap<String, BTreeSet<String>>,
+    /// Encapsulation of each l2transport subinterface keyed by subinterface name.
+    pub encapsulations: BTreeMap<String, Encapsulation>,
+    /// l2transport subinterfaces that are administratively down.
+    pub shutdown_subinterfaces: BTreeSet<String>,
 }
 
 impl SimplifiedConfigData {
@@ -52,12 +56,16 @@ impl SimplifiedConfigData {
         base_interfaces: BTreeMap<String, Vec<String>>,
         bvi_interfaces: BTreeMap<String, Option<String>>,
         bundle_members: BTreeMap<String, BTreeSet<String>>,
+        encapsulations: BTreeMap<String, Encapsulation>,
+        shutdown_subinterfaces: BTreeSet<String>,
     ) -> Self {
         Self {
             domains,
             base_interfaces,
             bvi_interfaces,
             bundle_members,
+            encapsulations,
+            shutdown_subinterfaces,
         }
     }
 }
@@ -66,6 +74,10 @@ impl SimplifiedConfigData {
 pub fn build_simplified_config(data: &SimplifiedConfigData) -> String {
     let mut vlan_map: BTreeMap<u32, Option<String>> = BTreeMap::new();
     let mut trunk_map: BTreeMap<String, BTreeSet<u32>> = BTreeMap::new();
+    let mut access_map: BTreeMap<String, u32> = BTreeMap::new();
+    let mut native_map: BTreeMap<String, u32> = BTreeMap::new();
+    let mut qinq_map: BTreeMap<String, BTreeSet<(u32, u32)>> = BTreeMap::new();
+    let mut shutdown_map: BTreeMap<String, BTreeSet<u32>> = BTreeMap::new();
 
     for domain in &data.domains {
         let entry = vlan_map.entry(domain.vlan_tag).or_insert(None);
@@ -74,9 +86,33 @@ pub fn build_simplified_config(data: &SimplifiedConfigData) -> String {
         }
 
         for interface in &domain.interfaces {
+            if !interface.contains('.') && !interface.starts_with("BVI") {
+                access_map.insert(interface.clone(), domain.vlan_tag);
+                continue;
+            }
             let Ok((base_interface, Some(_))) = split_subinterface_id(interface) else {
                 continue;
             };
+            if data.shutdown_subinterfaces.contains(interface) {
+                shutdown_map
+                    .entry(base_interface.clone())
+                    .or_default()
+                    .insert(domain.vlan_tag);
+            }
+            match data.encapsulations.get(interface) {
+                Some(Encapsulation::Untagged) => {
+                    native_map.insert(base_interface, domain.vlan_tag);
+                    continue;
+                }
+                Some(Encapsulation::QinQ { outer, inner }) => {
+                    qinq_map
+                        .entry(base_interface)
+                        .or_default()
+                        .insert((*outer, *inner));
+                    continue;
+                }
+                _ => {}
+            }
 
             trunk_map
                 .entry(base_interface)
@@ -87,19 +123,47 @@ pub fn build_simplified_config(data: &SimplifiedConfigData) -> String {
 
     let mut lines: Vec<String> = Vec::new();
 
-    if !trunk_map.is_empty() {
-        for (base_interface, vlan_tags) in trunk_map {
-            let vlan_list = format_vlan_ranges(&vlan_tags);
+    let ports: BTreeSet<&String> = trunk_map
+        .keys()
+        .chain(access_map.keys())
+        .chain(native_map.keys())
+        .chain(qinq_map.keys())
+        .collect();
+    if !ports.is_empty() {
+        for base_interface in ports {
             lines.push(format!("interface {}", base_interface));
-            if let Some(stmts) = data.base_interfaces.get(&base_interface) {
+            if let Some(stmts) = data.base_interfaces.get(base_interface) {
                 for stmt in stmts {
                     lines.push(format!("  {}", stmt));
                 }
             }
-            lines.push(format!("  switchport trunk allowed vlan {}", vlan_list));
+            if let Some(vlan_tag) = access_map.get(base_interface) {
+                lines.push("  switchport mode access".to_string());
+                lines.push(format!("  switchport access vlan {}", vlan_tag));
+            } else {
+                if let Some(vlan_tags) = trunk_map.get(base_interface) {
+                    let vlan_list = format_vlan_ranges(vlan_tags);
+                    lines.push(format!("  switchport trunk allowed vlan {}", vlan_list));
+                }
+                if let Some(vlan_tag) = native_map.get(base_interface) {
+                    lines.push(format!("  switchport trunk native vlan {}", vlan_tag));
+                }
+                for (outer, inner) in qinq_map.get(base_interface).into_iter().flatten() {
+                    lines.push(format!(
+                        "  switchport dot1q-tunnel vlan {} inner {}",
+                        outer, inner
+                    ));
+                }
+            }
+            if let Some(vlan_tags) = shutdown_map.get(base_interface) {
+                lines.push(format!(
+                    "  ! -- shutdown vlan {} --",
+                    format_vlan_ranges(vlan_tags)
+                ));
+            }
             lines.push(String::new());
 
-            if let Some(members) = data.bundle_members.get(&base_interface) {
+            if let Some(members) = data.bundle_members.get(base_interface) {
                 for member in members {
                     lines.push(format!("interface {}", member));
                     if let Some(stmts) = data.base_interfaces.get(member) {